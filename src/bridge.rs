@@ -0,0 +1,561 @@
+// Run the connection in a Web Worker and use it from another context over a MessagePort.
+// See WsStreamRemote for the overview.
+//
+use
+{
+	crate   :: { import::*, WsErr, WsErrKind, WsMessage, WsState, WsIo, WsStream, CloseEvent } ,
+	futures :: { channel::mpsc::unbounded, future::{ pending, poll_fn, Either }, SinkExt       } ,
+	js_sys  :: { Object, Reflect                                                             } ,
+};
+
+
+// How many messages and errors the worker may send before waiting for more credit.
+//
+const CREDIT: u32 = 64;
+
+
+// The control protocol. Every message over the port is an object with a type in the `t` property.
+//
+// worker -> remote: open { url, protocol }, msg { d }, error { e }, close { code, reason, clean }
+// remote -> worker: send { d }, credit { n }, close { code, reason }
+//
+pub(crate) fn control( kind: &str ) -> Object
+{
+	let obj = Object::new();
+
+	set( &obj, "t", &kind.into() );
+
+	obj
+}
+
+
+// Reflect::set only throws when the target isn't an object, and we only call this on objects we created.
+//
+pub(crate) fn set( obj: &Object, key: &str, value: &JsValue )
+{
+	Reflect::set( obj, &key.into(), value ).expect_throw( "set property on object" );
+}
+
+
+pub(crate) fn get( obj: &JsValue, key: &str ) -> JsValue
+{
+	Reflect::get( obj, &key.into() ).unwrap_or( JsValue::UNDEFINED )
+}
+
+
+pub(crate) fn get_u16( obj: &JsValue, key: &str ) -> u16
+{
+	get( obj, key ).as_f64().unwrap_or( 0.0 ) as u16
+}
+
+
+pub(crate) fn get_string( obj: &JsValue, key: &str ) -> String
+{
+	get( obj, key ).as_string().unwrap_or_default()
+}
+
+
+fn post( port: &MessagePort, obj: &Object ) -> Result<(), WsErr>
+{
+	port.post_message( obj ).map_err( |_| WsErrKind::ConnectionNotOpen.into() )
+}
+
+
+// Post a message, transferring the buffer for binary data.
+//
+fn post_data( port: &MessagePort, kind: &str, msg: WsMessage ) -> Result<(), WsErr>
+{
+	let obj = control( kind );
+
+	match msg
+	{
+		WsMessage::Text( s ) =>
+		{
+			set( &obj, "d", &s.into() );
+			post( port, &obj )
+		}
+
+		WsMessage::Binary(_) =>
+		{
+			let buf = msg.to_array_buffer();
+
+			set( &obj, "d", &buf );
+
+			port.post_message_with_transferable( &obj, &Array::of1( &buf ) )
+
+				.map_err( |_| WsErrKind::ConnectionNotOpen.into() )
+		}
+	}
+}
+
+
+
+// Listens to messages on a port, for as long as this lives.
+//
+struct PortListener
+{
+	port     : MessagePort                                 ,
+	_on_mesg : Closure< dyn FnMut( MessageEvent ) + 'static > ,
+}
+
+
+impl PortListener
+{
+	fn new( port: MessagePort, f: impl FnMut( JsValue ) + 'static ) -> Self
+	{
+		let mut f = f;
+
+		let on_mesg = Closure::wrap( Box::new( move |evt: MessageEvent| f( evt.data() ) ) as Box< dyn FnMut( MessageEvent ) > );
+
+		// Setting onmessage also starts the port, so messages that were sent before are delivered now.
+		//
+		port.set_onmessage( Some( on_mesg.as_ref().unchecked_ref() ) );
+
+		Self { port, _on_mesg: on_mesg }
+	}
+}
+
+
+impl Drop for PortListener
+{
+	fn drop( &mut self )
+	{
+		self.port.set_onmessage( None );
+	}
+}
+
+
+
+/// Forward a connection over a `MessagePort`, usually in a Web Worker. The other end of the port should
+/// be given to [WsStreamRemote::connect]. This resolves when the connection is closed, after the
+/// close event has been forwarded.
+///
+/// Errors on incoming messages are forwarded to the remote as strings.
+//
+pub async fn serve_port( ws: WsStream, mut wsio: WsIo, port: MessagePort )
+{
+	enum Step
+	{
+		Incoming( Option< Result<WsMessage, WsErr> > ),
+		Command ( Option< JsValue >                  ),
+		Closed  ( CloseEvent                         ),
+	}
+
+
+	let (tx, mut commands) = unbounded();
+
+	let _listener = PortListener::new( port.clone(), move |data| { let _ = tx.unbounded_send( data ); } );
+
+	let mut closed = Box::pin( ws.closed() );
+	let mut credit = 0;
+
+
+	let open = control( "open" );
+
+	set( &open, "url"     , &ws.url()     .into() );
+	set( &open, "protocol", &ws.protocol().into() );
+
+	if post( &port, &open ).is_err() { return }
+
+
+	loop
+	{
+		// Only read from the connection when the remote can take more.
+		//
+		let incoming = match credit
+		{
+			0 => Either::Left ( pending() ),
+			_ => Either::Right( wsio.next() ),
+		};
+
+		let step = match select( select( incoming, commands.next() ), &mut closed ).await
+		{
+			Either::Left(( Either::Left (( msg, _ )), _ )) => Step::Incoming( msg ),
+			Either::Left(( Either::Right(( cmd, _ )), _ )) => Step::Command ( cmd ),
+			Either::Right(( evt, _ ))                      => Step::Closed  ( evt ),
+		};
+
+
+		match step
+		{
+			// Errors are items of the remote stream as well, so they take credit like messages.
+			//
+			Step::Incoming( Some( item ) ) =>
+			{
+				credit -= 1;
+
+				forward( &port, item );
+			}
+
+			// The stream ended, so the connection is closing. Wait for the close event.
+			//
+			Step::Incoming( None ) => credit = 0,
+
+			Step::Command( Some( cmd ) ) => match get_string( &cmd, "t" ).as_str()
+			{
+				"credit" => credit += get( &cmd, "n" ).as_f64().unwrap_or( 0.0 ) as u32,
+
+				"send" => match WsMessage::try_from( get( &cmd, "d" ) )
+				{
+					Ok ( msg ) => { let _ = wsio.send( msg ).await; }
+					Err( e   ) => error!( "serve_port: can't send: {}", e ),
+				}
+
+				"close" =>
+				{
+					let _ = ws.wrapped().close_with_code_and_reason( get_u16( &cmd, "code" ), &get_string( &cmd, "reason" ) );
+				}
+
+				other => error!( "serve_port: unknown command: {}", other ),
+			}
+
+			Step::Command( None ) => {}
+
+			Step::Closed( evt ) =>
+			{
+				// Messages that arrived before the close can still be queued in the WsIo when the remote had no
+				// credit left. The remote won't ask for any after the close, so send them regardless.
+				//
+				while let Some( item ) = wsio.next().await
+				{
+					forward( &port, item );
+				}
+
+				let close = control( "close" );
+
+				set( &close, "code"  , &evt.code     .into() );
+				set( &close, "reason", &evt.reason   .into() );
+				set( &close, "clean" , &evt.was_clean.into() );
+
+				let _ = post( &port, &close );
+
+				return;
+			}
+		}
+	}
+}
+
+
+
+// Post an incoming message, or the error in its place, to the remote.
+//
+fn forward( port: &MessagePort, item: Result<WsMessage, WsErr> )
+{
+	match item
+	{
+		Ok( msg ) => { let _ = post_data( port, "msg", msg ); }
+
+		Err( e ) =>
+		{
+			let err = control( "error" );
+
+			set( &err, "e", &e.to_string().into() );
+
+			let _ = post( port, &err );
+		}
+	}
+}
+
+
+
+#[ derive( Default ) ]
+//
+struct RemoteState
+{
+	queue      : VecDeque< Result<WsMessage, WsErr> >,
+	consumed   : u32                                ,
+	opened     : bool                               ,
+	closing    : bool                               ,
+	close_evt  : Option< CloseEvent >               ,
+	url        : String                             ,
+	protocol   : String                             ,
+	waker      : Option< Waker >                    ,
+	state_waker: Vec< Waker >                       ,
+}
+
+
+impl RemoteState
+{
+	fn wake( &mut self )
+	{
+		if let Some( w ) = self.waker.take() { w.wake() }
+
+		for w in self.state_waker.drain(..) { w.wake() }
+	}
+}
+
+
+
+/// The remote end of a connection forwarded with [serve_port]. This implements `Stream` and `Sink<WsMessage>`
+/// like [WsIo](crate::WsIo). Errors that happen in the worker are returned as [WsErrKind::Remote].
+///
+/// This lets you run the connection, and the decoding of messages, in a Web Worker. In the worker, connect as usual
+/// and hand the connection to [serve_port]. Give the other end of the `MessagePort` to [WsStreamRemote::connect].
+///
+/// Binary messages are sent as transferable `ArrayBuffer`s, so they are not copied between the contexts.
+/// The worker only forwards messages and errors when the remote end has credit left. The remote grants credit as it
+/// consumes messages, so a slow consumer makes messages accumulate in the worker rather than in the
+/// message queue of the port.
+//
+pub struct WsStreamRemote
+{
+	port     : MessagePort              ,
+	state    : Rc<RefCell< RemoteState >>,
+	_listener: PortListener             ,
+}
+
+
+
+impl WsStreamRemote
+{
+	/// Start using the connection on the other end of `port`. Resolves once [serve_port] has started. If the
+	/// connection closed before that, [WsErrKind::ConnectionFailed] is returned.
+	//
+	pub async fn connect( port: MessagePort ) -> Result< Self, WsErr >
+	{
+		let state  = Rc::new( RefCell::new( RemoteState::default() ) );
+		let state2 = state.clone();
+
+		let listener = PortListener::new( port.clone(), move |data|
+		{
+			let mut state = state2.borrow_mut();
+
+			match get_string( &data, "t" ).as_str()
+			{
+				"open" =>
+				{
+					state.opened   = true;
+					state.url      = get_string( &data, "url"      );
+					state.protocol = get_string( &data, "protocol" );
+				}
+
+				"msg"   => { let msg = WsMessage::try_from( get( &data, "d" ) ); state.queue.push_back( msg ); }
+				"error" => state.queue.push_back( Err( WsErrKind::Remote( get_string( &data, "e" ) ).into() ) ),
+
+				"close" =>
+				{
+					state.close_evt = Some( CloseEvent
+					{
+						code     : get_u16   ( &data, "code"   ),
+						reason   : get_string( &data, "reason" ),
+						was_clean: get( &data, "clean" ).as_bool().unwrap_or( false ),
+					});
+				}
+
+				other => error!( "WsStreamRemote: unknown message: {}", other ),
+			}
+
+			state.wake();
+		});
+
+
+		let credit = control( "credit" );
+
+		set( &credit, "n", &CREDIT.into() );
+
+		post( &port, &credit )?;
+
+
+		let remote = Self { port, state, _listener: listener };
+
+		poll_fn( |cx|
+		{
+			let mut state = remote.state.borrow_mut();
+
+			if let Some( evt ) = &state.close_evt
+			{
+				if !state.opened { return Poll::Ready( Err( WsErr::from( WsErrKind::ConnectionFailed( evt.clone() ) ) ) ) }
+			}
+
+			if state.opened { return Poll::Ready( Ok(()) ) }
+
+			state.state_waker.push( cx.waker().clone() );
+
+			Poll::Pending
+
+		}).await?;
+
+		Ok( remote )
+	}
+
+
+
+	/// The state of the connection, as far as we know here.
+	//
+	pub fn ready_state( &self ) -> WsState
+	{
+		let state = self.state.borrow();
+
+		if      state.close_evt.is_some() { WsState::Closed  }
+		else if state.closing             { WsState::Closing }
+		else                              { WsState::Open    }
+	}
+
+
+
+	/// The url of the connection.
+	//
+	pub fn url( &self ) -> String
+	{
+		self.state.borrow().url.clone()
+	}
+
+
+
+	/// The subprotocol the server selected.
+	//
+	pub fn protocol( &self ) -> String
+	{
+		self.state.borrow().protocol.clone()
+	}
+
+
+
+	/// Close the connection with code 1000. Resolves with the close event once the connection is closed.
+	//
+	pub async fn close( &self ) -> CloseEvent
+	{
+		self.close_reason( 1000, "" ).await
+	}
+
+
+
+	/// Close the connection with a code and reason. Resolves with the close event once the connection is closed.
+	/// If the code or reason is refused by the browser, the connection stays open and the error is
+	/// reported in the worker.
+	//
+	pub async fn close_reason( &self, code: u16, reason: impl AsRef<str> ) -> CloseEvent
+	{
+		self.start_close( code, reason.as_ref() );
+
+		poll_fn( |cx| self.poll_closed( cx ) ).await
+	}
+
+
+
+	fn start_close( &self, code: u16, reason: &str )
+	{
+		let mut state = self.state.borrow_mut();
+
+		if state.closing || state.close_evt.is_some() { return }
+
+		state.closing = true;
+
+		let close = control( "close" );
+
+		set( &close, "code"  , &code  .into() );
+		set( &close, "reason", &reason.into() );
+
+		let _ = post( &self.port, &close );
+	}
+
+
+
+	fn poll_closed( &self, cx: &mut Context ) -> Poll<CloseEvent>
+	{
+		let mut state = self.state.borrow_mut();
+
+		if let Some( evt ) = &state.close_evt { return Poll::Ready( evt.clone() ) }
+
+		state.state_waker.push( cx.waker().clone() );
+
+		Poll::Pending
+	}
+}
+
+
+
+impl fmt::Debug for WsStreamRemote
+{
+	fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result
+	{
+		write!( f, "WsStreamRemote for connection: {}", self.url() )
+	}
+}
+
+
+
+impl Stream for WsStreamRemote
+{
+	type Item = Result< WsMessage, WsErr >;
+
+
+	fn poll_next( self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Option< Self::Item >>
+	{
+		let mut state = self.state.borrow_mut();
+
+		if let Some( msg ) = state.queue.pop_front()
+		{
+			// Give back credit in batches, so we don't need a message for every message.
+			//
+			state.consumed += 1;
+
+			if state.consumed >= CREDIT / 2
+			{
+				let credit = control( "credit" );
+
+				set( &credit, "n", &state.consumed.into() );
+
+				let _ = post( &self.port, &credit );
+
+				state.consumed = 0;
+			}
+
+			return Poll::Ready( Some( msg ) );
+		}
+
+
+		if state.close_evt.is_some() { return Poll::Ready( None ) }
+
+		match &state.waker
+		{
+			Some( w ) if w.will_wake( cx.waker() ) => {}
+			_ => state.waker = Some( cx.waker().clone() ),
+		}
+
+		Poll::Pending
+	}
+}
+
+
+
+impl Sink<WsMessage> for WsStreamRemote
+{
+	type Error = WsErr;
+
+
+	fn poll_ready( self: Pin<&mut Self>, _: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		match self.ready_state()
+		{
+			WsState::Open => Poll::Ready( Ok(()) ),
+			_             => Poll::Ready( Err( WsErrKind::ConnectionNotOpen.into() ) ),
+		}
+	}
+
+
+	fn start_send( self: Pin<&mut Self>, item: WsMessage ) -> Result<(), Self::Error>
+	{
+		match self.ready_state()
+		{
+			WsState::Open => post_data( &self.port, "send", item ),
+			_             => Err( WsErrKind::ConnectionNotOpen.into() ),
+		}
+	}
+
+
+	fn poll_flush( self: Pin<&mut Self>, _: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		Poll::Ready( Ok(()) )
+	}
+
+
+	fn poll_close( self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		self.start_close( 1000, "" );
+
+		let _ = ready!( self.poll_closed( cx ) );
+
+		Poll::Ready( Ok(()) )
+	}
+}
@@ -0,0 +1,232 @@
+use
+{
+	crate   :: { import::*                  } ,
+	futures :: { channel::mpsc::unbounded   } ,
+};
+
+
+/// Keeps an event listener installed with `addEventListener`. Dropping it removes the listener and drops the closure.
+/// Obtained with [listen].
+//
+pub struct ListenerGuard
+{
+	inner: Rc< Listener >,
+}
+
+
+
+impl ListenerGuard
+{
+	// Install a listener that calls `callback`. This is what the crate uses for it's own listeners.
+	//
+	pub(crate) fn new( target: &EventTarget, event: &str, callback: impl FnMut( Event ) + 'static ) -> Self
+	{
+		Self { inner: Listener::new( target, event, callback ) }
+	}
+}
+
+
+
+impl fmt::Debug for ListenerGuard
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		write!( f, "ListenerGuard for event: {}", self.inner.event )
+	}
+}
+
+
+
+impl Drop for ListenerGuard
+{
+	fn drop( &mut self )
+	{
+		self.inner.remove();
+	}
+}
+
+
+
+/// The events received by a listener installed with [listen]. The stream ends when the [ListenerGuard] is dropped.
+/// Dropping the stream removes the listener as well.
+//
+pub struct EventStream
+{
+	rx   : UnboundedReceiver< Event >,
+	inner: Rc< Listener >            ,
+}
+
+
+
+impl Stream for EventStream
+{
+	type Item = Event;
+
+	fn poll_next( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll<Option< Self::Item >>
+	{
+		Pin::new( &mut self.rx ).poll_next( cx )
+	}
+}
+
+
+
+impl fmt::Debug for EventStream
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		write!( f, "EventStream for event: {}", self.inner.event )
+	}
+}
+
+
+
+impl Drop for EventStream
+{
+	fn drop( &mut self )
+	{
+		self.inner.remove();
+	}
+}
+
+
+
+/// Listen to `event` on `target` with `addEventListener`, rather than by setting an `onxxx` property, so other
+/// listeners on the same object are not affected.
+///
+/// The listener stays installed until either the [ListenerGuard] or the [EventStream] is dropped. Events are
+/// buffered without limit until they are read from the stream.
+//
+pub fn listen( target: &EventTarget, event: &str ) -> ( EventStream, ListenerGuard )
+{
+	let ( tx, rx ) = unbounded();
+	let inner      = Listener::new( target, event, move |evt| { let _ = tx.unbounded_send( evt ); } );
+
+	( EventStream { rx, inner: inner.clone() }, ListenerGuard { inner } )
+}
+
+
+
+/// A future that resolves with the next `event` on `target`. The listener is installed right away, so events that
+/// happen before the future is polled are not missed. It's removed when the future resolves or is dropped.
+//
+pub fn once( target: &EventTarget, event: &str ) -> impl Future< Output = Event >
+{
+	let state  = Rc::new( RefCell::new( OnceState::default() ) );
+	let state2 = state.clone();
+
+	// Later events are ignored. The closure keeps the state alive, so it is never freed under it, even
+	// if the browser calls it after the future was dropped.
+	//
+	let guard = ListenerGuard::new( target, event, move |evt|
+	{
+		let mut state = state2.borrow_mut();
+
+		if state.fired { return }
+
+		state.fired = true;
+		state.evt   = Some( evt );
+
+		if let Some( w ) = state.waker.take() { w.wake() }
+	});
+
+	Once { state, guard: Some( guard ) }
+}
+
+
+
+#[ derive( Default ) ]
+//
+struct OnceState
+{
+	fired: bool           ,
+	evt  : Option< Event >,
+	waker: Option< Waker >,
+}
+
+
+
+// The future returned by `once`. Dropping it drops the guard, which removes the listener.
+//
+struct Once
+{
+	state: Rc<RefCell< OnceState >>,
+	guard: Option< ListenerGuard > ,
+}
+
+
+
+impl Future for Once
+{
+	type Output = Event;
+
+	fn poll( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Self::Output >
+	{
+		let evt = self.state.borrow_mut().evt.take();
+
+		match evt
+		{
+			Some( evt ) =>
+			{
+				self.guard = None;
+				Poll::Ready( evt )
+			}
+
+			None =>
+			{
+				self.state.borrow_mut().waker = Some( cx.waker().clone() );
+				Poll::Pending
+			}
+		}
+	}
+}
+
+
+
+type Callback = Closure< dyn FnMut( Event ) >;
+
+
+
+// The listener shared by the guard and the stream. It can be removed by either of them.
+//
+struct Listener
+{
+	target : EventTarget                  ,
+	event  : String                       ,
+	closure: RefCell< Option< Callback > >,
+}
+
+
+
+impl Listener
+{
+	fn new( target: &EventTarget, event: &str, callback: impl FnMut( Event ) + 'static ) -> Rc<Self>
+	{
+		let closure = Closure::wrap( Box::new( callback ) as Box< dyn FnMut( Event ) > );
+
+		// This only throws if the callback isn't a function.
+		//
+		if target.add_event_listener_with_callback( event, closure.as_ref().unchecked_ref() ).is_err()
+		{
+			error!( "failed to add listener for event: {}", event );
+		}
+
+		Rc::new( Self
+		{
+			target : target.clone()                ,
+			event  : event.to_string()             ,
+			closure: RefCell::new( Some( closure ) ),
+		})
+	}
+
+
+	// Remove the listener. Dropping the closure drops the sender of the stream, so it ends.
+	// Does nothing if it was already removed.
+	//
+	fn remove( &self )
+	{
+		if let Some( closure ) = self.closure.borrow_mut().take()
+		{
+			let _ = self.target.remove_event_listener_with_callback( &self.event, closure.as_ref().unchecked_ref() );
+		}
+	}
+}
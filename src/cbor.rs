@@ -0,0 +1,43 @@
+use
+{
+	crate :: { WsDecode, WsEncode, WsErr, WsErrKind, WsMessage } ,
+	serde :: { de::DeserializeOwned, Serialize                 } ,
+};
+
+
+/// A codec for [WsChannel](crate::WsChannel) that serializes any serde type as CBOR in binary messages.
+/// Requires the `cbor` feature.
+///
+/// Text messages can't be decoded and yield [WsErrKind::Decode].
+//
+#[ derive( Debug, Clone, Copy, Default, PartialEq, Eq ) ]
+//
+pub struct CborCodec;
+
+
+
+impl<T> WsEncode<T> for CborCodec where T: Serialize
+{
+	fn encode( &mut self, item: &T ) -> Result<WsMessage, WsErr>
+	{
+		serde_cbor::to_vec( item )
+
+			.map( WsMessage::Binary )
+			.map_err( |e| WsErrKind::Encode( e.to_string() ).into() )
+	}
+}
+
+
+
+impl<T> WsDecode<T> for CborCodec where T: DeserializeOwned
+{
+	fn decode( &mut self, msg: WsMessage ) -> Result<T, WsErr>
+	{
+		match msg
+		{
+			WsMessage::Binary( b ) => serde_cbor::from_slice( &b ).map_err( |e| WsErrKind::Decode( e.to_string() ).into() ),
+
+			WsMessage::Text( _ ) => Err( WsErrKind::Decode( "CborCodec can't decode text messages".to_string() ).into() ),
+		}
+	}
+}
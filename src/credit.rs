@@ -0,0 +1,164 @@
+use
+{
+	crate :: { import::*, WsErr, WsMessage, WsSender } ,
+};
+
+
+/// The client side of a credit based flow control scheme. The WebSocket protocol has no way to ask the server to slow
+/// down, so some servers only send a message if the client granted them credit for it. This wraps the stream of incoming
+/// messages, like [WsIo](crate::WsIo), and grants new credit as the messages are consumed.
+///
+/// At most `window` messages are ever outstanding: sent by the server but not yet taken from this stream, whether they
+/// are still on the network or waiting in the queue of the connection. Set it to the number of messages you are willing
+/// to buffer. When the outstanding credit drops to `low_water`, the credit is topped up to `window` again by sending the
+/// message produced by `encode( n )`, where `n` is the number of new credits. The first grant is sent when the stream is
+/// first polled.
+///
+/// Every item of the underlying stream counts as a message from the server, including errors, since [WsIo](crate::WsIo)
+/// only yields errors for messages it received but couldn't deliver, eg. because they were too big. Messages dropped by a
+/// [WsInterceptor](crate::WsInterceptor) are never seen here, so they don't give back their credit.
+///
+/// Grants are sent with the [WsSender], so they go through the interceptors and obey [WsConfig::max_send_size](crate::WsConfig::max_send_size).
+/// If sending a grant fails, the error is logged and the stream continues. This normally only happens when the
+/// connection is closing, after which the stream will end.
+///
+/// ```no_run
+/// # #![ feature( async_await ) ]
+/// use { ws_stream_wasm::*, futures::prelude::* };
+///
+/// # async fn example() -> Result<(), WsErr> {
+/// let ( _ws, wsio ) = WsStream::connect( "ws://127.0.0.1:3012" ).await?;
+/// let sender        = wsio.sender();
+///
+/// let mut incoming = CreditFlow::new( wsio, sender, 32, 8, |n| WsMessage::Text( format!( "{{\"credit\":{}}}", n ) ) );
+///
+/// while let Some( msg ) = incoming.next().await
+/// {
+///    println!( "{:?}, the server can send {} more", msg, incoming.outstanding() );
+/// }
+/// # Ok(())
+/// # }
+/// ```
+//
+pub struct CreditFlow<S>
+{
+	inner    : S                                    ,
+	sender   : WsSender                             ,
+	encode   : Box< dyn Fn( u32 ) -> WsMessage >    ,
+	window   : u32                                  ,
+	low_water: u32                                  ,
+	granted  : u64                                  ,
+	consumed : u64                                  ,
+}
+
+
+
+impl<S> CreditFlow<S> where S: Stream< Item = Result<WsMessage, WsErr> > + Unpin
+{
+	/// Wrap `inner`, sending grants with `sender`. A `low_water` of `window` or more is treated as `window - 1`, so
+	/// we don't send a grant for every message.
+	//
+	pub fn new( inner: S, sender: WsSender, window: u32, low_water: u32, encode: impl Fn( u32 ) -> WsMessage + 'static ) -> Self
+	{
+		let low_water = cmp::min( low_water, window.saturating_sub( 1 ) );
+
+		Self { inner, sender, encode: Box::new( encode ), window, low_water, granted: 0, consumed: 0 }
+	}
+
+
+	/// The number of messages the server may still send without us granting more: credit it didn't use yet plus
+	/// messages we haven't consumed. This is never more than `window`, unless the server sends more than it's allowed.
+	//
+	pub fn outstanding( &self ) -> u32
+	{
+		self.granted.saturating_sub( self.consumed ) as u32
+	}
+
+
+	/// The total amount of credit granted so far.
+	//
+	pub fn granted( &self ) -> u64
+	{
+		self.granted
+	}
+
+
+	/// The number of messages taken from this stream so far.
+	//
+	pub fn consumed( &self ) -> u64
+	{
+		self.consumed
+	}
+
+
+	/// Get the underlying stream back.
+	//
+	pub fn into_inner( self ) -> S
+	{
+		self.inner
+	}
+
+
+	// Top up the credit if it's at or below the low water mark. If the sender isn't ready, we try again
+	// next time we are polled.
+	//
+	fn grant( &mut self, cx: &mut Context )
+	{
+		let outstanding = self.outstanding();
+
+		if outstanding > self.low_water || outstanding >= self.window { return }
+
+		let n = self.window - outstanding;
+
+		let res = match Pin::new( &mut &self.sender ).poll_ready( cx )
+		{
+			Poll::Pending       => return,
+			Poll::Ready( res  ) => res.and_then( |_| Pin::new( &mut &self.sender ).start_send( ( self.encode )( n ) ) ),
+		};
+
+		match res
+		{
+			Ok (_) => self.granted += u64::from( n ),
+			Err(e) => error!( "CreditFlow: failed to grant credit: {}", e ),
+		}
+	}
+}
+
+
+
+impl<S> Stream for CreditFlow<S> where S: Stream< Item = Result<WsMessage, WsErr> > + Unpin
+{
+	type Item = Result<WsMessage, WsErr>;
+
+
+	fn poll_next( mut self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Option< Self::Item >>
+	{
+		self.grant( cx );
+
+		let item = ready!( Pin::new( &mut self.inner ).poll_next( cx ) );
+
+		if item.is_some()
+		{
+			self.consumed += 1;
+			self.grant( cx );
+		}
+
+		Poll::Ready( item )
+	}
+}
+
+
+
+impl<S> fmt::Debug for CreditFlow<S>
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		f.debug_struct( "CreditFlow" )
+
+			.field( "window"   , &self.window    )
+			.field( "low_water", &self.low_water )
+			.field( "granted"  , &self.granted   )
+			.field( "consumed" , &self.consumed  )
+			.finish()
+	}
+}
@@ -1,4 +1,9 @@
-use crate::{ import::*, CloseEvent };
+use
+{
+	crate  :: { import::*, CloseEvent, WsMessage                          } ,
+	js_sys :: { Object, Reflect                                           } ,
+	std    :: { collections::HashMap, sync::atomic::{ AtomicU64, Ordering } } ,
+};
 
 /// The error type for errors happening in `ws_stream_wasm`.
 ///
@@ -9,6 +14,58 @@ use crate::{ import::*, CloseEvent };
 pub struct WsErr
 {
 	inner: FailContext<WsErrKind>,
+
+	// The exception of the browser this error comes from, see WsErr::js_error.
+	//
+	cause: Option<JsCause>,
+}
+
+
+
+// A JsValue isn't Send, but a WsErr has to be, so the exceptions of the browser are kept in a table on the thread
+// that caught them and the error only holds the key. Keys are unique over all threads, so on another thread
+// the exception is just not found.
+//
+thread_local!
+{
+	static CAUSES: RefCell< HashMap<u64, JsValue> > = RefCell::new( HashMap::new() );
+}
+
+static NEXT_CAUSE: AtomicU64 = AtomicU64::new( 0 );
+
+
+#[ derive( Debug ) ]
+//
+struct JsCause( u64 );
+
+
+impl JsCause
+{
+	fn new( value: JsValue ) -> Self
+	{
+		let key = NEXT_CAUSE.fetch_add( 1, Ordering::Relaxed );
+
+		CAUSES.with( |c| c.borrow_mut().insert( key, value ) );
+
+		Self( key )
+	}
+
+
+	fn get( &self ) -> Option<JsValue>
+	{
+		CAUSES.with( |c| c.borrow().get( &self.0 ).cloned() )
+	}
+}
+
+
+impl Drop for JsCause
+{
+	fn drop( &mut self )
+	{
+		// The table can already be gone when the thread is shutting down.
+		//
+		let _ = CAUSES.try_with( |c| c.borrow_mut().remove( &self.0 ) );
+	}
 }
 
 
@@ -63,6 +120,157 @@ pub enum WsErrKind
 	#[ fail( display = "Failed to connect to the server. CloseEvent: {:?}", _0 ) ]
 	//
 	ConnectionFailed(CloseEvent),
+
+	/// The server did not select any of the requested subprotocols, see [WsConfig::require_protocol](crate::WsConfig::require_protocol).
+	/// `got` is the protocol the server selected, usually an empty string.
+	///
+	#[ fail( display = "The server did not accept any of the requested subprotocols {:?}, got: {:?}", requested, got ) ]
+	//
+	ProtocolNegotiationFailed
+	{
+		/// The subprotocols we asked for.
+		//
+		requested: Vec<String>,
+
+		/// The subprotocol selected by the server.
+		//
+		got: String,
+	},
+
+	/// An operation did not complete in time, see [WsSender::send_timeout](crate::WsSender::send_timeout).
+	///
+	#[ fail( display = "The operation timed out." ) ]
+	//
+	Timeout,
+
+	/// The connection attempt was cancelled through the `AbortHandle` returned by
+	/// [WsStream::connect_abortable](crate::WsStream::connect_abortable).
+	///
+	#[ fail( display = "The connection attempt was aborted." ) ]
+	//
+	ConnectAborted,
+
+	/// None of the urls given to [WsStream::connect_race](crate::WsStream::connect_race) could be connected to.
+	/// Contains the error for each url, in the order in which they failed.
+	///
+	#[ fail( display = "All connection attempts failed: {:?}", _0 ) ]
+	//
+	AllConnectionsFailed( Vec<( String, WsErrKind )> ),
+
+	/// A binary message could not be converted to text because it's not valid UTF-8. See
+	/// [WsConfig::binary_to_text](crate::WsConfig::binary_to_text).
+	///
+	#[ fail( display = "The message is not valid UTF-8." ) ]
+	//
+	InvalidUtf8,
+
+	/// A binary message was received on a [TextOnly](crate::TextOnly) stream with [Mismatch::Error](crate::Mismatch::Error).
+	///
+	#[ fail( display = "Received a binary message where only text was expected." ) ]
+	//
+	UnexpectedBinary,
+
+	/// A text message was received on a [BinaryOnly](crate::BinaryOnly) stream with [Mismatch::Error](crate::Mismatch::Error).
+	///
+	#[ fail( display = "Received a text message where only binary was expected." ) ]
+	//
+	UnexpectedText,
+
+	/// A JavaScript value could not be converted to a [WsMessage](crate::WsMessage), because it's not a string,
+	/// an `ArrayBuffer` or a view on one. Contains a description of the value.
+	///
+	#[ fail( display = "Can't convert JavaScript value to WsMessage: {}", _0 ) ]
+	//
+	UnsupportedData( String ),
+
+	/// An error happened on the other end of a [WsStreamRemote](crate::WsStreamRemote). Contains the error message.
+	///
+	#[ fail( display = "Error in the remote connection: {}", _0 ) ]
+	//
+	Remote( String ),
+
+	/// An item could not be encoded by the codec of a [WsChannel](crate::WsChannel). Contains a description of the problem.
+	///
+	#[ fail( display = "Failed to encode item: {}", _0 ) ]
+	//
+	Encode( String ),
+
+	/// A message could not be decoded by the codec of a [WsChannel](crate::WsChannel). Contains a description of the problem.
+	///
+	#[ fail( display = "Failed to decode message: {}", _0 ) ]
+	//
+	Decode( String ),
+
+	/// A [SendBuffer](crate::SendBuffer) with [Overflow::Error](crate::Overflow::Error) is full, or a message
+	/// is bigger than its byte limit.
+	///
+	#[ fail( display = "The send buffer is full." ) ]
+	//
+	SendBufferFull,
+
+	/// A message sent to a [Replay](crate::Replay) doesn't match the recording.
+	///
+	#[ fail( display = "The message sent doesn't match the recording. Expected: {:?}, got: {:?}", expected, got ) ]
+	//
+	ReplayMismatch
+	{
+		/// The recorded message, `None` when all recorded messages were already sent.
+		//
+		expected: Option<WsMessage>,
+
+		/// The message that was sent.
+		//
+		got: WsMessage,
+	},
+
+	/// Nothing was received within the timeout of the [Heartbeat](crate::Heartbeat). The connection is closed.
+	///
+	#[ fail( display = "The connection timed out: no message received within the heartbeat timeout." ) ]
+	//
+	HeartbeatTimeout,
+
+	/// The server's answer to a [Handshake](crate::Handshake) was not acceptable. Contains a description of the problem.
+	///
+	#[ fail( display = "The handshake was rejected: {}", _0 ) ]
+	//
+	HandshakeRejected( String ),
+
+	/// The browser has no `BroadcastChannel`, so the connection can't be shared, see
+	/// [WsStreamShared](crate::WsStreamShared).
+	///
+	#[ fail( display = "BroadcastChannel is not available, can't share the connection." ) ]
+	//
+	SharedUnavailable,
+
+	/// Reading the data of a [WsMessageStream](crate::WsMessageStream) failed. Contains a description of the JavaScript
+	/// error, the error itself is available from [WsErr::js_error].
+	///
+	#[ fail( display = "Failed to read the message data: {}", _0 ) ]
+	//
+	BlobRead( String ),
+
+	/// The browser threw an exception we didn't expect, eg. from the `WebSocket` constructor or from `send`.
+	/// Contains a description of the exception, the exception itself is available from [WsErr::js_error].
+	///
+	#[ fail( display = "The browser returned an unexpected error: {}", _0 ) ]
+	//
+	Browser( String ),
+
+	/// A message is bigger than the configured maximum size. See
+	/// [WsConfig::max_message_size](crate::WsConfig::max_message_size).
+	///
+	#[ fail( display = "The message size ({}) exceeds the maximum size ({}).", size, limit ) ]
+	//
+	MessageTooBig
+	{
+		/// The size of the message.
+		//
+		size : usize,
+
+		/// The maximum allowed size.
+		//
+		limit: usize,
+	},
 }
 
 
@@ -99,20 +307,226 @@ impl WsErr
 	{
 		self.inner.get_context()
 	}
+
+
+	/// The exception the browser threw, eg. a `DomException`, for errors that come from one, like
+	/// [WsErrKind::Browser] and [WsErrKind::BlobRead]. `None` for other errors, and on other threads than
+	/// the one that created the error, since JavaScript values can't move between threads.
+	//
+	pub fn js_error( &self ) -> Option<JsValue>
+	{
+		self.cause.as_ref().and_then( JsCause::get )
+	}
+
+
+	// An error of `kind` that was caused by the exception `js`.
+	//
+	pub(crate) fn from_js( kind: WsErrKind, js: JsValue ) -> Self
+	{
+		WsErr { inner: FailContext::new( kind ), cause: Some( JsCause::new( js ) ) }
+	}
 }
 
 impl From<WsErrKind> for WsErr
 {
 	fn from( kind: WsErrKind ) -> WsErr
 	{
-		WsErr { inner: FailContext::new( kind ) }
+		WsErr { inner: FailContext::new( kind ), cause: None }
+	}
+}
+
+/// An exception of the browser becomes a [WsErrKind::Browser], which keeps the exception, see [WsErr::js_error].
+//
+impl From<JsValue> for WsErr
+{
+	fn from( js: JsValue ) -> WsErr
+	{
+		WsErr::from_js( WsErrKind::Browser( format!( "{:?}", js ) ), js )
+	}
+}
+
+impl WsErr
+{
+	/// Recover the [WsErr] from an `std::io::Error` that was created from it, for example by
+	/// the AsyncRead/AsyncWrite implementation of [WsIo](crate::WsIo).
+	///
+	/// Returns `None` if the io error does not wrap a [WsErr].
+	//
+	pub fn from_io_error( err: &io::Error ) -> Option< &WsErr >
+	{
+		err.get_ref()
+
+			.and_then( |inner| inner.downcast_ref::< Compat<WsErr> >() )
+			.map     ( |compat| compat.get_ref() )
+	}
+}
+
+
+
+/// Convert into an `std::io::Error` for use in io based adapters. The [WsErr] is preserved
+/// as the inner error and can be recovered with [WsErr::from_io_error].
+///
+/// | WsErrKind           | io::ErrorKind       |
+/// |---------------------|---------------------|
+/// | `ConnectionNotOpen` | `NotConnected`      |
+/// | `InvalidWsState`    | `InvalidData`       |
+/// | `MessageTooBig`     | `InvalidData`       |
+/// | `InvalidUtf8`       | `InvalidData`       |
+/// | `UnsupportedData`   | `InvalidData`       |
+/// | `UnexpectedBinary`  | `InvalidData`       |
+/// | `UnexpectedText`    | `InvalidData`       |
+/// | `Decode`            | `InvalidData`       |
+/// | `ReplayMismatch`    | `InvalidData`       |
+/// | `Encode`            | `InvalidInput`      |
+/// | `ConnectAborted`    | `ConnectionAborted` |
+/// | `Timeout`           | `TimedOut`          |
+/// | `HeartbeatTimeout`  | `TimedOut`          |
+/// | everything else     | `Other`             |
+//
+impl From<WsErr> for io::Error
+{
+	fn from( err: WsErr ) -> io::Error
+	{
+		let kind = match err.kind()
+		{
+			WsErrKind::ConnectionNotOpen             => io::ErrorKind::NotConnected      ,
+			WsErrKind::InvalidWsState(_)             => io::ErrorKind::InvalidData       ,
+			WsErrKind::MessageTooBig{..}             => io::ErrorKind::InvalidData       ,
+			WsErrKind::InvalidUtf8                   => io::ErrorKind::InvalidData       ,
+			WsErrKind::UnsupportedData(_)            => io::ErrorKind::InvalidData       ,
+			WsErrKind::UnexpectedBinary              => io::ErrorKind::InvalidData       ,
+			WsErrKind::UnexpectedText                => io::ErrorKind::InvalidData       ,
+			WsErrKind::Decode(_)                     => io::ErrorKind::InvalidData       ,
+			WsErrKind::ReplayMismatch{..}            => io::ErrorKind::InvalidData       ,
+			WsErrKind::Encode(_)                     => io::ErrorKind::InvalidInput      ,
+			WsErrKind::ConnectAborted                => io::ErrorKind::ConnectionAborted ,
+			WsErrKind::Timeout                       => io::ErrorKind::TimedOut          ,
+			WsErrKind::HeartbeatTimeout              => io::ErrorKind::TimedOut          ,
+
+			WsErrKind::ForbiddenPort                 |
+			WsErrKind::InvalidUrl(_)                 |
+			WsErrKind::InvalidCloseCode(_)           |
+			WsErrKind::ReasonStringToLong            |
+			WsErrKind::ConnectionFailed(_)           |
+			WsErrKind::AllConnectionsFailed(_)       |
+			WsErrKind::Remote(_)                     |
+			WsErrKind::BlobRead(_)                   |
+			WsErrKind::Browser(_)                    |
+			WsErrKind::HandshakeRejected(_)          |
+			WsErrKind::SharedUnavailable             |
+			WsErrKind::SendBufferFull                |
+			WsErrKind::ProtocolNegotiationFailed{..} => io::ErrorKind::Other             ,
+		};
+
+		io::Error::new( kind, err.compat() )
 	}
 }
 
+
+
+/// Convert into a JavaScript `Error`, for functions exported with `wasm_bindgen` that return `Result<T, JsValue>`.
+///
+/// The `name` is `"WsErr"`, the `message` is the display text and `kind` is the name of the [WsErrKind] variant, eg.
+/// `"ConnectionNotOpen"`. Variants with data have extra properties:
+///
+/// | WsErrKind                   | properties                            |
+/// |-----------------------------|---------------------------------------|
+/// | `InvalidWsState`            | `state`                               |
+/// | `InvalidUrl`                | `url`                                 |
+/// | `InvalidCloseCode`          | `code`                                |
+/// | `ConnectionFailed`          | `code`, `reason`, `wasClean`          |
+/// | `ProtocolNegotiationFailed` | `requested` (an array), `got`         |
+/// | `AllConnectionsFailed`      | `errors`, an array of `{ url, kind }` |
+/// | `MessageTooBig`             | `size`, `limit`                       |
+///
+/// When the error comes from an exception of the browser (see [WsErr::js_error]), that exception is the `cause`,
+/// like for `new Error( message, { cause } )`.
+//
+impl From<WsErr> for JsValue
+{
+	fn from( err: WsErr ) -> JsValue
+	{
+		let js = js_sys::Error::new( &err.to_string() );
+
+		js.set_name( "WsErr" );
+
+		let set = |obj: &JsValue, key: &str, value: JsValue|
+		{
+			// Only fails if obj is not an object, and it always is.
+			//
+			let _ = Reflect::set( obj, &key.into(), &value );
+		};
+
+		set( &js, "kind", variant( err.kind() ).into() );
+
+		if let Some( cause ) = err.js_error()
+		{
+			set( &js, "cause", cause );
+		}
+
+		match err.kind()
+		{
+			WsErrKind::InvalidWsState  ( state ) => set( &js, "state", ( *state ).into() ),
+			WsErrKind::InvalidUrl      ( url   ) => set( &js, "url"  , url.into()        ),
+			WsErrKind::InvalidCloseCode( code  ) => set( &js, "code" , ( *code  ).into() ),
+
+			WsErrKind::ConnectionFailed( evt ) =>
+			{
+				set( &js, "code"    , evt.code.into()           );
+				set( &js, "reason"  , evt.reason.as_str().into() );
+				set( &js, "wasClean", evt.was_clean.into()      );
+			}
+
+			WsErrKind::ProtocolNegotiationFailed{ requested, got } =>
+			{
+				set( &js, "requested", requested.iter().map( JsValue::from ).collect::<Array>().into() );
+				set( &js, "got"      , got.into()                                                      );
+			}
+
+			WsErrKind::AllConnectionsFailed( list ) =>
+			{
+				let errors = list.iter().map( |( url, kind )|
+				{
+					let obj: JsValue = Object::new().into();
+
+					set( &obj, "url" , url.into()              );
+					set( &obj, "kind", variant( kind ).into() );
+
+					obj
+
+				}).collect::<Array>();
+
+				set( &js, "errors", errors.into() );
+			}
+
+			WsErrKind::MessageTooBig{ size, limit } =>
+			{
+				set( &js, "size" , ( *size  as f64 ).into() );
+				set( &js, "limit", ( *limit as f64 ).into() );
+			}
+
+			_ => {}
+		}
+
+		js.into()
+	}
+}
+
+
+
+// The name of the variant, eg. "InvalidCloseCode" for `InvalidCloseCode(5)`.
+//
+fn variant( kind: &WsErrKind ) -> String
+{
+	format!( "{:?}", kind ).chars().take_while( |c| c.is_alphanumeric() ).collect()
+}
+
+
+
 impl From< FailContext<WsErrKind> > for WsErr
 {
 	fn from( inner: FailContext<WsErrKind> ) -> WsErr
 	{
-		WsErr { inner }
+		WsErr { inner, cause: None }
 	}
 }
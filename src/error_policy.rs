@@ -0,0 +1,63 @@
+use
+{
+	crate :: { import::*, WsErr, WsErrKind, WsEvent } ,
+};
+
+
+/// What to do when an incoming message can't be converted: invalid UTF-8 with
+/// [WsConfig::binary_to_text](crate::WsConfig::binary_to_text), data of an unsupported type, or a message
+/// a [WsChannel](crate::WsChannel) codec fails to decode.
+///
+/// Set it with [WsConfig::error_policy](crate::WsConfig::error_policy) for [WsIo](crate::WsIo) and with
+/// [WsChannel::error_policy](crate::WsChannel::error_policy) for decoding. Other errors, like
+/// [WsErrKind::MessageTooBig], are not affected. The default is [ErrorPolicy::YieldError].
+//
+#[ derive( Debug, Clone, Copy, PartialEq, Eq, Default ) ]
+//
+pub enum ErrorPolicy
+{
+	/// Drop the message and count it, see [WsStats::skipped](crate::WsStats::skipped).
+	//
+	SkipAndCount,
+
+	/// Yield the error in place of the message. The stream continues afterwards.
+	//
+	#[ default ]
+	//
+	YieldError,
+
+	/// Yield the error and close the connection with this code, so the stream ends. Messages that arrived
+	/// before are still delivered.
+	///
+	/// **Note**: Browsers only let scripts close with code 1000 or 3000-4999. If the code is refused,
+	/// for example 1007 (Invalid frame payload data), 1000 is used instead.
+	//
+	CloseConnection( u16 ),
+}
+
+
+
+// Whether the policy applies to this error.
+//
+pub(crate) fn is_conversion( err: &WsErr ) -> bool
+{
+	matches!( err.kind(), WsErrKind::InvalidUtf8 | WsErrKind::UnsupportedData(_) | WsErrKind::Decode(_) )
+}
+
+
+
+// Close the connection for ErrorPolicy::CloseConnection, also used by the heartbeat. Just like after
+// WsStream::close, we notify observers that the connection is closing.
+//
+pub(crate) fn close( ws: &WebSocket, pharos: &RefCell< Pharos<WsEvent> >, code: u16, reason: &str )
+{
+	if ws.close_with_code_and_reason( code, reason ).is_err()
+	&& ws.close_with_code_and_reason( 1000, reason ).is_err()
+	{
+		// Already closing or closed.
+		//
+		return;
+	}
+
+	rt::block_on( pharos.borrow_mut().notify( &WsEvent::Closing ) );
+}
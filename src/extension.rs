@@ -0,0 +1,141 @@
+/// A websocket extension negotiated with the server, eg. `permessage-deflate`.
+///
+/// See: [RFC 6455 - Extensions](https://tools.ietf.org/html/rfc6455#section-9).
+//
+#[ derive( Debug, Clone, PartialEq, Eq, Hash ) ]
+//
+pub struct Extension
+{
+	/// The name of the extension, eg. `permessage-deflate`.
+	//
+	pub name  : String,
+
+	/// The parameters of the extension in the order they were sent. Parameters without a value,
+	/// like `server_no_context_takeover`, have `None` as value. Quoted values are unquoted.
+	//
+	pub params: Vec<( String, Option<String> )>,
+}
+
+
+
+impl Extension
+{
+	/// Parse the value of the `Sec-WebSocket-Extensions` header, as returned by `WebSocket.extensions`
+	/// in the browser. Handles `; param=value` syntax and quoted values. Entries without a name are
+	/// ignored, so this never fails.
+	///
+	/// ```
+	/// use ws_stream_wasm::Extension;
+	///
+	/// let ext = Extension::parse_list( "permessage-deflate; client_max_window_bits=15" );
+	///
+	/// assert_eq!( "permessage-deflate", ext[0].name );
+	/// assert_eq!( Some( "15" ), ext[0].param( "client_max_window_bits" ).unwrap() );
+	/// ```
+	//
+	pub fn parse_list( header: &str ) -> Vec<Extension>
+	{
+		split_unquoted( header, ',' ).into_iter().filter_map( |ext|
+		{
+			let mut parts = split_unquoted( ext, ';' ).into_iter();
+
+			let name = parts.next()?.trim();
+
+			if name.is_empty() { return None }
+
+
+			let params = parts.filter_map( |param|
+			{
+				let mut kv  = param.splitn( 2, '=' );
+				let     key = kv.next()?.trim();
+
+				if key.is_empty() { return None }
+
+				Some(( key.to_string(), kv.next().map( |v| unquote( v.trim() ) ) ))
+
+			}).collect();
+
+
+			Some( Extension { name: name.to_string(), params } )
+
+		}).collect()
+	}
+
+
+	/// Look up a parameter by name. The outer option tells whether the parameter is present,
+	/// the inner one whether it has a value.
+	//
+	pub fn param( &self, name: &str ) -> Option< Option<&str> >
+	{
+		self.params.iter()
+
+			.find( |(key, _)| key.eq_ignore_ascii_case( name ) )
+			.map ( |(_, value)| value.as_ref().map( String::as_str ) )
+	}
+
+
+	/// Whether this is the `permessage-deflate` compression extension.
+	//
+	pub fn is_permessage_deflate( &self ) -> bool
+	{
+		self.name.eq_ignore_ascii_case( "permessage-deflate" )
+	}
+}
+
+
+
+// Split on a separator, unless it's inside a quoted string.
+//
+fn split_unquoted( input: &str, sep: char ) -> Vec<&str>
+{
+	let mut parts   = Vec::new();
+	let mut start   = 0;
+	let mut quoted  = false;
+	let mut escaped = false;
+
+	for (i, c) in input.char_indices()
+	{
+		match c
+		{
+			_    if escaped => escaped = false,
+			'\\' if quoted  => escaped = true ,
+			'"'             => quoted  = !quoted,
+
+			_ if c == sep && !quoted =>
+			{
+				parts.push( &input[ start..i ] );
+				start = i + c.len_utf8();
+			}
+
+			_ => {}
+		}
+	}
+
+	parts.push( &input[ start.. ] );
+	parts
+}
+
+
+
+// Remove surrounding quotes and backslash escapes from a quoted string.
+//
+fn unquote( value: &str ) -> String
+{
+	if value.len() < 2 || !value.starts_with( '"' ) || !value.ends_with( '"' )
+	{
+		return value.to_string();
+	}
+
+	let mut out     = String::with_capacity( value.len() );
+	let mut escaped = false;
+
+	for c in value[ 1..value.len()-1 ].chars()
+	{
+		if !escaped && c == '\\' { escaped = true; continue }
+
+		escaped = false;
+		out.push( c );
+	}
+
+	out
+}
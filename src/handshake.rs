@@ -0,0 +1,142 @@
+use
+{
+	crate   :: { import::*, WsErr, WsErrKind, WsIo, WsMessage, WsStream } ,
+	futures :: { future::poll_fn, sink::SinkExt                         } ,
+	std     :: { time::Duration                                         } ,
+};
+
+
+/// An application level handshake on a new connection: send a hello message, wait for the first message from the
+/// server and check it before normal traffic starts. Typically used to negotiate a protocol version.
+///
+/// ```no_run
+/// use { ws_stream_wasm::*, std::time::Duration };
+///
+/// # async fn example() -> Result<(), WsErr> {
+/// let conn = WsStream::connect( "ws://127.0.0.1:3012" ).await?;
+///
+/// let ( (ws, wsio), version ) = Handshake::new( WsMessage::from( "hello v2" ), Duration::from_secs( 5 ) )
+///
+///    .close_code( 4000 )
+///    .run( conn, |msg| match msg
+///    {
+///       WsMessage::Text( t ) if t.starts_with( "accept " ) => Ok( t[ 7.. ].to_string() ),
+///       _                                                 => Err( WsErrKind::HandshakeRejected( "version".into() ).into() ),
+///    })
+///    .await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// When the validator returns an error, when no message arrives within the timeout or when the connection fails
+/// during the handshake, the connection is closed with the close code and the error is returned. A timeout gives
+/// [WsErrKind::Timeout]. The time is measured with [WsConfig::timer](crate::WsConfig::timer).
+///
+/// Only the first message is taken from [WsIo]. Messages the server sends right after it stay queued, so you will
+/// receive them from the stream as usual.
+//
+#[ derive( Debug, Clone, PartialEq, Eq ) ]
+//
+pub struct Handshake
+{
+	hello     : WsMessage,
+	timeout   : Duration ,
+	close_code: u16      ,
+}
+
+
+
+impl Handshake
+{
+	/// Send `hello` and wait at most `timeout` for the answer. The default close code is 1000.
+	//
+	pub fn new( hello: WsMessage, timeout: Duration ) -> Self
+	{
+		Self { hello, timeout, close_code: 1000 }
+	}
+
+
+	/// The code to close the connection with when the handshake fails. Browsers only allow 1000 and 3000-4999.
+	/// If the browser refuses the code, we close with 1000.
+	//
+	pub fn close_code( mut self, code: u16 ) -> Self
+	{
+		self.close_code = code;
+		self
+	}
+
+
+	/// Do the handshake on `conn`. On success, you get the connection back, together with what `validate`
+	/// made of the answer.
+	//
+	pub async fn run<T>
+	(
+		&self,
+		conn    : (WsStream, WsIo)                              ,
+		validate: impl FnOnce( &WsMessage ) -> Result<T, WsErr> ,
+	)
+		-> Result< ((WsStream, WsIo), T), WsErr >
+	{
+		let ( ws, mut wsio ) = conn;
+
+		match self.exchange( &ws, &mut wsio ).await.and_then( |msg| validate( &msg ) )
+		{
+			Ok( value ) => Ok( ( (ws, wsio), value ) ),
+
+			Err( e ) =>
+			{
+				debug!( "Handshake failed: {}", e );
+
+				if ws.close_code( self.close_code ).await.is_err()
+				{
+					ws.close().await;
+				}
+
+				Err( e )
+			}
+		}
+	}
+
+
+	// Send the hello and wait for the first message.
+	//
+	async fn exchange( &self, ws: &WsStream, wsio: &mut WsIo ) -> Result<WsMessage, WsErr>
+	{
+		wsio.sender().send( self.hello.clone() ).await?;
+
+		let mut sleep = ws.timer().sleep( self.timeout );
+
+		poll_fn( |cx|
+		{
+			// Check for the message before the timer, so the message wins a tie.
+			//
+			if let Poll::Ready( msg ) = Pin::new( &mut *wsio ).poll_next( cx )
+			{
+				return Poll::Ready( msg.unwrap_or_else( || Err( WsErrKind::ConnectionNotOpen.into() ) ) );
+			}
+
+			match sleep.as_mut().poll( cx )
+			{
+				Poll::Ready(_) => Poll::Ready( Err( WsErrKind::Timeout.into() ) ),
+				Poll::Pending  => Poll::Pending,
+			}
+
+		}).await
+	}
+}
+
+
+
+/// Do a [Handshake] that closes the connection with code 1000 when it fails.
+//
+pub async fn handshake<T>
+(
+	conn    : (WsStream, WsIo)                              ,
+	hello   : WsMessage                                     ,
+	validate: impl FnOnce( &WsMessage ) -> Result<T, WsErr> ,
+	timeout : Duration                                      ,
+)
+	-> Result< ((WsStream, WsIo), T), WsErr >
+{
+	Handshake::new( hello, timeout ).run( conn, validate ).await
+}
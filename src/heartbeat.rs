@@ -0,0 +1,238 @@
+use
+{
+	crate   :: { import::*, timer::SharedTimer, visibility::{ self, VisibilityGuard }, WsMessage, WsSender, WsState } ,
+	futures :: { channel::mpsc, future::{ Either, FutureExt, LocalBoxFuture }                                      } ,
+	std     :: { cell::Cell, time::Duration                                                                         } ,
+};
+
+
+/// What a [Heartbeat] does while the page is hidden. Browsers throttle timers in background tabs, so a heartbeat
+/// might not fire in time anyway.
+///
+/// In a worker there is no page, so this has no effect.
+//
+#[ derive( Debug, Clone, Copy, PartialEq, Eq ) ]
+//
+pub enum Visibility
+{
+	/// Don't look at the visibility of the page.
+	//
+	Ignore,
+
+	/// Keep sending heartbeats while hidden, at this interval, which should be one the server tolerates.
+	//
+	KeepAlive( Duration ),
+
+	/// Stop sending heartbeats while hidden.
+	//
+	PauseWhileHidden,
+}
+
+
+
+/// Sends a message at a regular interval, so the server and proxies keep the connection open. Browsers don't let us
+/// send WebSocket ping frames, so this is an application level message that the server has to accept.
+/// Set it with [WsConfig::heartbeat](crate::WsConfig::heartbeat).
+///
+/// ```
+/// use { ws_stream_wasm::*, std::time::Duration };
+///
+/// let heartbeat = Heartbeat::new( Duration::from_secs( 30 ), WsMessage::from( "ping" ) )
+///
+///    .timeout   ( Duration::from_secs( 75 )                  )
+///    .visibility( Visibility::KeepAlive( Duration::from_secs( 120 ) ) )
+/// ;
+///
+/// let config = WsConfig::default().heartbeat( heartbeat );
+/// ```
+///
+/// With a timeout, the connection is considered dead when nothing was received for that long. [WsIo](crate::WsIo) then
+/// yields [WsErrKind::HeartbeatTimeout](crate::WsErrKind::HeartbeatTimeout) and the connection is closed, so the stream
+/// ends. The timeout is checked when a heartbeat is due, so it is only as precise as the interval. With
+/// [Visibility::KeepAlive], that is the longer interval while the page is hidden. With [Visibility::PauseWhileHidden],
+/// it isn't checked while the page is hidden, but the time still counts. It's checked again one interval after
+/// the page became visible, so the heartbeat sent at that moment can be answered first.
+///
+/// When the page becomes visible again, a heartbeat is sent right away and the normal interval resumes.
+///
+/// Heartbeats are sent with a [WsSender], so they go through interceptors.
+//
+#[ derive( Debug, Clone, PartialEq, Eq ) ]
+//
+pub struct Heartbeat
+{
+	interval  : Duration          ,
+	message   : WsMessage         ,
+	timeout   : Option< Duration >,
+	visibility: Visibility        ,
+}
+
+
+
+impl Heartbeat
+{
+	/// Send `message` every `interval`, without timeout and ignoring the visibility of the page.
+	//
+	pub fn new( interval: Duration, message: WsMessage ) -> Self
+	{
+		Self { interval, message, timeout: None, visibility: Visibility::Ignore }
+	}
+
+
+	/// Close the connection when nothing was received for this long. `None` means never.
+	//
+	pub fn timeout( mut self, timeout: impl Into<Option<Duration>> ) -> Self
+	{
+		self.timeout = timeout.into();
+		self
+	}
+
+
+	/// What to do while the page is hidden. Defaults to [Visibility::Ignore].
+	//
+	pub fn visibility( mut self, visibility: Visibility ) -> Self
+	{
+		self.visibility = visibility;
+		self
+	}
+}
+
+
+
+enum Wake
+{
+	Tick,
+	Changed( bool ),
+}
+
+
+
+// Sends heartbeats until the connection is no longer open. `activity` is set by the onmessage callback
+// for every message. `on_timeout` is called before we stop because of the timeout. `closed` resolves when
+// the connection closes, so we also stop while paused.
+//
+pub(crate) async fn run
+(
+	hb        : Heartbeat          ,
+	sender    : WsSender           ,
+	timer     : SharedTimer        ,
+	activity  : Rc<Cell< bool >>   ,
+	mut closed: impl Future + Unpin,
+	on_timeout: impl FnOnce()      ,
+)
+{
+
+	let ( tx, mut rx ) = mpsc::unbounded();
+
+	// Without a document, this is None and Visibility::Ignore is what we get. We keep `tx` around so `rx`
+	// doesn't end in that case.
+	//
+	let tx2   = tx.clone();
+	let guard = match hb.visibility
+	{
+		Visibility::Ignore => None,
+		_                  => VisibilityGuard::new( move |hidden| { let _ = tx2.unbounded_send( hidden ); } ),
+	};
+
+	let mut hidden = guard.is_some() && visibility::is_hidden();
+
+	// Resolves once nothing was received for the timeout. It's only started again when a heartbeat finds that
+	// something was received, so changes of visibility don't reset it.
+	//
+	let silence      = || hb.timeout.map( |t| timer.sleep( t ) );
+	let mut deadline = silence();
+
+	// The wait for the next heartbeat. A change of visibility that doesn't pause us or send a heartbeat keeps
+	// it, so the time waited so far isn't lost.
+	//
+	let mut tick: Option< LocalBoxFuture<'static, ()> > = None;
+
+	let beat = |sender: &WsSender|
+	{
+		if let Err( e ) = Pin::new( &mut &*sender ).start_send( hb.message.clone() )
+		{
+			error!( "Heartbeat: failed to send: {}", e );
+		}
+	};
+
+
+	loop
+	{
+		let wait = match hb.visibility
+		{
+			Visibility::KeepAlive( d ) if hidden => d           ,
+			_                                    => hb.interval ,
+		};
+
+		let paused = hidden && hb.visibility == Visibility::PauseWhileHidden;
+
+		if      paused         { tick = None                        }
+		else if tick.is_none() { tick = Some( timer.sleep( wait ) ) }
+
+		let next = async
+		{
+			match &mut tick
+			{
+				None => Wake::Changed( rx.next().await.unwrap_or( false ) ),
+
+				Some( sleep ) => match select( sleep, rx.next() ).await
+				{
+					Either::Left (_)        => Wake::Tick,
+					Either::Right(( h, _ )) => Wake::Changed( h.unwrap_or( false ) ),
+				}
+			}
+		};
+
+		let wake = match select( Box::pin( next ), &mut closed ).await
+		{
+			Either::Left (( wake, _ )) => wake,
+			Either::Right(_)           => break,
+		};
+
+
+		if sender.ready_state() != WsState::Open { break }
+
+
+		match wake
+		{
+			Wake::Changed( h ) =>
+			{
+				let was = hidden;
+
+				hidden = h;
+
+				// The normal interval starts over from this heartbeat.
+				//
+				if was && !hidden
+				{
+					tick = None;
+					beat( &sender );
+				}
+			}
+
+			Wake::Tick =>
+			{
+				tick = None;
+
+				// While paused we don't tick, so this only runs at the KeepAlive interval while hidden.
+				//
+				if activity.replace( false )
+				{
+					deadline = silence();
+				}
+
+				else if deadline.as_mut().map( |d| d.now_or_never().is_some() ).unwrap_or( false )
+				{
+					warn!( "Heartbeat: nothing received for {:?}, closing connection", hb.timeout.unwrap_or_default() );
+
+					on_timeout();
+					break;
+				}
+
+				beat( &sender );
+			}
+		}
+	}
+
+	drop( tx );
+}
@@ -0,0 +1,189 @@
+// Marks and measures on the Performance API, see WsConfig::instrument. Without the `instrument`
+// feature, this is a no-op with the same interface, so the call sites don't need cfg attributes.
+//
+#[ cfg( feature = "instrument" ) ]
+//
+use
+{
+	crate   :: { import::*, WsConfig } ,
+	js_sys  :: { global, Reflect     } ,
+	std     :: { cell::Cell          } ,
+	web_sys :: { Performance         } ,
+};
+
+#[ cfg( not( feature = "instrument" ) ) ]
+//
+use crate::WsConfig;
+
+
+
+#[ cfg( feature = "instrument" ) ]
+//
+thread_local!
+{
+	static NEXT_ID: Cell<u64> = const { Cell::new( 1 ) };
+}
+
+
+
+#[ derive( Clone, Default ) ]
+//
+pub(crate) struct Instrument
+{
+	#[ cfg( feature = "instrument" ) ]
+	//
+	inner: Option< Rc<Inner> >,
+}
+
+
+
+#[ cfg( feature = "instrument" ) ]
+//
+struct Inner
+{
+	perf     : Performance,
+	prefix   : String     ,
+	every    : u64        ,
+	received : Cell<u64>  ,
+	delivered: Cell<u64>  ,
+}
+
+
+
+#[ cfg( feature = "instrument" ) ]
+//
+impl Instrument
+{
+	/// Returns a no-op instrument if it's not enabled in the config or if there is no Performance API
+	/// on the global scope, which works both in windows and in workers.
+	//
+	pub(crate) fn new( config: &WsConfig ) -> Self
+	{
+		let every = match config.instrument
+		{
+			Some( every ) => every,
+			None          => return Self::default(),
+		};
+
+		let perf = Reflect::get( &global(), &"performance".into() ).ok()
+
+			.and_then( |p| p.dyn_into::<Performance>().ok() );
+
+		let perf = match perf
+		{
+			Some( p ) => p,
+
+			None =>
+			{
+				warn!( "WsStream: Performance API not available, instrumentation disabled" );
+				return Self::default();
+			}
+		};
+
+		let id = NEXT_ID.with( |n| { let id = n.get(); n.set( id + 1 ); id } );
+
+		Self { inner: Some( Rc::new( Inner
+		{
+			perf                                   ,
+			prefix   : format!( "ws_stream_wasm:{}", id ),
+			every    : u64::from( every )                ,
+			received : Cell::new( 0 )                    ,
+			delivered: Cell::new( 0 )                    ,
+		}))}
+	}
+
+
+	pub(crate) fn connect_start( &self )
+	{
+		if let Some( i ) = &self.inner { i.mark( &format!( "{}:connect:start", i.prefix ) ) }
+	}
+
+
+	pub(crate) fn connect_open( &self )
+	{
+		if let Some( i ) = &self.inner
+		{
+			let start = format!( "{}:connect:start", i.prefix );
+			let open  = format!( "{}:connect:open" , i.prefix );
+
+			i.mark   ( &open );
+			i.measure( &format!( "{}:connect", i.prefix ), &start, &open );
+		}
+	}
+
+
+	/// A message was put in the queue of WsIo.
+	//
+	pub(crate) fn received( &self )
+	{
+		if let Some( i ) = &self.inner
+		{
+			let n = i.received.get();
+			i.received.set( n + 1 );
+
+			if i.sampled( n ) { i.mark( &format!( "{}:msg:{}:received", i.prefix, n ) ) }
+		}
+	}
+
+
+	/// A message was taken from the queue of WsIo, or dropped from it after it was read. Messages
+	/// are delivered in the order they are received, so the counts match up.
+	//
+	pub(crate) fn delivered( &self )
+	{
+		if let Some( i ) = &self.inner
+		{
+			let n = i.delivered.get();
+			i.delivered.set( n + 1 );
+
+			if i.sampled( n )
+			{
+				let received  = format!( "{}:msg:{}:received" , i.prefix, n );
+				let delivered = format!( "{}:msg:{}:delivered", i.prefix, n );
+
+				i.mark   ( &delivered );
+				i.measure( &format!( "{}:msg:{}", i.prefix, n ), &received, &delivered );
+			}
+		}
+	}
+}
+
+
+
+#[ cfg( feature = "instrument" ) ]
+//
+impl Inner
+{
+	fn sampled( &self, n: u64 ) -> bool
+	{
+		self.every != 0 && n.is_multiple_of( self.every )
+	}
+
+
+	// Failures are not worth bothering the user with, the marks are just missing from the timeline.
+	//
+	fn mark( &self, name: &str )
+	{
+		let _ = self.perf.mark( name );
+	}
+
+
+	fn measure( &self, name: &str, start: &str, end: &str )
+	{
+		let _ = self.perf.measure_with_start_mark_and_end_mark( name, start, end );
+	}
+}
+
+
+
+#[ cfg( not( feature = "instrument" ) ) ]
+//
+impl Instrument
+{
+	pub(crate) fn new( _config: &WsConfig ) -> Self { Self::default() }
+
+	pub(crate) fn connect_start( &self ) {}
+	pub(crate) fn connect_open ( &self ) {}
+	pub(crate) fn received     ( &self ) {}
+	pub(crate) fn delivered    ( &self ) {}
+}
@@ -0,0 +1,263 @@
+// A class for JavaScript code that shares a connection owned by rust. Only compiled with the `js_api` feature.
+//
+use
+{
+	crate                :: { import::*, bridge::set, CloseEvent, WsIo, WsMessage, WsSender, WsStream, WsSubscription } ,
+	futures              :: { future::{ FutureExt, TryFutureExt }, SinkExt                                            } ,
+	js_sys               :: { Function, Object, Promise                                                               } ,
+	wasm_bindgen         :: { prelude::wasm_bindgen                                                                   } ,
+	wasm_bindgen_futures :: { future_to_promise                                                                       } ,
+};
+
+
+// Text becomes a string and binary data a Uint8Array. This copies the data once, into the JavaScript heap.
+//
+fn to_js( msg: &WsMessage ) -> JsValue
+{
+	match msg
+	{
+		WsMessage::Text  ( s ) => JsValue::from_str( s ),
+		WsMessage::Binary( _ ) => msg.to_uint8array().into(),
+	}
+}
+
+
+fn close_to_js( evt: &CloseEvent ) -> JsValue
+{
+	let obj = Object::new();
+
+	set( &obj, "code"    , &evt.code     .into() );
+	set( &obj, "reason"  , &evt.reason.as_str().into() );
+	set( &obj, "wasClean", &evt.was_clean.into() );
+
+	obj.into()
+}
+
+
+// Run `fut` and resolve the promise with it's output. A WsErr rejects it with a JavaScript Error,
+// see the conversion in error.rs.
+//
+fn promise( fut: impl Future< Output = Result<JsValue, JsValue> > + 'static ) -> Promise
+{
+	future_to_promise( fut.boxed_local().compat() )
+}
+
+
+// Call a callback from a task, after taking it out of the cell, so the callback can register a new one.
+//
+fn call( callback: &RefCell< Option<Function> >, arg: &JsValue )
+{
+	let f = callback.borrow().clone();
+
+	if let Some( f ) = f
+	{
+		if let Err( e ) = f.call1( &JsValue::NULL, arg )
+		{
+			error!( "JsWsConnection: callback threw: {:?}", e );
+		}
+	}
+}
+
+
+
+/// A connection for JavaScript code, eg. the parts of an app that aren't ported to rust yet. The connection is
+/// owned and configured by rust, so JavaScript shares it's heartbeat, interceptors and so on, rather than opening
+/// a connection of it's own. Only available with the `js_api` feature.
+///
+/// Create it from rust with [JsWsConnection::new] and hand it to JavaScript, or open a new connection from
+/// JavaScript with the static `connect` method. The methods have the names JavaScript code expects:
+///
+/// ```js
+/// conn.onMessage( msg => console.log( msg ) );   // a string or a Uint8Array
+/// conn.onClose  ( evt => console.log( evt.code, evt.reason, evt.wasClean ) );
+///
+/// await conn.send( "hello" );
+/// await conn.send( new Uint8Array([ 1, 2, 3 ]) );
+///
+/// console.log( conn.readyState );                // like WebSocket.readyState
+///
+/// let evt = await conn.close( 1000, "bye" );     // code and reason are optional
+/// ```
+///
+/// The promises are rejected with the `Error` described in the conversion of [WsErr](crate::WsErr) to `JsValue`.
+///
+/// Messages are copied once when they cross the boundary: what JavaScript sends is copied into a [WsMessage],
+/// what arrives is copied into a string or a `Uint8Array`. Incoming messages are received through a
+/// [WsSubscription](crate::WsSubscription), so rust code can keep reading the [WsIo] as well. Messages that arrive
+/// before `onMessage` is called are kept by the subscription, within it's capacity.
+//
+#[ wasm_bindgen ]
+//
+pub struct JsWsConnection
+{
+	ws        : WsStream                         ,
+	sender    : WsSender                         ,
+	messages  : RefCell< Option<WsSubscription> >,
+	on_message: Rc<RefCell< Option<Function> >>  ,
+	on_close  : Rc<RefCell< Option<Function> >>  ,
+}
+
+
+
+impl JsWsConnection
+{
+	/// Share a connection with JavaScript. The connection stays owned by rust: when the [WsIo] is dropped, it's
+	/// closed. Call this right after connecting if JavaScript should receive all messages.
+	//
+	pub fn new( ws: &WsStream, wsio: &WsIo ) -> Self
+	{
+		Self
+		{
+			ws        : ws.clone()                             ,
+			sender    : ws.sender()                            ,
+			messages  : RefCell::new( Some( wsio.subscribe() ) ),
+			on_message: Rc::new( RefCell::new( None ) )        ,
+			on_close  : Rc::new( RefCell::new( None ) )        ,
+		}
+	}
+}
+
+
+
+#[ wasm_bindgen ]
+//
+impl JsWsConnection
+{
+	/// Open a new connection to `url`. The promise resolves with a `JsWsConnection`. As nobody else reads
+	/// from the connection, the messages only go to `onMessage`. The connection stays open until it's closed.
+	//
+	pub fn connect( url: String ) -> Promise
+	{
+		promise( async move
+		{
+			let ( ws, mut wsio ) = WsStream::connect( url ).await?;
+			let conn             = Self::new( &ws, &wsio );
+
+			// Nobody reads the WsIo, so read it to keep messages from piling up in it. This keeps
+			// the connection open until it's closed.
+			//
+			ws.spawner().spawn_local( async move { while wsio.next().await.is_some() {} } );
+
+			Ok( conn.into() )
+		})
+	}
+
+
+	/// Send a string or binary data (an `ArrayBuffer` or a view on one like `Uint8Array`). The promise resolves
+	/// once the message is handed to the browser.
+	//
+	pub fn send( &self, data: JsValue ) -> Promise
+	{
+		let mut sender = self.sender.clone();
+
+		promise( async move
+		{
+			sender.send( WsMessage::try_from( data )? ).await?;
+
+			Ok( JsValue::UNDEFINED )
+		})
+	}
+
+
+	/// Close the connection. The code and reason are optional, as for `WebSocket.close`. The promise resolves
+	/// with an object with `code`, `reason` and `wasClean` once the connection is closed.
+	//
+	pub fn close( &self, code: Option<u16>, reason: Option<String> ) -> Promise
+	{
+		let ws = self.ws.clone();
+
+		promise( async move
+		{
+			let evt = match ( code, reason )
+			{
+				( Some( code ), Some( reason ) ) => ws.close_reason( code, reason ).await?,
+				( Some( code ), None           ) => ws.close_code  ( code         ).await?,
+				( None        , _              ) => ws.close       (              ).await ,
+			};
+
+			Ok( close_to_js( &evt ) )
+		})
+	}
+
+
+	/// The state of the connection, as the numbers of `WebSocket.readyState`.
+	//
+	#[ wasm_bindgen( getter, js_name = readyState ) ]
+	//
+	pub fn ready_state( &self ) -> u16
+	{
+		self.ws.wrapped().ready_state()
+	}
+
+
+	/// The url of the connection.
+	//
+	#[ wasm_bindgen( getter ) ]
+	//
+	pub fn url( &self ) -> String
+	{
+		self.ws.url()
+	}
+
+
+	/// Call `callback` with every incoming message, a string or a `Uint8Array`. Calling this again replaces
+	/// the callback.
+	//
+	#[ wasm_bindgen( js_name = onMessage ) ]
+	//
+	pub fn on_message( &self, callback: Function )
+	{
+		*self.on_message.borrow_mut() = Some( callback );
+
+		// Only the first call starts the task.
+		//
+		let mut messages = match self.messages.borrow_mut().take()
+		{
+			Some( messages ) => messages,
+			None             => return  ,
+		};
+
+		let callback = self.on_message.clone();
+
+		self.ws.spawner().spawn_local( async move
+		{
+			while let Some( msg ) = messages.next().await
+			{
+				call( &callback, &to_js( &msg ) );
+			}
+		});
+	}
+
+
+	/// Call `callback` with an object with `code`, `reason` and `wasClean` when the connection closes, right away
+	/// if it's already closed. Calling this again replaces the callback.
+	//
+	#[ wasm_bindgen( js_name = onClose ) ]
+	//
+	pub fn on_close( &self, callback: Function )
+	{
+		let first = self.on_close.borrow_mut().replace( callback ).is_none();
+
+		if !first { return }
+
+		let closed   = self.ws.closed();
+		let callback = self.on_close.clone();
+
+		self.ws.spawner().spawn_local( async move
+		{
+			let evt = closed.await;
+
+			call( &callback, &close_to_js( &evt ) );
+		});
+	}
+}
+
+
+
+impl fmt::Debug for JsWsConnection
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		write!( f, "JsWsConnection for connection: {}", self.ws.url() )
+	}
+}
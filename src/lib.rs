@@ -14,13 +14,39 @@
 //! - `WsIo`: A futures Sink/Stream of WsMessage. (can use the futures compat layer to get futures 01 versions).
 //!                 It also implements AsyncRead/AsyncWrite from futures 0.3. With the compat layer you can obtain futures
 //!                 01 versions for use with tokio codec.
+//! - `WsConfig`: Options for the connection, like subprotocols and the maximum size of incoming messages.
+//! - `WsSender`: A cheaply clonable handle that implements Sink of WsMessage through a shared reference.
+//!   For simple cases there are `send_text` and `send_binary`, also on `WsStream`.
+//! - `WsInterceptor`: Hooks to modify or drop messages as they are sent and received.
+//! - `WsStreamExt`: `text_only` and `binary_only` adapters for protocols that use one type of message.
+//! - `CreditFlow`: Credit based flow control, for servers that only send when the client granted them credit.
+//! - `WsChannel`: A typed Sink/Stream with different send and receive types, converted by a codec that implements
+//!   `WsEncode`/`WsDecode`. `CborCodec` is included with the `cbor` feature.
+//! - `WsPool`: Several connections to the same url used as one Sink/Stream, for throughput. Messages are not ordered across connections.
+//! - `WsMessageStream`: Receive huge binary messages in chunks, see `WsConfig::stream_threshold`.
+//! - `Handshake`: Send a hello message and check the server's first answer, eg. to negotiate a protocol version.
+//! - `SendBuffer`: Keep outgoing messages while reconnecting and send them in order on the new connection, with an optional high priority lane.
+//! - `Heartbeat`: Send a message at a regular interval to keep the connection open, optionally closing it when the server
+//!   stops answering. It can slow down or pause while the page is hidden.
+//! - `WsSubscription`: Lets several consumers receive all incoming messages, obtained with `WsIo::subscribe`.
+//! - `WsStats`: Counts messages dropped by slow subscribers, with an optional warning, see `WsConfig::lag_warning`.
+//! - `WsStreamRemote`: Use a connection that lives in a Web Worker over a `MessagePort`, see `serve_port`.
+//! - `WsStreamShared`: Share one connection between the tabs of a site over a `BroadcastChannel`, see `serve_shared`.
+//! - `listen` and `once`: Listen to DOM events with `addEventListener`, the listener is removed when the guard is dropped.
+//! - `Recorder` and `Replay`: Record the traffic of a connection and play it back in a test, serializable with the `serde` feature.
+//! - `Timer`: Plug in your own source of time for retry delays and polling, eg. `MockTimer` with the `mock` feature.
+//! - `Spawner`: Choose where the background tasks of a connection run.
+//! - `Watch`: A value with change notifications. `WsStream::state_signal` and `RetryPolicy::status_signal` give streams
+//!   of the connection state for UI code to bind to.
+//! - `WsUrl`: A builder for urls with percent-encoded query parameters.
 //! - `WsEvents`: `WsStream` is observable with [pharos](https://crates.io/crates/pharos) for events (mainly connection close).
 //!
 //! **NOTE:** this crate only works on WASM. If you want a server side equivalent that implements AsyncRead/AsyncWrite over
 //! WebSockets, check out [ws_stream](https://crates.io/crates/ws_stream).
 //!
 //! **missing features:**
-//! - no automatic reconnect
+//! - no automatic reconnect, although `WsStream::connect_retry` can retry the initial connection and `SendBuffer`
+//!   can keep outgoing messages for the new connection
 //! - not all features are thoroughly tested. Notably, I have little use for extensions and subprotocols. Tungstenite,
 //!   which I use for the server end (and for automated testing) doesn't support these, making it hard to write unit tests.
 //!
@@ -59,7 +85,13 @@
 //!
 //! This crate has few dependiencies. Cargo will automatically handle it's dependencies for you.
 //!
-//! There are no optional features.
+//! Optional features:
+//!
+//! - `mock`: `MockTimer`, a `Timer` for tests.
+//! - `instrument`: `WsConfig::instrument`, performance marks for the browser's developer tools.
+//! - `cbor`: `CborCodec` for `WsChannel`.
+//! - `js_api`: `JsWsConnection`, a class to use a connection from JavaScript code.
+//! - `serde`: Serialize and Deserialize for `WsMessage`, `WsEvent` and `Recording`.
 //!
 //!
 //! ## Usage
@@ -81,7 +113,7 @@
 //!
 //! let program = async
 //! {
-//!    let (mut ws, _wsio) = WsStream::connect( "127.0.0.1:3012", None ).await
+//!    let (mut ws, _wsio) = WsStream::connect( "127.0.0.1:3012" ).await
 //!
 //!       .expect_throw( "assume the connection succeeds" );
 //!
@@ -159,42 +191,109 @@
 #![ forbid ( unsafe_code                                      ) ]
 #![ allow  ( clippy::suspicious_else_formatting               ) ]
 
-mod error           ;
-mod ws_event        ;
-mod ws_message      ;
-mod ws_io           ;
-mod ws_state        ;
-mod ws_stream       ;
+mod bridge            ;
+mod callback_future   ;
+mod credit            ;
+mod error             ;
+mod error_policy      ;
+mod extension         ;
+mod handshake         ;
+mod heartbeat         ;
+mod instrument        ;
+mod network           ;
+mod record            ;
+mod replay            ;
+mod retry             ;
+mod send_buffer       ;
+mod shared            ;
+mod spawner           ;
+mod text_codec        ;
+mod timer             ;
+mod unload            ;
+mod visibility        ;
+mod watch             ;
+mod ws_adapter        ;
+mod ws_channel        ;
+mod ws_codec          ;
+mod ws_config         ;
+mod ws_event          ;
+mod ws_message        ;
+mod ws_message_stream ;
+mod ws_pool           ;
+mod ws_interceptor    ;
+mod ws_io             ;
+mod ws_sender         ;
+mod ws_state          ;
+mod ws_stats          ;
+mod ws_stream         ;
+mod ws_subscription   ;
+mod ws_url            ;
 
 pub use
 {
-	error             :: { WsErr  , WsErrKind                          } ,
-	ws_event          :: { WsEvent, CloseEvent, NextEvent, WsEventType } ,
-	ws_message        :: { WsMessage                                   } ,
-	ws_io             :: { WsIo                                        } ,
-	ws_stream         :: { WsStream                                    } ,
-	ws_state          :: { WsState                                     } ,
+	bridge            :: { serve_port, WsStreamRemote                                      } ,
+	callback_future   :: { listen, once, EventStream, ListenerGuard                        } ,
+	credit            :: { CreditFlow                                                      } ,
+	error             :: { WsErr  , WsErrKind                                              } ,
+	error_policy      :: { ErrorPolicy                                                     } ,
+	extension         :: { Extension                                                       } ,
+	handshake         :: { handshake, Handshake                                            } ,
+	heartbeat         :: { Heartbeat, Visibility                                           } ,
+	record            :: { Recorder, Recording, Recorded, Entry                            } ,
+	replay            :: { Replay, Timing, Matching                                        } ,
+	retry             :: { RetryPolicy, RetryProgress, RetryStatus, Failover               } ,
+	send_buffer       :: { SendBuffer, Overflow, Priority                                  } ,
+	shared            :: { serve_shared, WsStreamShared                                    } ,
+	spawner           :: { Spawner, RtSpawner                                              } ,
+	timer             :: { Timer, JsTimer                                                  } ,
+	watch             :: { Watch, WatchStream                                              } ,
+	ws_adapter        :: { WsStreamExt, TextOnly, BinaryOnly, Mismatch                     } ,
+	ws_channel        :: { WsChannel, WsChannelSink, WsChannelStream, ReuniteError         } ,
+	ws_codec          :: { WsEncode, WsDecode                                              } ,
+	ws_config         :: { WsConfig, DEFAULT_MAX_MESSAGE_SIZE, DEFAULT_SUBSCRIBER_CAPACITY } ,
+	ws_config         :: { DEFAULT_BACKLOG_THRESHOLD                                       } ,
+	ws_event          :: { WsEvent, CloseEvent, NextEvent, WsEventType                     } ,
+	ws_interceptor    :: { WsInterceptor                                                   } ,
+	ws_message        :: { WsMessage                                                       } ,
+	ws_message_stream :: { WsMessageStream, WsIncoming, DEFAULT_CHUNK_SIZE                 } ,
+	ws_io             :: { WsIo                                                            } ,
+	ws_pool           :: { WsPool, Balance                                                 } ,
+	ws_sender         :: { WsSender                                                        } ,
+	ws_stream         :: { WsStream                                                        } ,
+	ws_state          :: { WsState                                                         } ,
+	ws_stats          :: { WsStats                                                         } ,
+	ws_subscription   :: { WsSubscription, SharedWsMessage                                 } ,
+	ws_url            :: { WsUrl                                                           } ,
 };
 
+#[ cfg( feature = "cbor" ) ] mod cbor;
+#[ cfg( feature = "cbor" ) ] pub use cbor::CborCodec;
+
+#[ cfg( feature = "js_api" ) ] mod js_api;
+#[ cfg( feature = "js_api" ) ] pub use js_api::JsWsConnection;
+
+#[ cfg( feature = "mock" ) ] mod mock;
+#[ cfg( feature = "mock" ) ] pub use mock::MockTimer;
+
 
 
 mod import
 {
 	pub(crate) use
 	{
-		async_runtime :: { rt                                                                            } ,
-		bitflags      :: { bitflags                                                                      } ,
-		failure       :: { Backtrace, Fail, Context as FailContext                                       } ,
-		futures       :: { channel::mpsc::{ Receiver, UnboundedReceiver }, Poll                          } ,
-		futures       :: { prelude::{ Stream, Sink, AsyncWrite, AsyncRead }, ready, future::ready        } ,
-		futures       :: { stream::{ StreamExt, FilterMap }, future::Ready                               } ,
-		std           :: { io, cmp, collections::VecDeque, fmt, task::{ Context, Waker }, future::Future } ,
-		std           :: { rc::Rc, cell::{ RefCell }, pin::Pin, convert::{ TryFrom, TryInto }            } ,
-		log           :: { *                                                                             } ,
-		js_sys        :: { ArrayBuffer, Uint8Array                                                       } ,
-		wasm_bindgen  :: { closure::Closure, JsCast, JsValue, UnwrapThrowExt                             } ,
-		web_sys       :: { *, BinaryType, Blob, WebSocket, CloseEvent as JsCloseEvt, DomException        } ,
-		js_sys        :: { Array                                                                         } ,
-		pharos        :: { Pharos, Observable, UnboundedObservable                                       } ,
+		async_runtime :: { rt                                                                                  } ,
+		bitflags      :: { bitflags                                                                            } ,
+		failure       :: { Backtrace, Fail, Context as FailContext, Compat                                     } ,
+		futures       :: { channel::mpsc::{ Receiver, UnboundedReceiver }, Poll                                } ,
+		futures       :: { prelude::{ Stream, Sink, AsyncWrite, AsyncRead }, ready, future::ready              } ,
+		futures       :: { stream::{ StreamExt, FilterMap }, future::{ Ready, select, abortable, AbortHandle } } ,
+		std           :: { io, cmp, collections::VecDeque, fmt, task::{ Context, Waker }, future::Future       } ,
+		std           :: { rc::Rc, cell::{ RefCell }, pin::Pin, convert::{ TryFrom, TryInto }                  } ,
+		log           :: { *                                                                                   } ,
+		js_sys        :: { ArrayBuffer, JsString, Uint8Array                                                   } ,
+		wasm_bindgen  :: { closure::Closure, JsCast, JsValue, UnwrapThrowExt                                   } ,
+		web_sys       :: { *, BinaryType, Blob, WebSocket, CloseEvent as JsCloseEvt, DomException              } ,
+		js_sys        :: { Array                                                                               } ,
+		pharos        :: { Pharos, Observable, UnboundedObservable                                             } ,
 	};
 }
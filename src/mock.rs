@@ -0,0 +1,159 @@
+use
+{
+	crate   :: { import::*, Timer                      } ,
+	futures :: { future::LocalBoxFuture                } ,
+	std     :: { time::Duration, collections::BTreeMap } ,
+};
+
+
+/// A [Timer] that only moves forward when you call [MockTimer::advance]. Clones share the same clock,
+/// so you can keep one to drive the clock and give another one to the code under test.
+///
+/// ```
+/// use { ws_stream_wasm::{ MockTimer, Timer }, std::time::Duration, futures::{ executor::block_on, future::{ select, ready, Either } } };
+///
+/// let timer = MockTimer::new();
+///
+/// let mut sleep = timer.sleep( Duration::from_secs( 5 ) );
+///
+/// timer.advance( Duration::from_secs( 4 ) );
+/// assert!( matches!( block_on( select( &mut sleep, ready(()) ) ), Either::Right(_) ) );
+///
+/// timer.advance( Duration::from_secs( 1 ) );
+/// assert!( matches!( block_on( select( &mut sleep, ready(()) ) ), Either::Left(_) ) );
+/// ```
+//
+#[ derive( Debug, Clone, Default ) ]
+//
+pub struct MockTimer
+{
+	clock: Rc<RefCell< Clock >>,
+}
+
+
+
+#[ derive( Debug, Default ) ]
+//
+struct Clock
+{
+	now    : Duration                            ,
+	next_id: u64                                 ,
+
+	// The deadline and the waker of every sleep that was polled and isn't due yet, by id of the sleep.
+	//
+	wakers : BTreeMap< u64, (Duration, Waker) >  ,
+}
+
+
+
+impl MockTimer
+{
+	/// Create a timer at time 0.
+	//
+	pub fn new() -> Self
+	{
+		Self::default()
+	}
+
+
+	/// How much time has passed since the timer was created.
+	//
+	pub fn now( &self ) -> Duration
+	{
+		self.clock.borrow().now
+	}
+
+
+	/// Move the clock forward, waking up all the sleeps that are due. The woken tasks only run when
+	/// your executor polls them.
+	//
+	pub fn advance( &self, duration: Duration )
+	{
+		let wakers =
+		{
+			let mut clock = self.clock.borrow_mut();
+
+			clock.now += duration;
+
+			let now = clock.now;
+			let due: Vec<u64> = clock.wakers.iter().filter( |(_, (d, _))| *d <= now ).map( |(id, _)| *id ).collect();
+
+			due.into_iter().filter_map( |id| clock.wakers.remove( &id ) ).map( |(_, w)| w ).collect::<Vec<_>>()
+		};
+
+		// Wake them after releasing the clock, in case a waker polls right away.
+		//
+		wakers.into_iter().for_each( Waker::wake );
+	}
+
+
+	/// How many sleeps are waiting to be woken up: those that were polled, aren't due yet and weren't dropped.
+	//
+	pub fn pending( &self ) -> usize
+	{
+		self.clock.borrow().wakers.len()
+	}
+}
+
+
+
+impl Timer for MockTimer
+{
+	fn sleep( &self, duration: Duration ) -> LocalBoxFuture<'static, ()>
+	{
+		let mut clock = self.clock.borrow_mut();
+		let id        = clock.next_id;
+
+		clock.next_id += 1;
+
+		Box::pin( MockSleep { clock: self.clock.clone(), deadline: clock.now + duration, id } )
+	}
+}
+
+
+
+struct MockSleep
+{
+	clock   : Rc<RefCell< Clock >>,
+	deadline: Duration            ,
+	id      : u64                 ,
+}
+
+
+
+impl Future for MockSleep
+{
+	type Output = ();
+
+	fn poll( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll<Self::Output>
+	{
+		let mut clock = self.clock.borrow_mut();
+
+		if clock.now >= self.deadline
+		{
+			clock.wakers.remove( &self.id );
+
+			return Poll::Ready(());
+		}
+
+		// Keep one waker per sleep, the one of the task that polled last.
+		//
+		match clock.wakers.get( &self.id )
+		{
+			Some(( _, w )) if w.will_wake( cx.waker() ) => {}
+			_ => { clock.wakers.insert( self.id, ( self.deadline, cx.waker().clone() ) ); }
+		}
+
+		Poll::Pending
+	}
+}
+
+
+
+impl Drop for MockSleep
+{
+	fn drop( &mut self )
+	{
+		self.clock.borrow_mut().wakers.remove( &self.id );
+	}
+}
@@ -0,0 +1,125 @@
+use
+{
+	crate  :: { import::*, ListenerGuard } ,
+	js_sys :: { global, Reflect          } ,
+};
+
+
+// The global scope as an EventTarget. Both windows and workers have `online` and `offline` events
+// on the global scope. If it's not an EventTarget at all, we just won't listen.
+//
+fn global_target() -> Option<EventTarget>
+{
+	global().dyn_into::<EventTarget>().ok()
+}
+
+
+
+/// Whether the browser thinks we are connected to the network (`navigator.onLine`). When we can't tell,
+/// we assume we are online, so we never wait for an event that will not come.
+//
+pub(crate) fn is_online() -> bool
+{
+	Reflect::get( &global(), &"navigator".into() )
+
+		.and_then( |nav| Reflect::get( &nav, &"onLine".into() ) )
+		.ok()
+		.and_then( |online| online.as_bool() )
+		.unwrap_or( true )
+}
+
+
+
+/// Listens to the `online` and `offline` events as long as it is alive.
+//
+pub(crate) struct NetworkGuard
+{
+	_online : ListenerGuard,
+	_offline: ListenerGuard,
+}
+
+
+
+impl NetworkGuard
+{
+	/// The callback is called with `true` when the network comes back and with `false` when it goes away.
+	/// Returns `None` if there is no global scope we can listen on.
+	//
+	pub(crate) fn new( callback: impl Fn(bool) + 'static ) -> Option<Self>
+	{
+		let target = global_target()?;
+
+		let callback  = Rc::new( callback );
+		let callback2 = callback.clone();
+
+		Some( Self
+		{
+			_online : ListenerGuard::new( &target, "online" , move |_| callback ( true  ) ),
+			_offline: ListenerGuard::new( &target, "offline", move |_| callback2( false ) ),
+		})
+	}
+}
+
+
+
+#[ derive( Default ) ]
+//
+struct OnlineState
+{
+	fired: bool         ,
+	waker: Option<Waker>,
+}
+
+
+
+/// A future that resolves on the next `online` event. If we can't listen for the event,
+/// it never resolves. The listener is removed when this is dropped.
+//
+pub(crate) struct Online
+{
+	state : Rc<RefCell< OnlineState >>,
+	_guard: Option< NetworkGuard >    ,
+}
+
+
+
+pub(crate) fn online() -> Online
+{
+	let state  = Rc::new( RefCell::new( OnlineState::default() ) );
+	let state2 = state.clone();
+
+	let guard = NetworkGuard::new( move |online|
+	{
+		if !online { return }
+
+		let waker =
+		{
+			let mut state = state2.borrow_mut();
+
+			state.fired = true;
+			state.waker.take()
+		};
+
+		if let Some( w ) = waker { w.wake() }
+	});
+
+	Online { state, _guard: guard }
+}
+
+
+
+impl Future for Online
+{
+	type Output = ();
+
+	fn poll( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll<Self::Output>
+	{
+		let mut state = self.state.borrow_mut();
+
+		if state.fired { return Poll::Ready(()) }
+
+		state.waker = Some( cx.waker().clone() );
+
+		Poll::Pending
+	}
+}
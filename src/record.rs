@@ -0,0 +1,167 @@
+use
+{
+	crate  :: { import::*, WsEvent, WsInterceptor, WsIo, WsMessage, WsStream } ,
+	js_sys :: { Date                                                         } ,
+	std    :: { time::Duration                                               } ,
+};
+
+
+/// What happened on a recorded connection.
+//
+#[ derive( Debug, Clone, PartialEq, Eq ) ]
+#[ cfg_attr( feature = "serde", derive( serde::Serialize, serde::Deserialize ) ) ]
+//
+pub enum Recorded
+{
+	/// A message we sent.
+	//
+	Sent( WsMessage ),
+
+	/// A message we received.
+	//
+	Received( WsMessage ),
+
+	/// An event of the connection, see [WsEvent].
+	//
+	Event( WsEvent ),
+}
+
+
+
+/// One entry of a [Recording].
+//
+#[ derive( Debug, Clone, PartialEq, Eq ) ]
+#[ cfg_attr( feature = "serde", derive( serde::Serialize, serde::Deserialize ) ) ]
+//
+pub struct Entry
+{
+	/// The time since the recording started.
+	//
+	pub at: Duration,
+
+	/// What happened.
+	//
+	pub item: Recorded,
+}
+
+
+
+/// The traffic of a connection, captured by a [Recorder], to play it back with [Replay](crate::Replay). With the `serde`
+/// feature it can be serialized, so you can save what happened for a user and replay it in a test.
+///
+/// You can also write one by hand, the entries are public.
+//
+#[ derive( Debug, Clone, PartialEq, Eq, Default ) ]
+#[ cfg_attr( feature = "serde", derive( serde::Serialize, serde::Deserialize ) ) ]
+//
+pub struct Recording
+{
+	/// The entries in the order they happened.
+	//
+	pub entries: Vec<Entry>,
+}
+
+
+
+/// Records all messages and events of a connection into a [Recording].
+///
+/// Messages are recorded by an interceptor, so they are recorded when they arrive rather than when you read
+/// them, and what is recorded depends on the interceptors that were installed before it: outgoing messages
+/// as changed by those interceptors, incoming messages as sent by the server. Messages that are rejected by
+/// the connection, eg. because they are too big, are not recorded.
+///
+/// ```no_run
+/// # #![ feature( async_await ) ]
+/// use ws_stream_wasm::*;
+///
+/// # async fn example() -> Result<(), WsErr> {
+/// let ( ws, wsio ) = WsStream::connect( "ws://127.0.0.1:3012" ).await?;
+/// let recorder     = Recorder::start( &ws, &wsio );
+///
+/// // use the connection...
+///
+/// let recording = recorder.recording();
+/// # Ok(())
+/// # }
+/// ```
+//
+#[ derive( Clone ) ]
+//
+pub struct Recorder
+{
+	log  : Rc<RefCell< Recording >>,
+	start: f64                     ,
+}
+
+
+
+impl Recorder
+{
+	/// Start recording the connection. Events are forwarded by the [Spawner](crate::Spawner) of the connection.
+	//
+	pub fn start( ws: &WsStream, wsio: &WsIo ) -> Self
+	{
+		let recorder = Self { log: Rc::new( RefCell::new( Recording::default() ) ), start: Date::now() };
+		let rec2     = recorder.clone();
+		let mut evts = ws.events_unbounded();
+
+		wsio.add_interceptor( recorder.clone() );
+
+		ws.spawner().spawn_local( async move
+		{
+			while let Some( evt ) = evts.next().await
+			{
+				rec2.push( Recorded::Event( evt ) );
+			}
+		});
+
+		recorder
+	}
+
+
+	/// A copy of what was recorded so far.
+	//
+	pub fn recording( &self ) -> Recording
+	{
+		self.log.borrow().clone()
+	}
+
+
+	fn push( &self, item: Recorded )
+	{
+		let elapsed = ( Date::now() - self.start ).max( 0.0 );
+		let at      = Duration::from_micros( ( elapsed * 1000.0 ) as u64 );
+
+		self.log.borrow_mut().entries.push( Entry { at, item } );
+	}
+}
+
+
+
+impl WsInterceptor for Recorder
+{
+	fn on_send( &mut self, msg: WsMessage ) -> Option<WsMessage>
+	{
+		self.push( Recorded::Sent( msg.clone() ) );
+
+		Some( msg )
+	}
+
+
+	fn on_receive( &mut self, msg: WsMessage ) -> Option<WsMessage>
+	{
+		self.push( Recorded::Received( msg.clone() ) );
+
+		Some( msg )
+	}
+}
+
+
+
+impl fmt::Debug for Recorder
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		write!( f, "Recorder with {} entries", self.log.borrow().entries.len() )
+	}
+}
@@ -0,0 +1,312 @@
+use
+{
+	crate   :: { import::*, timer::SharedTimer, Recorded, Recording, Timer, WsErr, WsErrKind, WsEvent, WsMessage } ,
+	futures :: { future::LocalBoxFuture                                                                           } ,
+	std     :: { time::Duration                                                                                   } ,
+};
+
+
+/// How [Replay] deals with the timestamps of the recording.
+//
+#[ derive( Debug, Clone, Copy, PartialEq, Eq, Default ) ]
+//
+pub enum Timing
+{
+	/// Yield messages as soon as they are due.
+	//
+	#[ default ]
+	//
+	AsFastAsPossible,
+
+	/// Wait between messages as long as the recording did, using the [Timer] of the replay.
+	//
+	Original,
+}
+
+
+
+/// How [Replay] compares the messages you send with the ones in the recording.
+//
+#[ derive( Debug, Clone, Copy, PartialEq, Eq, Default ) ]
+//
+pub enum Matching
+{
+	/// A message that doesn't match, or one too many, fails with [WsErrKind::ReplayMismatch].
+	//
+	#[ default ]
+	//
+	Strict,
+
+	/// Mismatches are logged and counted, see [Replay::mismatches], but sending succeeds.
+	//
+	Lenient,
+}
+
+
+
+/// Plays back a [Recording] as a `Stream`/`Sink` of [WsMessage], like [WsIo](crate::WsIo), so you can test your client code
+/// against traffic that was recorded with a [Recorder](crate::Recorder), without a server.
+///
+/// The replay follows the recording: a received message is only yielded once you sent all messages that were sent
+/// before it, so a client that waits for an answer gets it after asking the question. The messages you send are compared
+/// with the recorded ones in order, see [Matching]. A recorded close event ends the stream, as does the end of the
+/// recording. Other events are skipped.
+///
+/// ```
+/// use { ws_stream_wasm::*, futures::{ executor::block_on, prelude::* } };
+///
+/// let recording = Recording { entries: vec!
+/// [
+///    Entry { at: Default::default(), item: Recorded::Sent    ( WsMessage::from( "ping" ) ) },
+///    Entry { at: Default::default(), item: Recorded::Received( WsMessage::from( "pong" ) ) },
+/// ]};
+///
+/// let mut replay = Replay::new( recording );
+///
+/// block_on( async
+/// {
+///    replay.send( WsMessage::from( "ping" ) ).await.expect( "matches" );
+///
+///    assert_eq!( WsMessage::from( "pong" ), replay.next().await.unwrap().unwrap() );
+///    assert!( replay.next().await.is_none() );
+/// });
+/// ```
+//
+pub struct Replay
+{
+	recording : Recording                            ,
+	timing    : Timing                               ,
+	matching  : Matching                             ,
+	timer     : SharedTimer                          ,
+
+	// The next entry the stream looks at and the next one to compare a sent message with.
+	//
+	recv      : usize                                ,
+	sent      : usize                                ,
+	mismatches: usize                                ,
+
+	// The timestamp of the last entry the stream yielded, and the sleep until the next one.
+	//
+	clock     : Duration                             ,
+	sleep     : Option< LocalBoxFuture<'static, ()> >,
+
+	waker     : Option< Waker >                      ,
+}
+
+
+
+impl Replay
+{
+	/// Replay `recording` as fast as possible with strict matching.
+	//
+	pub fn new( recording: Recording ) -> Self
+	{
+		Self
+		{
+			recording                             ,
+			timing    : Timing::default()         ,
+			matching  : Matching::default()       ,
+			timer     : SharedTimer::default()    ,
+			recv      : 0                         ,
+			sent      : 0                         ,
+			mismatches: 0                         ,
+			clock     : Duration::from_secs( 0 )  ,
+			sleep     : None                      ,
+			waker     : None                      ,
+		}
+	}
+
+
+	/// Whether to honor the timing of the recording. Defaults to [Timing::AsFastAsPossible].
+	//
+	pub fn timing( mut self, timing: Timing ) -> Self
+	{
+		self.timing = timing;
+		self
+	}
+
+
+	/// How to compare sent messages. Defaults to [Matching::Strict].
+	//
+	pub fn matching( mut self, matching: Matching ) -> Self
+	{
+		self.matching = matching;
+		self
+	}
+
+
+	/// The timer for [Timing::Original]. Defaults to [JsTimer](crate::JsTimer).
+	//
+	pub fn timer( mut self, timer: impl Timer + 'static ) -> Self
+	{
+		self.timer = SharedTimer::new( timer );
+		self
+	}
+
+
+	/// The number of sent messages that didn't match the recording with [Matching::Lenient], including sends
+	/// after all recorded ones were matched.
+	//
+	pub fn mismatches( &self ) -> usize
+	{
+		self.mismatches
+	}
+
+
+	/// The recorded messages you haven't sent yet. Check that this is empty at the end of a test to verify your
+	/// code sent everything it should have.
+	//
+	pub fn unsent( &self ) -> Vec<WsMessage>
+	{
+		self.recording.entries[ self.sent.. ].iter().filter_map( |e| match &e.item
+		{
+			Recorded::Sent( msg ) => Some( msg.clone() ),
+			_                     => None,
+		})
+
+		.collect()
+	}
+
+
+	// Move the send cursor to the next recorded sent message.
+	//
+	fn next_sent( &mut self ) -> Option<WsMessage>
+	{
+		while let Some( entry ) = self.recording.entries.get( self.sent )
+		{
+			self.sent += 1;
+
+			if let Recorded::Sent( msg ) = &entry.item { return Some( msg.clone() ) }
+		}
+
+		None
+	}
+}
+
+
+
+impl Stream for Replay
+{
+	type Item = Result<WsMessage, WsErr>;
+
+
+	fn poll_next( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll<Option< Self::Item >>
+	{
+		loop
+		{
+			let entry = match self.recording.entries.get( self.recv )
+			{
+				Some( entry ) => entry.clone(),
+				None          => return Poll::Ready( None ),
+			};
+
+
+			// Wait for the client to send what it sent before this in the recording.
+			//
+			if let Recorded::Sent(_) = entry.item
+			{
+				if self.sent <= self.recv
+				{
+					self.waker = Some( cx.waker().clone() );
+					return Poll::Pending;
+				}
+
+				self.recv += 1;
+				continue;
+			}
+
+
+			if self.timing == Timing::Original && entry.at > self.clock
+			{
+				if self.sleep.is_none()
+				{
+					let delay  = entry.at - self.clock;
+					self.sleep = Some( self.timer.sleep( delay ) );
+				}
+
+				if let Some( sleep ) = self.sleep.as_mut()
+				{
+					ready!( sleep.as_mut().poll( cx ) );
+				}
+
+				self.sleep = None;
+				self.clock = entry.at;
+			}
+
+
+			self.recv += 1;
+
+			match entry.item
+			{
+				Recorded::Received( msg )                => return Poll::Ready( Some( Ok( msg ) ) ),
+				Recorded::Event   ( WsEvent::Close(_) )  => return Poll::Ready( None ),
+				_                                        => continue,
+			}
+		}
+	}
+}
+
+
+
+impl Sink<WsMessage> for Replay
+{
+	type Error = WsErr;
+
+
+	fn poll_ready( self: Pin<&mut Self>, _: &mut Context<'_> ) -> Poll<Result<(), Self::Error>>
+	{
+		Poll::Ready( Ok(()) )
+	}
+
+
+	fn start_send( mut self: Pin<&mut Self>, item: WsMessage ) -> Result<(), Self::Error>
+	{
+		let expected = self.next_sent();
+
+		if let Some( w ) = self.waker.take() { w.wake() }
+
+		if expected.as_ref() == Some( &item ) { return Ok(()) }
+
+
+		let err = WsErr::from( WsErrKind::ReplayMismatch{ expected, got: item } );
+
+		match self.matching
+		{
+			Matching::Strict =>
+			{
+				error!( "{}", err );
+				Err( err )
+			}
+
+			Matching::Lenient =>
+			{
+				warn!( "{}", err );
+
+				self.mismatches += 1;
+				Ok(())
+			}
+		}
+	}
+
+
+	fn poll_flush( self: Pin<&mut Self>, _: &mut Context<'_> ) -> Poll<Result<(), Self::Error>>
+	{
+		Poll::Ready( Ok(()) )
+	}
+
+
+	fn poll_close( self: Pin<&mut Self>, _: &mut Context<'_> ) -> Poll<Result<(), Self::Error>>
+	{
+		Poll::Ready( Ok(()) )
+	}
+}
+
+
+
+impl fmt::Debug for Replay
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		write!( f, "Replay at entry {} of {}", self.recv, self.recording.entries.len() )
+	}
+}
@@ -0,0 +1,347 @@
+use
+{
+	crate :: { import::*, WsErrKind, Timer, timer::SharedTimer, Watch, WatchStream } ,
+	std   :: { time::Duration       } ,
+};
+
+
+/// Configures how [WsStream::connect_retry](crate::WsStream::connect_retry) and
+/// [WsStream::connect_failover](crate::WsStream::connect_failover) retry the initial connection.
+///
+/// The defaults are: retry forever, waiting 500ms, 1s, 2s, 4s and from then on 8s between
+/// attempts, don't abort on any close code and use [Failover::RoundRobin] when there are several urls.
+///
+/// Errors that can not be solved by retrying, [WsErrKind::ForbiddenPort] and [WsErrKind::InvalidUrl],
+/// are always returned immediately.
+///
+/// ```
+/// use { ws_stream_wasm::{ RetryPolicy, Failover }, std::time::Duration };
+///
+/// let policy = RetryPolicy::default()
+///
+///    .delays      ( vec![ Duration::from_millis( 100 ), Duration::from_secs( 1 ) ] )
+///    .max_attempts( 5    )
+///    .abort_on    ( 1008 )
+///    .failover    ( Failover::Sticky( 2 ) )
+///    .on_progress ( |p| println!( "attempt {} failed, retrying in {:?}", p.attempt, p.next_delay ) )
+/// ;
+/// ```
+//
+#[ derive( Clone ) ]
+//
+pub struct RetryPolicy
+{
+	delays      : Vec<Duration>        ,
+	max_attempts: Option<usize>        ,
+	abort_codes : Vec<u16>             ,
+	failover    : Failover             ,
+	on_progress : Option< ProgressFn > ,
+	timer       : SharedTimer          ,
+	status      : Watch<RetryStatus>   ,
+}
+
+
+type ProgressFn = Rc< dyn Fn( &RetryProgress ) >;
+
+
+
+/// How [WsStream::connect_failover](crate::WsStream::connect_failover) chooses the url for the next attempt.
+//
+#[ derive( Clone, Copy, Debug, PartialEq, Eq ) ]
+//
+pub enum Failover
+{
+	/// Move on to the next url after every failed attempt.
+	//
+	RoundRobin,
+
+	/// Keep trying the same url until it failed this many times in a row, then move on to the next one.
+	/// `Sticky( 2 )` tries each host twice before moving on. `Sticky( 0 )` is the same as `Sticky( 1 )`.
+	//
+	Sticky( usize ),
+}
+
+
+
+/// Information about a failed connection attempt, passed to the callback set with [RetryPolicy::on_progress].
+//
+#[ derive( Clone, Debug, PartialEq, Eq ) ]
+//
+pub struct RetryProgress
+{
+	/// The number of the attempt that failed, starting at 1.
+	//
+	pub attempt   : usize           ,
+
+	/// The url of the attempt that failed.
+	//
+	pub url       : String          ,
+
+	/// Why the attempt failed.
+	//
+	pub error     : WsErrKind       ,
+
+	/// How long we will wait before the next attempt. `None` means we are giving up.
+	//
+	pub next_delay: Option<Duration>,
+}
+
+
+
+/// Where [WsStream::connect_retry](crate::WsStream::connect_retry) is at, see [RetryPolicy::status_signal].
+//
+#[ derive( Clone, Debug, PartialEq, Eq ) ]
+//
+pub enum RetryStatus
+{
+	/// No connection attempt was made yet.
+	//
+	Idle,
+
+	/// Attempt number `attempt`, starting at 1, is connecting to `url`.
+	//
+	Connecting
+	{
+		/// The number of the attempt.
+		//
+		attempt: usize,
+
+		/// The url of the attempt.
+		//
+		url: String,
+	},
+
+	/// Attempt number `attempt` failed and we will try again after `next_retry`, or when the network comes back.
+	//
+	Waiting
+	{
+		/// The number of the attempt that failed.
+		//
+		attempt: usize,
+
+		/// How long we wait before the next attempt.
+		//
+		next_retry: Duration,
+	},
+
+	/// Attempt number `attempt` succeeded.
+	//
+	Connected
+	{
+		/// The number of the attempt that succeeded.
+		//
+		attempt: usize,
+	},
+
+	/// We gave up after attempt number `attempt`.
+	//
+	GaveUp
+	{
+		/// The number of the last attempt.
+		//
+		attempt: usize,
+	},
+}
+
+
+
+impl RetryPolicy
+{
+	/// The delays to wait between consecutive attempts. The last delay is repeated for all the
+	/// following attempts. An empty list means retrying immediately.
+	//
+	pub fn delays( mut self, delays: Vec<Duration> ) -> Self
+	{
+		self.delays = delays;
+		self
+	}
+
+
+	/// The maximum number of attempts, including the first one. `None` means retrying until
+	/// the connection succeeds.
+	//
+	pub fn max_attempts( mut self, max: impl Into<Option<usize>> ) -> Self
+	{
+		self.max_attempts = max.into();
+		self
+	}
+
+
+	/// Stop retrying if a connection attempt fails with this close code. Can be called several times.
+	//
+	pub fn abort_on( mut self, code: u16 ) -> Self
+	{
+		self.abort_codes.push( code );
+		self
+	}
+
+
+	/// How to choose the url for the next attempt when there are several. This has no effect
+	/// with [WsStream::connect_retry](crate::WsStream::connect_retry), which only has one url.
+	//
+	pub fn failover( mut self, failover: Failover ) -> Self
+	{
+		self.failover = failover;
+		self
+	}
+
+
+	/// Set a callback that will be called after every failed attempt, for example to give feedback
+	/// in the user interface.
+	//
+	pub fn on_progress( mut self, f: impl Fn( &RetryProgress ) + 'static ) -> Self
+	{
+		self.on_progress = Some( Rc::new( f ) );
+		self
+	}
+
+
+	/// The status of the connection attempts made with this policy, as a stream that yields the current status
+	/// right away and then every change. Clones of the policy share the status. The stream ends when the policy
+	/// and all its clones are dropped, so after `connect_retry` returned, unless you kept a clone.
+	///
+	/// This is meant for showing "reconnecting, attempt 3" in a user interface. For the error of each failed
+	/// attempt, use [RetryPolicy::on_progress].
+	//
+	pub fn status_signal( &self ) -> WatchStream<RetryStatus>
+	{
+		self.status.subscribe()
+	}
+
+
+	/// The [Timer] used to wait between attempts. Defaults to [JsTimer](crate::JsTimer).
+	//
+	pub fn timer( mut self, timer: impl Timer + 'static ) -> Self
+	{
+		self.timer = SharedTimer::new( timer );
+		self
+	}
+
+
+	/// Wait for `duration` with the timer of this policy.
+	//
+	pub(crate) fn sleep( &self, duration: Duration ) -> impl Future< Output = () >
+	{
+		self.timer.sleep( duration )
+	}
+
+
+	/// The delay to wait after attempt number `attempt` (starting at 1) failed.
+	//
+	pub(crate) fn delay( &self, attempt: usize ) -> Duration
+	{
+		if self.delays.is_empty() { return Duration::from_millis( 0 ) }
+
+		self.delays[ cmp::min( attempt, self.delays.len() ) - 1 ]
+	}
+
+
+	/// Whether we should try again after attempt number `attempt` failed with `err`.
+	//
+	pub(crate) fn should_retry( &self, attempt: usize, err: &WsErrKind ) -> bool
+	{
+		if !self.attempts_left( attempt ) { return false }
+
+		if Self::is_permanent( err ) { return false }
+
+		match err
+		{
+			WsErrKind::ConnectionFailed( evt ) => !self.abort_codes.contains( &evt.code ),
+
+			_ => true,
+		}
+	}
+
+
+	/// Whether max_attempts allows another attempt after attempt number `attempt`.
+	//
+	pub(crate) fn attempts_left( &self, attempt: usize ) -> bool
+	{
+		match self.max_attempts
+		{
+			Some( max ) => attempt < max,
+			None        => true         ,
+		}
+	}
+
+
+	/// Errors that will not go away by trying the same url again.
+	//
+	pub(crate) fn is_permanent( err: &WsErrKind ) -> bool
+	{
+		matches!( err, WsErrKind::ForbiddenPort | WsErrKind::InvalidUrl(_) )
+	}
+
+
+	/// The index of the url to use for the next attempt, given the current index, the number of consecutive
+	/// failures on the current url and the number of urls.
+	//
+	pub(crate) fn next_url( &self, current: usize, failures: usize, count: usize ) -> usize
+	{
+		let stay = match self.failover
+		{
+			Failover::RoundRobin  => false,
+			Failover::Sticky( n ) => failures < cmp::max( n, 1 ),
+		};
+
+		if stay { current } else { ( current + 1 ) % count }
+	}
+
+
+	pub(crate) fn progress( &self, progress: &RetryProgress )
+	{
+		if let Some( f ) = &self.on_progress { f( progress ) }
+	}
+
+
+	pub(crate) fn set_status( &self, status: RetryStatus )
+	{
+		self.status.set( status );
+	}
+}
+
+
+
+impl Default for RetryPolicy
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			delays: vec!
+			[
+				Duration::from_millis(  500 ),
+				Duration::from_millis( 1000 ),
+				Duration::from_millis( 2000 ),
+				Duration::from_millis( 4000 ),
+				Duration::from_millis( 8000 ),
+			],
+
+			max_attempts: None                  ,
+			abort_codes : Vec::new()            ,
+			failover    : Failover::RoundRobin  ,
+			on_progress : None                  ,
+			timer       : SharedTimer::default(),
+			status      : Watch::new( RetryStatus::Idle ),
+		}
+	}
+}
+
+
+
+impl fmt::Debug for RetryPolicy
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		f.debug_struct( "RetryPolicy" )
+
+			.field( "delays"      , &self.delays               )
+			.field( "max_attempts", &self.max_attempts         )
+			.field( "abort_codes" , &self.abort_codes          )
+			.field( "failover"    , &self.failover             )
+			.field( "on_progress" , &self.on_progress.is_some() )
+			.field( "timer"       , &self.timer                 )
+			.field( "status"      , &self.status                )
+			.finish()
+	}
+}
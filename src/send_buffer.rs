@@ -0,0 +1,433 @@
+use
+{
+	crate   :: { import::*, ws_message::byte_len, timer::SharedTimer, Timer, WsErr, WsErrKind     } ,
+	crate   :: { WsMessage, WsSender                                                             } ,
+	futures :: { future::{ poll_fn, FutureExt, LocalBoxFuture }                                  } ,
+	std     :: { time::Duration                                                                  } ,
+};
+
+
+/// What [SendBuffer::push] does when the buffer is full.
+//
+#[ derive( Debug, Clone, Copy, PartialEq, Eq, Default ) ]
+//
+pub enum Overflow
+{
+	/// Refuse the new message with [WsErrKind::SendBufferFull].
+	//
+	#[ default ]
+	//
+	Error,
+
+	/// Drop the oldest messages until the new one fits.
+	//
+	DropOldest,
+}
+
+
+
+/// The lane of a message in a [SendBuffer].
+//
+#[ derive( Debug, Clone, Copy, PartialEq, Eq, Default ) ]
+//
+pub enum Priority
+{
+	/// Sent before all normal messages, see [SendBuffer::fairness].
+	//
+	High,
+
+	/// The lane of [SendBuffer::push] and [SendBuffer::send_or_buffer].
+	//
+	#[ default ]
+	//
+	Normal,
+}
+
+
+
+/// Keeps outgoing messages while there is no connection, to send them in order once there is one again.
+///
+/// This crate doesn't reconnect by itself, so this is meant for the reconnect loop of your application: put messages
+/// in with [SendBuffer::send_or_buffer] or [SendBuffer::push] while disconnected, and when you have a new connection:
+///
+/// 1. do what you need to with the new connection first, eg. send authentication messages, so they jump the queue,
+/// 2. if the buffered messages are no longer valid, eg. because the server requires a new authentication,
+///    [clear](SendBuffer::clear) the buffer,
+/// 3. [flush](SendBuffer::flush) it to the new connection.
+///
+/// ```no_run
+/// # #![ feature( async_await ) ]
+/// use ws_stream_wasm::*;
+///
+/// # async fn example( buffer: &mut SendBuffer, needs_auth: bool ) -> Result<(), WsErr> {
+/// let ( _ws, wsio ) = WsStream::connect_retry( "ws://127.0.0.1:3012", RetryPolicy::default() ).await?;
+/// let mut sender    = wsio.sender();
+///
+/// sender.send_text_ref( "auth:secret" )?;
+///
+/// if needs_auth { buffer.clear() }
+///
+/// buffer.flush( &mut sender ).await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Limits are both in messages and in bytes, for text counting the UTF-8 bytes. By default there are no limits.
+///
+/// Messages go in one of two lanes, see [Priority]. Flushing sends all high priority messages before the normal ones,
+/// so latency critical messages don't wait behind bulk data. The order within a lane is kept. Under a steady stream of
+/// high priority messages, normal ones can wait forever, unless you set [SendBuffer::fairness]. A [WsSender] doesn't
+/// queue anything itself, it hands messages to the browser right away, so priorities only apply to messages that
+/// wait in a buffer.
+///
+/// Messages added with [SendBuffer::push_with_ttl] are silently dropped when they are still in the buffer once their
+/// time to live has passed, and counted in [SendBuffer::expired]. Once [SendBuffer::flush] hands a message to the sink,
+/// it can no longer expire.
+//
+#[ derive( Debug, Default ) ]
+//
+pub struct SendBuffer
+{
+	high        : VecDeque< Queued >    ,
+	normal      : VecDeque< Queued >    ,
+	bytes       : usize                 ,
+	max_messages: Option< usize >       ,
+	max_bytes   : Option< usize >       ,
+	overflow    : Overflow              ,
+
+	// See SendBuffer::fairness, and how many high priority messages were flushed in a row.
+	//
+	fairness    : Option< usize >       ,
+	streak      : usize                 ,
+
+	// For the time to live of messages.
+	//
+	timer       : SharedTimer           ,
+	expired     : u64                   ,
+}
+
+
+
+// A message in the buffer, with the sleep that ends it's time to live if it has one.
+//
+struct Queued
+{
+	msg   : WsMessage                              ,
+	expiry: Option< LocalBoxFuture<'static, ()> >  ,
+}
+
+
+impl Queued
+{
+	// We don't need to be woken up, we only look when we are about to send. Both the JsTimer and the MockTimer
+	// know whether they are due without being polled before.
+	//
+	fn is_expired( &mut self ) -> bool
+	{
+		self.expiry.as_mut().map( |sleep| sleep.now_or_never().is_some() ).unwrap_or( false )
+	}
+}
+
+
+impl fmt::Debug for Queued
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		f.debug_struct( "Queued" )
+
+			.field( "msg", &self.msg                )
+			.field( "ttl", &self.expiry.is_some() )
+			.finish()
+	}
+}
+
+
+
+impl SendBuffer
+{
+	/// An empty buffer without limits.
+	//
+	pub fn new() -> Self
+	{
+		Self::default()
+	}
+
+
+	/// The maximum number of messages in the buffer. `None` means unlimited. A maximum of 0 is treated as 1.
+	//
+	pub fn max_messages( mut self, max: impl Into<Option<usize>> ) -> Self
+	{
+		self.max_messages = max.into().map( |m| m.max( 1 ) );
+		self
+	}
+
+
+	/// The maximum number of bytes in the buffer. `None` means unlimited.
+	//
+	pub fn max_bytes( mut self, max: impl Into<Option<usize>> ) -> Self
+	{
+		self.max_bytes = max.into();
+		self
+	}
+
+
+	/// What to do when the buffer is full. Defaults to [Overflow::Error].
+	//
+	pub fn overflow( mut self, overflow: Overflow ) -> Self
+	{
+		self.overflow = overflow;
+		self
+	}
+
+
+	/// Let one normal message through after every `ratio` high priority messages sent in a row by [SendBuffer::flush],
+	/// so the normal lane can't starve. `None`, the default, always sends high priority messages first. A ratio of 0 is
+	/// treated as 1.
+	//
+	pub fn fairness( mut self, ratio: impl Into<Option<usize>> ) -> Self
+	{
+		self.fairness = ratio.into().map( |r| r.max( 1 ) );
+		self
+	}
+
+
+	/// The timer that measures the time to live of messages, see [SendBuffer::push_with_ttl]. Defaults to
+	/// [JsTimer](crate::JsTimer).
+	//
+	pub fn timer( mut self, timer: impl Timer + 'static ) -> Self
+	{
+		self.timer = SharedTimer::new( timer );
+		self
+	}
+
+
+	/// Add a message at the end of the normal lane. When that would exceed a limit, [Overflow] decides.
+	/// A message that exceeds `max_bytes` on its own is always refused with [WsErrKind::SendBufferFull].
+	//
+	pub fn push( &mut self, msg: WsMessage ) -> Result<(), WsErr>
+	{
+		self.push_with_priority( msg, Priority::Normal )
+	}
+
+
+	/// Add a message at the end of a lane. With [Overflow::DropOldest], the oldest normal messages are dropped
+	/// first, high priority ones only when there are no normal ones left.
+	//
+	pub fn push_with_priority( &mut self, msg: WsMessage, priority: Priority ) -> Result<(), WsErr>
+	{
+		self.enqueue( Queued { msg, expiry: None }, priority )
+	}
+
+
+	/// Add a message at the end of the normal lane, that is dropped instead of sent if it's still in the buffer
+	/// once `ttl` has passed.
+	//
+	pub fn push_with_ttl( &mut self, msg: WsMessage, ttl: Duration ) -> Result<(), WsErr>
+	{
+		let expiry = Some( self.timer.sleep( ttl ) );
+
+		self.enqueue( Queued { msg, expiry }, Priority::Normal )
+	}
+
+
+	fn enqueue( &mut self, queued: Queued, priority: Priority ) -> Result<(), WsErr>
+	{
+		// Don't let expired messages count against the limits.
+		//
+		self.prune();
+
+		let size = byte_len( &queued.msg );
+
+		if self.max_bytes.map( |max| size > max ).unwrap_or( false )
+		{
+			return Err( WsErrKind::SendBufferFull.into() );
+		}
+
+		while self.is_full( size )
+		{
+			match self.overflow
+			{
+				Overflow::Error => return Err( WsErrKind::SendBufferFull.into() ),
+
+				Overflow::DropOldest =>
+				{
+					let old = self.normal.pop_front().or_else( || self.high.pop_front() ).expect( "a full buffer isn't empty" );
+
+					warn!( "SendBuffer: dropping oldest message" );
+
+					self.bytes -= byte_len( &old.msg );
+				}
+			}
+		}
+
+		self.bytes += size;
+
+		match priority
+		{
+			Priority::High   => self.high  .push_back( queued ),
+			Priority::Normal => self.normal.push_back( queued ),
+		}
+
+		Ok(())
+	}
+
+
+	// Whether adding a message of `size` bytes exceeds a limit.
+	//
+	fn is_full( &self, size: usize ) -> bool
+	{
+		self.max_messages.map( |max| self.len() + 1  > max ).unwrap_or( false ) ||
+		self.max_bytes   .map( |max| self.bytes + size > max ).unwrap_or( false )
+	}
+
+
+	/// Send the message right away if the connection is open and nothing is waiting in the buffer, otherwise
+	/// add it to the buffer, so it will be sent after the messages that are already waiting. While the browser
+	/// holds more than [WsConfig::send_high_water](crate::WsConfig::send_high_water), the message is buffered as well.
+	//
+	pub fn send_or_buffer( &mut self, sender: &WsSender, msg: WsMessage ) -> Result<(), WsErr>
+	{
+		self.send_with_priority( sender, msg, Priority::Normal )
+	}
+
+
+	/// Like [SendBuffer::send_or_buffer], in a lane. A high priority message is sent right away when no other high
+	/// priority message is waiting, even if normal ones are.
+	//
+	pub fn send_with_priority( &mut self, sender: &WsSender, msg: WsMessage, priority: Priority ) -> Result<(), WsErr>
+	{
+		let waiting = match priority
+		{
+			Priority::High   => !self.high.is_empty(),
+			Priority::Normal => !self.is_empty()     ,
+		};
+
+		if !waiting && sender.is_ready()
+		{
+			return Pin::new( &mut &*sender ).start_send( msg );
+		}
+
+		self.push_with_priority( msg, priority )
+	}
+
+
+	/// Send all buffered messages to `sink`, high priority ones first, and within a lane in the order they were added.
+	/// Messages that expire before the sink is ready for them are dropped. When sending fails, the messages
+	/// that haven't been sent yet stay in the buffer, except the one that failed in `start_send`, which is lost.
+	//
+	pub async fn flush<S>( &mut self, sink: &mut S ) -> Result<(), S::Error>
+
+		where S: Sink<WsMessage> + Unpin
+	{
+		loop
+		{
+			self.prune();
+
+			if self.is_empty() { break }
+
+			poll_fn( |cx| Pin::new( &mut *sink ).poll_ready( cx ) ).await?;
+
+			// Only take the message once the sink is ready for it, it might have expired while we waited.
+			//
+			self.prune();
+
+			let msg = match self.pop()
+			{
+				Some( queued ) => queued.msg,
+				None           => break     ,
+			};
+
+			self.bytes -= byte_len( &msg );
+
+			Pin::new( &mut *sink ).start_send( msg )?;
+		}
+
+		poll_fn( |cx| Pin::new( &mut *sink ).poll_flush( cx ) ).await
+	}
+
+
+	// Drop the messages whose time to live has passed.
+	//
+	fn prune( &mut self )
+	{
+		for lane in &mut [ &mut self.high, &mut self.normal ]
+		{
+			let before = lane.len();
+
+			for mut queued in std::mem::take( *lane )
+			{
+				if queued.is_expired() { self.bytes -= byte_len( &queued.msg ) }
+				else                   { lane.push_back( queued )              }
+			}
+
+			let expired = before - lane.len();
+
+			if expired > 0
+			{
+				trace!( "SendBuffer: {} message(s) expired", expired );
+
+				self.expired += expired as u64;
+			}
+		}
+	}
+
+
+	// Take the next message to send, taking fairness into account.
+	//
+	fn pop( &mut self ) -> Option<Queued>
+	{
+		let starved = self.fairness.map( |ratio| self.streak >= ratio ).unwrap_or( false );
+
+		if !self.high.is_empty() && ( !starved || self.normal.is_empty() )
+		{
+			self.streak += 1;
+			return self.high.pop_front();
+		}
+
+		self.streak = 0;
+		self.normal.pop_front()
+	}
+
+
+	/// Drop all buffered messages.
+	//
+	pub fn clear( &mut self )
+	{
+		self.high  .clear();
+		self.normal.clear();
+		self.bytes  = 0;
+		self.streak = 0;
+	}
+
+
+	/// The number of buffered messages.
+	//
+	pub fn len( &self ) -> usize
+	{
+		self.high.len() + self.normal.len()
+	}
+
+
+	/// Whether the buffer is empty.
+	//
+	pub fn is_empty( &self ) -> bool
+	{
+		self.high.is_empty() && self.normal.is_empty()
+	}
+
+
+	/// The size of the buffered messages in bytes.
+	//
+	pub fn bytes( &self ) -> usize
+	{
+		self.bytes
+	}
+
+
+	/// How many messages were dropped because their time to live passed while they were in the buffer.
+	//
+	pub fn expired( &self ) -> u64
+	{
+		self.expired
+	}
+}
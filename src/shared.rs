@@ -0,0 +1,440 @@
+// Share one connection between the tabs of a site over a BroadcastChannel.
+// See WsStreamShared for the overview.
+//
+use
+{
+	crate   :: { import::*, ListenerGuard, WsErr, WsErrKind, WsMessage, WsState, WsIo, WsStream, CloseEvent } ,
+	crate   :: { bridge::{ control, set, get, get_u16, get_string }                                         } ,
+	futures :: { channel::mpsc::unbounded, future::{ pending, poll_fn, Either }, SinkExt                      } ,
+	js_sys  :: { Object                                                                                     } ,
+};
+
+
+// The protocol. Every message on the channel is an object with a type in the `t` property. All tabs
+// see all messages, so everyone ignores the types that aren't meant for them.
+//
+// leader -> followers: open { url, protocol }, msg { d }, error { e }, close { code, reason, clean }
+// follower -> leader : hello, send { d }
+//
+fn post( channel: &BroadcastChannel, obj: &Object ) -> Result<(), WsErr>
+{
+	channel.post_message( obj ).map_err( |_| WsErrKind::ConnectionNotOpen.into() )
+}
+
+
+// BroadcastChannel can't transfer, so binary data is copied by the structured clone of the ArrayBuffer.
+//
+fn post_data( channel: &BroadcastChannel, kind: &str, msg: WsMessage ) -> Result<(), WsErr>
+{
+	let obj = control( kind );
+
+	match msg
+	{
+		WsMessage::Text  ( s ) => set( &obj, "d", &s.into()              ),
+		WsMessage::Binary(_)   => set( &obj, "d", &msg.to_array_buffer() ),
+	}
+
+	post( channel, &obj )
+}
+
+
+fn open( name: &str ) -> Result<BroadcastChannel, WsErr>
+{
+	BroadcastChannel::new( name ).map_err( |_| WsErrKind::SharedUnavailable.into() )
+}
+
+
+// Calls `f` with the data of every message on the channel, for as long as this lives.
+//
+fn listen( channel: &BroadcastChannel, f: impl FnMut( JsValue ) + 'static ) -> ListenerGuard
+{
+	let mut f = f;
+
+	ListenerGuard::new( channel, "message", move |evt|
+	{
+		if let Some( evt ) = evt.dyn_ref::<MessageEvent>() { f( evt.data() ) }
+	})
+}
+
+
+
+/// Serve a connection to the other tabs that use the `BroadcastChannel` called `name`, see [WsStreamShared].
+/// This resolves when the connection is closed, after the close event has been forwarded.
+///
+/// This takes over the stream of incoming messages. To receive messages in the tab that owns the connection,
+/// call [WsIo::subscribe](crate::WsIo::subscribe) before, and use [WsStream::sender](crate::WsStream::sender)
+/// to send.
+///
+/// Fails with [WsErrKind::SharedUnavailable] if the browser has no `BroadcastChannel`.
+//
+pub async fn serve_shared( ws: WsStream, mut wsio: WsIo, name: &str ) -> Result<(), WsErr>
+{
+	enum Step
+	{
+		Incoming( Option< Result<WsMessage, WsErr> > ),
+		Command ( Option< JsValue >                  ),
+		Closed  ( CloseEvent                         ),
+	}
+
+
+	let channel            = open( name )?;
+	let (tx, mut commands) = unbounded();
+
+	let _listener = listen( &channel, move |data| { let _ = tx.unbounded_send( data ); } );
+
+	let mut closed = Box::pin( ws.closed() );
+	let mut done   = false;
+
+
+	let announce = ||
+	{
+		let open = control( "open" );
+
+		set( &open, "url"     , &ws.url()     .into() );
+		set( &open, "protocol", &ws.protocol().into() );
+
+		post( &channel, &open )
+	};
+
+	// Followers that were waiting already sent their hello.
+	//
+	announce()?;
+
+
+	let result = loop
+	{
+		let incoming = match done
+		{
+			true  => Either::Left ( pending() ),
+			false => Either::Right( wsio.next() ),
+		};
+
+		let step = match select( select( incoming, commands.next() ), &mut closed ).await
+		{
+			Either::Left(( Either::Left (( msg, _ )), _ )) => Step::Incoming( msg ),
+			Either::Left(( Either::Right(( cmd, _ )), _ )) => Step::Command ( cmd ),
+			Either::Right(( evt, _ ))                      => Step::Closed  ( evt ),
+		};
+
+
+		match step
+		{
+			Step::Incoming( Some( Ok( msg ) ) ) => { let _ = post_data( &channel, "msg", msg ); }
+
+			Step::Incoming( Some( Err( e ) ) ) =>
+			{
+				let err = control( "error" );
+
+				set( &err, "e", &e.to_string().into() );
+
+				let _ = post( &channel, &err );
+			}
+
+			// The stream ended, so the connection is closing. Wait for the close event.
+			//
+			Step::Incoming( None ) => done = true,
+
+			Step::Command( Some( cmd ) ) => match get_string( &cmd, "t" ).as_str()
+			{
+				"hello" => { let _ = announce(); }
+
+				"send" => match WsMessage::try_from( get( &cmd, "d" ) )
+				{
+					Ok ( msg ) => { let _ = wsio.send( msg ).await; }
+					Err( e   ) => error!( "serve_shared: can't send: {}", e ),
+				}
+
+				// Messages of the leader protocol.
+				//
+				_ => {}
+			}
+
+			Step::Command( None ) => {}
+
+			Step::Closed( evt ) =>
+			{
+				let close = control( "close" );
+
+				set( &close, "code"  , &evt.code     .into() );
+				set( &close, "reason", &evt.reason   .into() );
+				set( &close, "clean" , &evt.was_clean.into() );
+
+				break post( &channel, &close );
+			}
+		}
+	};
+
+	channel.close();
+
+	result
+}
+
+
+
+#[ derive( Default ) ]
+//
+struct SharedState
+{
+	queue      : VecDeque< Result<WsMessage, WsErr> >,
+	opened     : bool                               ,
+	detached   : bool                               ,
+	close_evt  : Option< CloseEvent >               ,
+	url        : String                             ,
+	protocol   : String                             ,
+	waker      : Option< Waker >                    ,
+	state_waker: Vec< Waker >                       ,
+}
+
+
+impl SharedState
+{
+	fn wake( &mut self )
+	{
+		if let Some( w ) = self.waker.take() { w.wake() }
+
+		for w in self.state_waker.drain(..) { w.wake() }
+	}
+}
+
+
+
+/// A connection that is owned by another tab of the same site and shared over a `BroadcastChannel`, so several
+/// tabs don't each need their own connection to the server. This implements `Stream` and `Sink<WsMessage>`
+/// like [WsIo](crate::WsIo). Errors that happen in the tab that owns the connection are returned as
+/// [WsErrKind::Remote].
+///
+/// One tab connects as usual and hands the connection to [serve_shared]. The other tabs call
+/// [WsStreamShared::connect] with the same channel name. Each of them receives all incoming messages and can send.
+///
+/// Binary messages are copied between the tabs by the structured clone of an `ArrayBuffer`. A `BroadcastChannel`
+/// can't transfer buffers, because every tab gets the message. There is no flow control, so a slow tab
+/// accumulates messages in memory.
+///
+/// You choose which tab owns the connection. There is no election of a new owner yet, so when the owning tab is
+/// closed, the other tabs don't learn that the connection is gone, unless the close handshake still gets through.
+/// Closing or dropping a `WsStreamShared` only detaches this tab, the connection stays open for the others.
+//
+pub struct WsStreamShared
+{
+	channel  : BroadcastChannel          ,
+	state    : Rc<RefCell< SharedState >>,
+	_listener: ListenerGuard             ,
+}
+
+
+
+impl WsStreamShared
+{
+	/// Use the connection served by [serve_shared] on the channel called `name`. Resolves once a tab that owns the
+	/// connection has answered, so you might want to put a timeout on this. If the connection closed before that,
+	/// [WsErrKind::ConnectionFailed] is returned. Fails with [WsErrKind::SharedUnavailable] if the browser has
+	/// no `BroadcastChannel`.
+	//
+	pub async fn connect( name: &str ) -> Result< Self, WsErr >
+	{
+		let channel = open( name )?;
+		let state   = Rc::new( RefCell::new( SharedState::default() ) );
+		let state2  = state.clone();
+
+		let listener = listen( &channel, move |data|
+		{
+			let mut state = state2.borrow_mut();
+
+			if state.detached { return }
+
+			match get_string( &data, "t" ).as_str()
+			{
+				"open" =>
+				{
+					state.opened   = true;
+					state.url      = get_string( &data, "url"      );
+					state.protocol = get_string( &data, "protocol" );
+				}
+
+				"msg"   => { let msg = WsMessage::try_from( get( &data, "d" ) ); state.queue.push_back( msg ); }
+				"error" => state.queue.push_back( Err( WsErrKind::Remote( get_string( &data, "e" ) ).into() ) ),
+
+				"close" =>
+				{
+					state.close_evt = Some( CloseEvent
+					{
+						code     : get_u16   ( &data, "code"   ),
+						reason   : get_string( &data, "reason" ),
+						was_clean: get( &data, "clean" ).as_bool().unwrap_or( false ),
+					});
+				}
+
+				// Messages of other followers.
+				//
+				_ => return,
+			}
+
+			state.wake();
+		});
+
+
+		post( &channel, &control( "hello" ) )?;
+
+
+		let shared = Self { channel, state, _listener: listener };
+
+		poll_fn( |cx|
+		{
+			let mut state = shared.state.borrow_mut();
+
+			if let Some( evt ) = &state.close_evt
+			{
+				if !state.opened { return Poll::Ready( Err( WsErr::from( WsErrKind::ConnectionFailed( evt.clone() ) ) ) ) }
+			}
+
+			if state.opened { return Poll::Ready( Ok(()) ) }
+
+			state.state_waker.push( cx.waker().clone() );
+
+			Poll::Pending
+
+		}).await?;
+
+		Ok( shared )
+	}
+
+
+
+	/// The state of the connection, as far as we know here. After [WsStreamShared::detach] this is `Closed`.
+	//
+	pub fn ready_state( &self ) -> WsState
+	{
+		let state = self.state.borrow();
+
+		if state.close_evt.is_some() || state.detached { WsState::Closed }
+		else                                           { WsState::Open   }
+	}
+
+
+
+	/// The close event of the connection, once the tab that owns it has forwarded it.
+	//
+	pub fn close_event( &self ) -> Option<CloseEvent>
+	{
+		self.state.borrow().close_evt.clone()
+	}
+
+
+
+	/// The url of the connection.
+	//
+	pub fn url( &self ) -> String
+	{
+		self.state.borrow().url.clone()
+	}
+
+
+
+	/// The subprotocol the server selected.
+	//
+	pub fn protocol( &self ) -> String
+	{
+		self.state.borrow().protocol.clone()
+	}
+
+
+
+	/// Stop using the connection in this tab. Messages that were already received can still be read, after which
+	/// the stream ends. The connection stays open for the other tabs.
+	//
+	pub fn detach( &self )
+	{
+		let mut state = self.state.borrow_mut();
+
+		state.detached = true;
+		state.wake();
+	}
+}
+
+
+
+impl Drop for WsStreamShared
+{
+	fn drop( &mut self )
+	{
+		self.channel.close();
+	}
+}
+
+
+
+impl fmt::Debug for WsStreamShared
+{
+	fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result
+	{
+		write!( f, "WsStreamShared for connection: {}", self.url() )
+	}
+}
+
+
+
+impl Stream for WsStreamShared
+{
+	type Item = Result< WsMessage, WsErr >;
+
+
+	fn poll_next( self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Option< Self::Item >>
+	{
+		let mut state = self.state.borrow_mut();
+
+		if let Some( msg ) = state.queue.pop_front() { return Poll::Ready( Some( msg ) ) }
+
+		if state.close_evt.is_some() || state.detached { return Poll::Ready( None ) }
+
+		match &state.waker
+		{
+			Some( w ) if w.will_wake( cx.waker() ) => {}
+			_ => state.waker = Some( cx.waker().clone() ),
+		}
+
+		Poll::Pending
+	}
+}
+
+
+
+impl Sink<WsMessage> for WsStreamShared
+{
+	type Error = WsErr;
+
+
+	fn poll_ready( self: Pin<&mut Self>, _: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		match self.ready_state()
+		{
+			WsState::Open => Poll::Ready( Ok(()) ),
+			_             => Poll::Ready( Err( WsErrKind::ConnectionNotOpen.into() ) ),
+		}
+	}
+
+
+	fn start_send( self: Pin<&mut Self>, item: WsMessage ) -> Result<(), Self::Error>
+	{
+		match self.ready_state()
+		{
+			WsState::Open => post_data( &self.channel, "send", item ),
+			_             => Err( WsErrKind::ConnectionNotOpen.into() ),
+		}
+	}
+
+
+	fn poll_flush( self: Pin<&mut Self>, _: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		Poll::Ready( Ok(()) )
+	}
+
+
+	/// Detaches this tab, see [WsStreamShared::detach].
+	//
+	fn poll_close( self: Pin<&mut Self>, _: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		self.detach();
+
+		Poll::Ready( Ok(()) )
+	}
+}
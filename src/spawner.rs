@@ -0,0 +1,97 @@
+use
+{
+	crate   :: { import::*              } ,
+	futures :: { future::LocalBoxFuture } ,
+};
+
+
+/// Runs the background tasks of a connection. Set it with [WsConfig::spawner](crate::WsConfig::spawner).
+///
+/// The default is [RtSpawner]. Provide your own to run the tasks on an executor you control, eg. a
+/// `LocalPool` in tests, so you can decide when they make progress.
+//
+pub trait Spawner
+{
+	/// Run the future to completion in the background on the current thread.
+	//
+	fn spawn_local( &self, fut: LocalBoxFuture<'static, ()> );
+}
+
+
+
+/// The default [Spawner], using [naja_async_runtime](https://docs.rs/naja_async_runtime). In the browser this
+/// spawns on the JavaScript event loop through `wasm-bindgen-futures`. Elsewhere it spawns on a thread local
+/// `LocalPool`, which only makes progress when you call `async_runtime::rt::run()`.
+//
+#[ derive( Debug, Clone, Copy, Default, PartialEq, Eq ) ]
+//
+pub struct RtSpawner;
+
+
+impl Spawner for RtSpawner
+{
+	fn spawn_local( &self, fut: LocalBoxFuture<'static, ()> )
+	{
+		// This can only fail if the default executor has been set to a threadpool,
+		// in which case there is nothing we can run a non-Send task on.
+		//
+		if let Err( e ) = rt::spawn_local( fut )
+		{
+			error!( "WsStream: failed to spawn background task: {}", e );
+		}
+	}
+}
+
+
+
+// A spawner shared between the options and the connection. Two of these are equal if they
+// are the same spawner.
+//
+#[ derive( Clone ) ]
+//
+pub(crate) struct SharedSpawner( Rc<dyn Spawner> );
+
+
+impl SharedSpawner
+{
+	pub(crate) fn new( spawner: impl Spawner + 'static ) -> Self
+	{
+		Self( Rc::new( spawner ) )
+	}
+
+
+	pub(crate) fn spawn_local( &self, fut: impl Future< Output = () > + 'static )
+	{
+		self.0.spawn_local( Box::pin( fut ) )
+	}
+}
+
+
+impl Default for SharedSpawner
+{
+	fn default() -> Self
+	{
+		Self::new( RtSpawner )
+	}
+}
+
+
+impl PartialEq for SharedSpawner
+{
+	fn eq( &self, other: &Self ) -> bool
+	{
+		Rc::ptr_eq( &self.0, &other.0 )
+	}
+}
+
+
+impl Eq for SharedSpawner {}
+
+
+impl fmt::Debug for SharedSpawner
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		write!( f, "Spawner" )
+	}
+}
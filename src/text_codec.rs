@@ -0,0 +1,155 @@
+use
+{
+	crate        :: { import::*, WsErr, WsErrKind, WsMessage } ,
+	js_sys       :: { Object, Reflect                        } ,
+	wasm_bindgen :: { prelude::wasm_bindgen                  } ,
+};
+
+
+// We bind these ourselves rather than through web_sys, because we want to keep the strings
+// on the JavaScript side. They exist both in windows and in workers.
+//
+#[ wasm_bindgen ]
+//
+extern "C"
+{
+	type TextDecoder;
+
+	#[ wasm_bindgen( constructor, catch ) ]
+	//
+	fn new( label: &str, options: &Object ) -> Result<TextDecoder, JsValue>;
+
+	#[ wasm_bindgen( method, catch ) ]
+	//
+	fn decode( this: &TextDecoder, input: &ArrayBuffer ) -> Result<JsString, JsValue>;
+
+
+	type TextEncoder;
+
+	#[ wasm_bindgen( constructor, catch ) ]
+	//
+	fn new() -> Result<TextEncoder, JsValue>;
+
+	#[ wasm_bindgen( method ) ]
+	//
+	fn encode( this: &TextEncoder, input: &JsString ) -> Uint8Array;
+}
+
+
+
+/// Converts incoming messages between text and binary, as configured with [WsConfig::binary_to_text](crate::WsConfig::binary_to_text)
+/// and [WsConfig::text_to_binary](crate::WsConfig::text_to_binary).
+///
+/// The conversion is done by the browser with `TextDecoder`/`TextEncoder` before the data is copied into wasm memory.
+/// If those API's are missing, we copy the data and convert in rust.
+//
+pub(crate) struct TextCodec
+{
+	binary_to_text: bool                 ,
+	text_to_binary: bool                 ,
+	decoder       : Option< TextDecoder >,
+	encoder       : Option< TextEncoder >,
+}
+
+
+
+impl TextCodec
+{
+	pub(crate) fn new( binary_to_text: bool, text_to_binary: bool ) -> Self
+	{
+		let decoder = if binary_to_text
+		{
+			let options = Object::new();
+
+			// Throw on invalid UTF-8 rather than inserting replacement characters. Setting a property
+			// on a plain object can't throw.
+			//
+			Reflect::set( &options, &"fatal".into(), &true.into() ).expect_throw( "set property on object" );
+
+			TextDecoder::new( "utf-8", &options ).map_err( |_| warn!( "TextDecoder not available, decoding in rust" ) ).ok()
+		}
+
+		else { None };
+
+
+		let encoder = if text_to_binary
+		{
+			TextEncoder::new().map_err( |_| warn!( "TextEncoder not available, encoding in rust" ) ).ok()
+		}
+
+		else { None };
+
+
+		Self { binary_to_text, text_to_binary, decoder, encoder }
+	}
+
+
+
+	/// Convert the data of a MessageEvent to a WsMessage, converting between text and binary if configured.
+	//
+	pub(crate) fn message( &self, evt: MessageEvent ) -> Result<WsMessage, WsErr>
+	{
+		let data = evt.data();
+
+		if let Some( buf ) = data.dyn_ref::< ArrayBuffer >() { return self.binary( buf ) }
+
+		if self.text_to_binary && data.is_string()
+		{
+			return Ok( self.encode( data.unchecked_ref() ) );
+		}
+
+		WsMessage::try_from( data )
+	}
+
+
+
+	/// Convert binary data, decoding it to text if configured.
+	//
+	pub(crate) fn binary( &self, buf: &ArrayBuffer ) -> Result<WsMessage, WsErr>
+	{
+		if self.binary_to_text { return self.decode( buf ) }
+
+		Ok( WsMessage::from_array_buffer( buf ) )
+	}
+
+
+
+	/// Decode a binary message to text. Fails with [WsErrKind::InvalidUtf8] if the message is not valid UTF-8.
+	//
+	fn decode( &self, buf: &ArrayBuffer ) -> Result<WsMessage, WsErr>
+	{
+		match &self.decoder
+		{
+			Some( d ) =>
+			{
+				let text = d.decode( buf ).map_err( |_| WsErrKind::InvalidUtf8 )?;
+
+				Ok( WsMessage::Text( text.into() ) )
+			}
+
+			None =>
+			{
+				let bytes = Uint8Array::new( buf ).to_vec();
+
+				String::from_utf8( bytes )
+
+					.map( WsMessage::Text )
+					.map_err( |_| WsErrKind::InvalidUtf8.into() )
+			}
+		}
+	}
+
+
+
+	/// Encode a text message as UTF-8 bytes.
+	//
+	fn encode( &self, text: &JsString ) -> WsMessage
+	{
+		match &self.encoder
+		{
+			Some( e ) => WsMessage::Binary( e.encode( text ).to_vec() ),
+
+			None => WsMessage::Binary( String::from( text ).into_bytes() ),
+		}
+	}
+}
@@ -0,0 +1,199 @@
+use
+{
+	crate        :: { import::*              } ,
+	futures      :: { future::LocalBoxFuture } ,
+	js_sys       :: { Function               } ,
+	std          :: { time::Duration         } ,
+	wasm_bindgen :: { prelude::wasm_bindgen  } ,
+};
+
+
+// These are available on the global scope both in windows and in workers.
+//
+#[ wasm_bindgen ]
+//
+extern "C"
+{
+	#[ wasm_bindgen( js_name = setTimeout ) ]
+	//
+	fn set_timeout( handler: &Function, timeout: i32 ) -> i32;
+
+	#[ wasm_bindgen( js_name = clearTimeout ) ]
+	//
+	fn clear_timeout( id: i32 );
+}
+
+
+
+/// The source of time for everything in this crate that waits: the delays of [RetryPolicy](crate::RetryPolicy),
+/// the stagger of [WsStream::connect_race](crate::WsStream::connect_race), the polling of
+/// [WsStream::ready](crate::WsStream::ready) and [WsSender::send_timeout](crate::WsSender::send_timeout).
+///
+/// The default is [JsTimer]. You can use your own through [WsConfig::timer](crate::WsConfig::timer) and
+/// [RetryPolicy::timer](crate::RetryPolicy::timer), for example a `MockTimer` (with the `mock` feature) to test
+/// retry and timeout logic without waiting. [WsStream::connect_race_with_config](crate::WsStream::connect_race_with_config)
+/// uses the timer of it's config.
+//
+pub trait Timer
+{
+	/// A future that resolves once `duration` has passed. Dropping it cancels the timer.
+	//
+	fn sleep( &self, duration: Duration ) -> LocalBoxFuture<'static, ()>;
+}
+
+
+
+/// The default [Timer], using `setTimeout` from the global scope, so it works both in windows and in workers.
+//
+#[ derive( Debug, Clone, Copy, Default, PartialEq, Eq ) ]
+//
+pub struct JsTimer;
+
+
+impl Timer for JsTimer
+{
+	fn sleep( &self, duration: Duration ) -> LocalBoxFuture<'static, ()>
+	{
+		Box::pin( sleep( duration ) )
+	}
+}
+
+
+
+// A timer shared between the options and the connection. Two of these are equal if they
+// are the same timer.
+//
+#[ derive( Clone ) ]
+//
+pub(crate) struct SharedTimer( Rc<dyn Timer> );
+
+
+impl SharedTimer
+{
+	pub(crate) fn new( timer: impl Timer + 'static ) -> Self
+	{
+		Self( Rc::new( timer ) )
+	}
+
+
+	pub(crate) fn sleep( &self, duration: Duration ) -> LocalBoxFuture<'static, ()>
+	{
+		self.0.sleep( duration )
+	}
+}
+
+
+impl Default for SharedTimer
+{
+	fn default() -> Self
+	{
+		Self::new( JsTimer )
+	}
+}
+
+
+impl PartialEq for SharedTimer
+{
+	fn eq( &self, other: &Self ) -> bool
+	{
+		Rc::ptr_eq( &self.0, &other.0 )
+	}
+}
+
+
+impl Eq for SharedTimer {}
+
+
+impl fmt::Debug for SharedTimer
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		write!( f, "Timer" )
+	}
+}
+
+
+
+#[ derive( Default ) ]
+//
+struct SleepState
+{
+	fired: bool         ,
+	waker: Option<Waker>,
+}
+
+
+
+/// A future that resolves after the given duration, using `setTimeout`. Dropping it
+/// clears the timeout.
+//
+pub(crate) struct Sleep
+{
+	state   : Rc<RefCell< SleepState >>      ,
+	id      : i32                            ,
+
+	// We need to store the closure to keep it from being dropped.
+	//
+	_closure: Closure< dyn FnMut() + 'static >,
+}
+
+
+
+pub(crate) fn sleep( duration: Duration ) -> Sleep
+{
+	let state  = Rc::new( RefCell::new( SleepState::default() ) );
+	let state2 = state.clone();
+
+	let closure = Closure::wrap( Box::new( move ||
+	{
+		let waker =
+		{
+			let mut state = state2.borrow_mut();
+
+			state.fired = true;
+			state.waker.take()
+		};
+
+		if let Some( w ) = waker { w.wake() }
+
+	}) as Box< dyn FnMut() > );
+
+
+	// setTimeout takes an i32 amount of milliseconds.
+	//
+	let millis = cmp::min( duration.as_millis(), i32::MAX as u128 ) as i32;
+	let id     = set_timeout( closure.as_ref().unchecked_ref(), millis );
+
+	Sleep { state, id, _closure: closure }
+}
+
+
+
+impl Future for Sleep
+{
+	type Output = ();
+
+	fn poll( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll<Self::Output>
+	{
+		let mut state = self.state.borrow_mut();
+
+		if state.fired { return Poll::Ready(()) }
+
+		state.waker = Some( cx.waker().clone() );
+
+		Poll::Pending
+	}
+}
+
+
+
+impl Drop for Sleep
+{
+	fn drop( &mut self )
+	{
+		if !self.state.borrow().fired
+		{
+			clear_timeout( self.id );
+		}
+	}
+}
@@ -0,0 +1,53 @@
+use
+{
+	crate :: { import::*, ListenerGuard } ,
+};
+
+
+/// Closes the connection when the page is unloaded. The listener is removed when this is dropped.
+///
+/// We listen to `pagehide` rather than `beforeunload`, because the latter can be cancelled by
+/// other handlers, in which case the page stays open and we would have closed the connection for nothing.
+//
+pub(crate) struct UnloadGuard
+{
+	_listener: ListenerGuard,
+}
+
+
+
+impl UnloadGuard
+{
+	/// Returns `None` when there is no window, eg. in a worker.
+	//
+	pub(crate) fn new( ws: Rc<WebSocket>, code: u16, reason: String ) -> Option<Self>
+	{
+		let window = web_sys::window()?;
+
+		let listener = ListenerGuard::new( &window, "pagehide", move |_|
+		{
+			trace!( "WsStream: page unloading, closing connection" );
+
+			// Browsers only allow 1000 and 3000-4999, so fall back to 1000 if the code is refused.
+			// This also fails if the reason is too long, in which case we drop it.
+			//
+			if ws.close_with_code_and_reason( code, &reason ).is_err()
+			&& ws.close_with_code_and_reason( 1000, &reason ).is_err()
+			{
+				let _ = ws.close_with_code( 1000 );
+			}
+		});
+
+		Some( Self { _listener: listener } )
+	}
+}
+
+
+
+impl Drop for UnloadGuard
+{
+	fn drop( &mut self )
+	{
+		trace!( "WsStream: remove pagehide listener" );
+	}
+}
@@ -0,0 +1,50 @@
+use
+{
+	crate  :: { import::*, ListenerGuard } ,
+	js_sys :: { global, Reflect          } ,
+};
+
+
+// The document, if there is one. Workers don't have one.
+//
+fn document() -> Option<EventTarget>
+{
+	Reflect::get( &global(), &"document".into() ).ok()?.dyn_into::<EventTarget>().ok()
+}
+
+
+
+/// Whether the page is hidden (`document.hidden`). Without a document, eg. in a worker, we are never hidden.
+//
+pub(crate) fn is_hidden() -> bool
+{
+	document()
+
+		.and_then( |doc| Reflect::get( &doc, &"hidden".into() ).ok() )
+		.and_then( |hidden| hidden.as_bool() )
+		.unwrap_or( false )
+}
+
+
+
+/// Listens to `visibilitychange` as long as it is alive.
+//
+pub(crate) struct VisibilityGuard
+{
+	_listener: ListenerGuard,
+}
+
+
+
+impl VisibilityGuard
+{
+	/// The callback is called with `true` when the page becomes hidden and with `false` when it becomes visible.
+	/// Returns `None` if there is no document.
+	//
+	pub(crate) fn new( callback: impl Fn(bool) + 'static ) -> Option<Self>
+	{
+		let target = document()?;
+
+		Some( Self { _listener: ListenerGuard::new( &target, "visibilitychange", move |_| callback( is_hidden() ) ) } )
+	}
+}
@@ -0,0 +1,194 @@
+use
+{
+	crate :: { import::* } ,
+};
+
+
+/// A value that can be watched for changes, like `tokio::sync::watch`, but for a single thread. This is what
+/// [WsStream::state_signal](crate::WsStream::state_signal) and [RetryPolicy::status_signal](crate::RetryPolicy::status_signal)
+/// return streams of, so UI code can bind to the state of a connection.
+///
+/// Cloning gives another handle to the same value. Setting a value that is equal to the current one does nothing.
+///
+/// ```
+/// use { ws_stream_wasm::Watch, futures::{ executor::block_on, StreamExt } };
+///
+/// let watch   = Watch::new( 1 );
+/// let mut rx  = watch.subscribe();
+///
+/// watch.set( 2 );
+/// watch.set( 2 );
+/// watch.set( 3 );
+///
+/// drop( watch );
+///
+/// // The stream only sees the latest value and ends once all handles are dropped.
+/// //
+/// assert_eq!( vec![ 3 ], block_on( rx.collect::<Vec<_>>() ) );
+/// ```
+//
+pub struct Watch<T>
+{
+	inner: Rc<RefCell< Inner<T> >>,
+}
+
+
+struct Inner<T>
+{
+	value  : T          ,
+	version: u64        ,
+	writers: usize      ,
+	wakers : Vec<Waker> ,
+}
+
+
+impl<T> Inner<T>
+{
+	fn wake( &mut self )
+	{
+		for w in self.wakers.drain(..) { w.wake() }
+	}
+}
+
+
+
+impl<T: Clone + PartialEq> Watch<T>
+{
+	/// Create a watch with an initial value.
+	//
+	pub fn new( value: T ) -> Self
+	{
+		Self { inner: Rc::new( RefCell::new( Inner { value, version: 0, writers: 1, wakers: Vec::new() } ) ) }
+	}
+
+
+	/// The current value.
+	//
+	pub fn get( &self ) -> T
+	{
+		self.inner.borrow().value.clone()
+	}
+
+
+	/// Change the value and wake up the subscribers. Does nothing if the value is equal to the current one.
+	//
+	pub fn set( &self, value: T )
+	{
+		let mut inner = self.inner.borrow_mut();
+
+		if inner.value == value { return }
+
+		inner.value    = value;
+		inner.version += 1;
+		inner.wake();
+	}
+
+
+	/// A stream that yields the current value right away and then every change. A subscriber that doesn't keep up
+	/// only sees the latest value, and never the same value twice in a row. The stream ends once all handles
+	/// to the watch are dropped.
+	//
+	pub fn subscribe( &self ) -> WatchStream<T>
+	{
+		WatchStream { inner: self.inner.clone(), version: None, last: None }
+	}
+}
+
+
+
+impl<T> Clone for Watch<T>
+{
+	fn clone( &self ) -> Self
+	{
+		self.inner.borrow_mut().writers += 1;
+
+		Self { inner: self.inner.clone() }
+	}
+}
+
+
+
+impl<T> Drop for Watch<T>
+{
+	fn drop( &mut self )
+	{
+		let mut inner = self.inner.borrow_mut();
+
+		inner.writers -= 1;
+
+		if inner.writers == 0 { inner.wake() }
+	}
+}
+
+
+
+impl<T: fmt::Debug> fmt::Debug for Watch<T>
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		write!( f, "Watch: {:?}", self.inner.borrow().value )
+	}
+}
+
+
+
+/// The changes of a [Watch], obtained with [Watch::subscribe].
+//
+pub struct WatchStream<T>
+{
+	inner  : Rc<RefCell< Inner<T> >>,
+	version: Option<u64>            ,
+	last   : Option<T>              ,
+}
+
+
+
+// We never pin the value we keep.
+//
+impl<T> Unpin for WatchStream<T> {}
+
+
+
+impl<T: Clone + PartialEq> Stream for WatchStream<T>
+{
+	type Item = T;
+
+	fn poll_next( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll<Option< Self::Item >>
+	{
+		let this      = &mut *self;
+		let mut inner = this.inner.borrow_mut();
+
+		// The value can have changed back to the one we yielded last, in which case there is nothing new.
+		//
+		if this.version != Some( inner.version )
+		{
+			this.version = Some( inner.version );
+
+			if this.last.as_ref() != Some( &inner.value )
+			{
+				this.last = Some( inner.value.clone() );
+
+				return Poll::Ready( Some( inner.value.clone() ) );
+			}
+		}
+
+		if inner.writers == 0 { return Poll::Ready( None ) }
+
+		if !inner.wakers.iter().any( |w| w.will_wake( cx.waker() ) )
+		{
+			inner.wakers.push( cx.waker().clone() );
+		}
+
+		Poll::Pending
+	}
+}
+
+
+
+impl<T: fmt::Debug> fmt::Debug for WatchStream<T>
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		write!( f, "WatchStream: {:?}", self.inner.borrow().value )
+	}
+}
@@ -0,0 +1,188 @@
+use
+{
+	crate   :: { import::*, WsErr, WsErrKind, WsMessage } ,
+	futures :: { stream::FusedStream                    } ,
+};
+
+
+/// What [TextOnly] and [BinaryOnly] do with a message of the other type.
+//
+#[ derive( Debug, Clone, Copy, PartialEq, Eq ) ]
+//
+pub enum Mismatch
+{
+	/// Silently drop the message.
+	//
+	Skip,
+
+	/// Yield an error: [WsErrKind::UnexpectedBinary] for [TextOnly] and [WsErrKind::UnexpectedText]
+	/// for [BinaryOnly]. The stream continues after the error.
+	//
+	Error,
+
+	/// Convert the message. [TextOnly] decodes binary messages as UTF-8, yielding [WsErrKind::InvalidUtf8]
+	/// if that fails. [BinaryOnly] yields the UTF-8 bytes of text messages.
+	//
+	Convert,
+}
+
+
+
+/// Adapters for streams of [WsMessage], like [WsIo](crate::WsIo), for protocols that only use one type of message.
+///
+/// The adapters are [FusedStream] when the underlying stream is.
+//
+pub trait WsStreamExt: Stream< Item = Result<WsMessage, WsErr> > + Sized
+{
+	/// Only yield the content of text messages. Binary messages are handled according to `policy`.
+	/// Errors from the underlying stream are passed through.
+	//
+	fn text_only( self, policy: Mismatch ) -> TextOnly<Self>
+	{
+		TextOnly { inner: self, policy }
+	}
+
+
+	/// Only yield the content of binary messages. Text messages are handled according to `policy`.
+	/// Errors from the underlying stream are passed through.
+	//
+	fn binary_only( self, policy: Mismatch ) -> BinaryOnly<Self>
+	{
+		BinaryOnly { inner: self, policy }
+	}
+}
+
+
+impl<S> WsStreamExt for S where S: Stream< Item = Result<WsMessage, WsErr> > {}
+
+
+
+/// A stream of the text messages of a connection, see [WsStreamExt::text_only].
+//
+#[ derive( Debug ) ]
+//
+pub struct TextOnly<S>
+{
+	inner : S        ,
+	policy: Mismatch ,
+}
+
+
+
+impl<S> TextOnly<S>
+{
+	/// Get the underlying stream back.
+	//
+	pub fn into_inner( self ) -> S
+	{
+		self.inner
+	}
+}
+
+
+
+// The Stream trait of futures 0.3.0-alpha.18 has no size_hint, so there is nothing to forward yet. Once we are on
+// a version that has it, both adapters should forward the hint of the inner stream: unchanged for Mismatch::Error
+// and Mismatch::Convert, which yield an item for every message, and `( 0, upper )` for Mismatch::Skip.
+//
+impl<S> Stream for TextOnly<S> where S: Stream< Item = Result<WsMessage, WsErr> > + Unpin
+{
+	type Item = Result<String, WsErr>;
+
+
+	fn poll_next( mut self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Option< Self::Item >>
+	{
+		loop
+		{
+			let item = match ready!( Pin::new( &mut self.inner ).poll_next( cx ) )
+			{
+				None        => return Poll::Ready( None ),
+				Some( msg ) => msg,
+			};
+
+			return Poll::Ready( Some( match ( item, self.policy )
+			{
+				( Err( e )                   , _                 ) => Err( e )                                ,
+				( Ok( WsMessage::Text  ( s ) ), _                 ) => Ok( s )                                 ,
+				( Ok( WsMessage::Binary( _ ) ), Mismatch::Skip    ) => continue                                ,
+				( Ok( WsMessage::Binary( _ ) ), Mismatch::Error   ) => Err( WsErrKind::UnexpectedBinary.into() ) ,
+				( Ok( WsMessage::Binary( b ) ), Mismatch::Convert ) =>
+
+					String::from_utf8( b ).map_err( |_| WsErrKind::InvalidUtf8.into() ),
+			}))
+		}
+	}
+}
+
+
+
+impl<S> FusedStream for TextOnly<S> where S: FusedStream< Item = Result<WsMessage, WsErr> > + Unpin
+{
+	fn is_terminated( &self ) -> bool
+	{
+		self.inner.is_terminated()
+	}
+}
+
+
+
+/// A stream of the binary messages of a connection, see [WsStreamExt::binary_only].
+//
+#[ derive( Debug ) ]
+//
+pub struct BinaryOnly<S>
+{
+	inner : S        ,
+	policy: Mismatch ,
+}
+
+
+
+impl<S> BinaryOnly<S>
+{
+	/// Get the underlying stream back.
+	//
+	pub fn into_inner( self ) -> S
+	{
+		self.inner
+	}
+}
+
+
+
+impl<S> Stream for BinaryOnly<S> where S: Stream< Item = Result<WsMessage, WsErr> > + Unpin
+{
+	type Item = Result<Vec<u8>, WsErr>;
+
+
+	fn poll_next( mut self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Option< Self::Item >>
+	{
+		loop
+		{
+			let item = match ready!( Pin::new( &mut self.inner ).poll_next( cx ) )
+			{
+				None        => return Poll::Ready( None ),
+				Some( msg ) => msg,
+			};
+
+			return Poll::Ready( Some( match ( item, self.policy )
+			{
+				( Err( e )                   , _                 ) => Err( e )                              ,
+				( Ok( WsMessage::Binary( b ) ), _                 ) => Ok( b )                               ,
+				( Ok( WsMessage::Text  ( _ ) ), Mismatch::Skip    ) => continue                              ,
+				( Ok( WsMessage::Text  ( _ ) ), Mismatch::Error   ) => Err( WsErrKind::UnexpectedText.into() ) ,
+				( Ok( WsMessage::Text  ( s ) ), Mismatch::Convert ) => Ok( s.into_bytes() )                  ,
+			}))
+		}
+	}
+}
+
+
+
+impl<S> FusedStream for BinaryOnly<S> where S: FusedStream< Item = Result<WsMessage, WsErr> > + Unpin
+{
+	fn is_terminated( &self ) -> bool
+	{
+		self.inner.is_terminated()
+	}
+}
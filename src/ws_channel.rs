@@ -0,0 +1,348 @@
+use
+{
+	crate :: { import::*, ErrorPolicy, WsDecode, WsEncode, WsErr, WsErrKind, WsIo, WsSender } ,
+	std   :: { marker::PhantomData                                                        } ,
+};
+
+
+/// A typed connection, that sends items of type `Tx` and receives items of type `Rx`. The two types can be different,
+/// eg. when a protocol sends commands and receives events. Messages are converted with a codec that implements
+/// [WsEncode] for `Tx` and [WsDecode] for `Rx`, so the wire format is up to you.
+///
+/// Implements `Sink<Tx>` and `Stream<Item = Result<Rx, WsErr>>`. Use [split](WsChannel::split) to get owned halves,
+/// which share the codec.
+///
+/// Errors tell you where they come from: failures of the codec always have kind [WsErrKind::Encode] or
+/// [WsErrKind::Decode]. Any other kind is an error of the connection. What happens to messages that fail to
+/// decode is up to the [ErrorPolicy], by default the error is yielded and the stream continues.
+///
+/// Created with [WsIo::into_channel].
+//
+pub struct WsChannel<Tx, Rx, C>
+{
+	sink  : WsChannelSink  <Tx, C>,
+	stream: WsChannelStream<Rx, C>,
+}
+
+
+
+impl<Tx, Rx, C> WsChannel<Tx, Rx, C> where C: WsEncode<Tx> + WsDecode<Rx>
+{
+	pub(crate) fn new( io: WsIo, codec: C ) -> Self
+	{
+		let codec = Rc::new( RefCell::new( codec ) );
+
+		Self
+		{
+			sink  : WsChannelSink  { sender: io.sender(), codec: codec.clone(), _tx: PhantomData },
+			stream: WsChannelStream{ io, codec, policy: ErrorPolicy::default(), skipped: 0, _rx: PhantomData },
+		}
+	}
+
+
+	/// What to do with messages the codec can't decode. Defaults to [ErrorPolicy::YieldError]. Messages
+	/// that can't be converted by the [WsIo] are handled by [WsConfig::error_policy](crate::WsConfig::error_policy).
+	//
+	pub fn error_policy( mut self, policy: ErrorPolicy ) -> Self
+	{
+		self.stream.policy = policy;
+		self
+	}
+
+
+	/// The number of messages dropped with [ErrorPolicy::SkipAndCount], see [WsChannelStream::skipped_messages].
+	//
+	pub fn skipped_messages( &self ) -> u64
+	{
+		self.stream.skipped_messages()
+	}
+
+
+	/// Split into a sending and a receiving half. Both halves share the codec, so stateful codecs keep working.
+	/// Put them back together with [WsChannel::reunite].
+	///
+	/// Dropping the receiving half closes the connection, just like dropping [WsIo].
+	//
+	pub fn split( self ) -> ( WsChannelSink<Tx, C>, WsChannelStream<Rx, C> )
+	{
+		( self.sink, self.stream )
+	}
+
+
+	/// Put the halves returned by [WsChannel::split] back together. Fails if they don't come from
+	/// the same channel, in which case you get both halves back in the error.
+	//
+	#[ allow( clippy::result_large_err ) ] // giving back the halves is the point
+	//
+	pub fn reunite( sink: WsChannelSink<Tx, C>, stream: WsChannelStream<Rx, C> ) -> Result< Self, ReuniteError<Tx, Rx, C> >
+	{
+		if Rc::ptr_eq( &sink.codec, &stream.codec )
+		{
+			Ok( Self { sink, stream } )
+		}
+
+		else { Err( ReuniteError( sink, stream ) ) }
+	}
+
+
+	/// Take the channel apart, to get at the connection or the codec. The [WsIo] gives access to the
+	/// senders, the events and the raw socket through [WsIo::wrapped].
+	//
+	pub fn into_parts( self ) -> ( WsIo, C )
+	{
+		let WsChannel { sink, stream } = self;
+
+		drop( sink );
+
+		let codec = match Rc::try_unwrap( stream.codec )
+		{
+			Ok ( codec ) => codec.into_inner(),
+
+			// The halves of a channel are the only owners of the codec and the sink is gone.
+			//
+			Err(_) => unreachable!( "WsChannel::into_parts: codec is shared" ),
+		};
+
+		( stream.io, codec )
+	}
+}
+
+
+
+/// The error of [WsChannel::reunite] when the halves come from different channels. Contains both halves,
+/// so nothing is lost.
+//
+pub struct ReuniteError<Tx, Rx, C>( pub WsChannelSink<Tx, C>, pub WsChannelStream<Rx, C> );
+
+
+
+impl<Tx, Rx, C> fmt::Debug for ReuniteError<Tx, Rx, C>
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		f.debug_tuple( "ReuniteError" ).field( &self.0 ).field( &self.1 ).finish()
+	}
+}
+
+
+
+impl<Tx, Rx, C> fmt::Display for ReuniteError<Tx, Rx, C>
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		write!( f, "Tried to reunite halves that are not from the same WsChannel." )
+	}
+}
+
+
+
+impl<Tx, Rx, C> std::error::Error for ReuniteError<Tx, Rx, C> {}
+
+
+
+/// The sending half of a [WsChannel].
+//
+pub struct WsChannelSink<Tx, C>
+{
+	sender: WsSender         ,
+	codec : Rc<RefCell< C >> ,
+
+	_tx: PhantomData< fn( Tx ) >,
+}
+
+
+
+/// The receiving half of a [WsChannel].
+//
+pub struct WsChannelStream<Rx, C>
+{
+	io     : WsIo             ,
+	codec  : Rc<RefCell< C >> ,
+	policy : ErrorPolicy      ,
+	skipped: u64              ,
+
+	_rx: PhantomData< fn() -> Rx >,
+}
+
+
+
+// Codecs can return any WsErr, but the channel promises that codec errors are recognizable.
+//
+fn encode_err( e: WsErr ) -> WsErr
+{
+	match e.kind()
+	{
+		WsErrKind::Encode(_) => e,
+		_                    => WsErrKind::Encode( e.to_string() ).into(),
+	}
+}
+
+
+fn decode_err( e: WsErr ) -> WsErr
+{
+	match e.kind()
+	{
+		WsErrKind::Decode(_) => e,
+		_                    => WsErrKind::Decode( e.to_string() ).into(),
+	}
+}
+
+
+
+impl<Tx, C> Sink<Tx> for WsChannelSink<Tx, C> where C: WsEncode<Tx>
+{
+	type Error = WsErr;
+
+
+	fn poll_ready( self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		Pin::new( &mut &self.sender ).poll_ready( cx )
+	}
+
+
+	fn start_send( self: Pin<&mut Self>, item: Tx ) -> Result<(), Self::Error>
+	{
+		let msg = self.codec.borrow_mut().encode( &item ).map_err( encode_err )?;
+
+		Pin::new( &mut &self.sender ).start_send( msg )
+	}
+
+
+	fn poll_flush( self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		Pin::new( &mut &self.sender ).poll_flush( cx )
+	}
+
+
+	fn poll_close( self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		Pin::new( &mut &self.sender ).poll_close( cx )
+	}
+}
+
+
+
+impl<Rx, C> WsChannelStream<Rx, C>
+{
+	/// The number of messages dropped with [ErrorPolicy::SkipAndCount], both by the codec of this channel
+	/// and by the [WsIo].
+	//
+	pub fn skipped_messages( &self ) -> u64
+	{
+		self.skipped + self.io.stats().skipped
+	}
+}
+
+
+
+impl<Rx, C> Stream for WsChannelStream<Rx, C> where C: WsDecode<Rx>
+{
+	type Item = Result<Rx, WsErr>;
+
+
+	fn poll_next( mut self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Option< Self::Item >>
+	{
+		loop
+		{
+			let item = match ready!( Pin::new( &mut self.io ).poll_next( cx ) )
+			{
+				None              => return Poll::Ready( None ),
+				Some( Err( e )  ) => return Poll::Ready( Some( Err( e ) ) ),
+				Some( Ok ( msg ) ) => self.codec.borrow_mut().decode( msg ).map_err( decode_err ),
+			};
+
+			return Poll::Ready( Some( match ( item, self.policy )
+			{
+				( Err( e ), ErrorPolicy::SkipAndCount ) =>
+				{
+					warn!( "WsChannel: skipping message: {}", e );
+
+					self.skipped += 1;
+					continue;
+				}
+
+				( Err( e ), ErrorPolicy::CloseConnection( code ) ) =>
+				{
+					self.io.close_for_policy( code );
+					Err( e )
+				}
+
+				( item, _ ) => item,
+			}))
+		}
+	}
+}
+
+
+
+impl<Tx, Rx, C> Sink<Tx> for WsChannel<Tx, Rx, C> where C: WsEncode<Tx>
+{
+	type Error = WsErr;
+
+
+	fn poll_ready( mut self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		Pin::new( &mut self.sink ).poll_ready( cx )
+	}
+
+
+	fn start_send( mut self: Pin<&mut Self>, item: Tx ) -> Result<(), Self::Error>
+	{
+		Pin::new( &mut self.sink ).start_send( item )
+	}
+
+
+	fn poll_flush( mut self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		Pin::new( &mut self.sink ).poll_flush( cx )
+	}
+
+
+	fn poll_close( mut self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		Pin::new( &mut self.sink ).poll_close( cx )
+	}
+}
+
+
+
+impl<Tx, Rx, C> Stream for WsChannel<Tx, Rx, C> where C: WsDecode<Rx>
+{
+	type Item = Result<Rx, WsErr>;
+
+
+	fn poll_next( mut self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Option< Self::Item >>
+	{
+		Pin::new( &mut self.stream ).poll_next( cx )
+	}
+}
+
+
+
+impl<Tx, Rx, C> fmt::Debug for WsChannel<Tx, Rx, C>
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		write!( f, "WsChannel for connection: {}", self.stream.io.wrapped().url() )
+	}
+}
+
+
+
+impl<Tx, C> fmt::Debug for WsChannelSink<Tx, C>
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		f.debug_tuple( "WsChannelSink" ).field( &self.sender ).finish()
+	}
+}
+
+
+
+impl<Rx, C> fmt::Debug for WsChannelStream<Rx, C>
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		write!( f, "WsChannelStream for connection: {}", self.io.wrapped().url() )
+	}
+}
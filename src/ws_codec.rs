@@ -0,0 +1,88 @@
+use
+{
+	crate :: { WsErr, WsMessage } ,
+};
+
+
+/// Turns items into [WsMessage] for a [WsChannel](crate::WsChannel).
+///
+/// Return [WsErrKind::Encode](crate::WsErrKind::Encode) when an item can't be encoded. Any other error kind is
+/// converted into `Encode` by the channel, so you can always tell codec errors from transport errors.
+///
+/// A codec decides the wire format of a typed channel. The crate ships [CborCodec](crate::CborCodec) with the `cbor`
+/// feature, but for anything else you implement [WsEncode] and [WsDecode] yourself. Both take `&mut self`, so a codec can
+/// keep state, eg. a protocol version negotiated with the first message. When a channel is split, both halves share the
+/// same codec.
+///
+/// As an example, a hand-rolled binary format with a fixed header of a 1 byte message type and a 4 byte length,
+/// followed by a body produced by some other serializer (eg. flatbuffers):
+///
+/// ```
+/// use ws_stream_wasm::*;
+///
+/// struct Frame { kind: u8, body: Vec<u8> }
+///
+/// struct HeaderCodec;
+///
+/// impl WsEncode<Frame> for HeaderCodec
+/// {
+///    fn encode( &mut self, item: &Frame ) -> Result<WsMessage, WsErr>
+///    {
+///       let mut buf = Vec::with_capacity( 5 + item.body.len() );
+///
+///       buf.push( item.kind );
+///       buf.extend_from_slice( &( item.body.len() as u32 ).to_be_bytes() );
+///       buf.extend_from_slice( &item.body );
+///
+///       Ok( WsMessage::Binary( buf ) )
+///    }
+/// }
+///
+/// impl WsDecode<Frame> for HeaderCodec
+/// {
+///    fn decode( &mut self, msg: WsMessage ) -> Result<Frame, WsErr>
+///    {
+///       let buf = match msg
+///       {
+///          WsMessage::Binary( b ) if b.len() >= 5 => b,
+///          _ => return Err( WsErrKind::Decode( "expected a binary frame".to_string() ).into() ),
+///       };
+///
+///       let mut len = [ 0u8; 4 ];
+///       len.copy_from_slice( &buf[1..5] );
+///
+///       if u32::from_be_bytes( len ) as usize != buf.len() - 5
+///       {
+///          return Err( WsErrKind::Decode( "wrong length in header".to_string() ).into() );
+///       }
+///
+///       Ok( Frame { kind: buf[0], body: buf[5..].to_vec() } )
+///    }
+/// }
+///
+/// let mut codec = HeaderCodec;
+/// let     msg   = codec.encode( &Frame{ kind: 1, body: vec![ 1, 2, 3 ] } ).unwrap();
+///
+/// assert_eq!( vec![ 1, 2, 3 ], codec.decode( msg ).unwrap().body );
+/// ```
+//
+pub trait WsEncode<T>
+{
+	/// Encode one item.
+	//
+	fn encode( &mut self, item: &T ) -> Result<WsMessage, WsErr>;
+}
+
+
+
+/// Turns [WsMessage] into items for a [WsChannel](crate::WsChannel).
+///
+/// Return [WsErrKind::Decode](crate::WsErrKind::Decode) when a message can't be decoded. Any other error kind is
+/// converted into `Decode` by the channel, so you can always tell codec errors from transport errors.
+//
+pub trait WsDecode<T>
+{
+	/// Decode one message.
+	//
+	fn decode( &mut self, msg: WsMessage ) -> Result<T, WsErr>;
+}
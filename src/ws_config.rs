@@ -0,0 +1,335 @@
+use
+{
+	crate :: { ErrorPolicy, Heartbeat, Spawner, Timer, spawner::SharedSpawner, timer::SharedTimer, ws_stats::LagWarning } ,
+	std   :: { time::Duration                                                                                        } ,
+};
+
+
+/// Options for [WsStream::connect_with_config](crate::WsStream::connect_with_config).
+///
+/// ```
+/// use ws_stream_wasm::WsConfig;
+///
+/// let config = WsConfig::default()
+///
+///    .protocols        ( vec![ "chat" ]   )
+///    .max_message_size ( 1024 * 1024      )
+///    .close_on_oversize( true             )
+///    .max_send_size    ( 64 * 1024        )
+///    .require_protocol ( 4000             )
+/// ;
+/// ```
+//
+#[ derive( Debug, Clone, PartialEq, Eq ) ]
+//
+pub struct WsConfig
+{
+	pub(crate) protocols          : Option< Vec<String>     >,
+	pub(crate) max_message_size   : Option< usize           >,
+	pub(crate) close_on_oversize  : bool                     ,
+	pub(crate) max_send_size      : Option< usize           >,
+	pub(crate) send_high_water    : Option< u32             >,
+	pub(crate) subscriber_capacity: usize                    ,
+	pub(crate) close_on_unload    : Option< (u16, String)   >,
+	pub(crate) binary_to_text     : bool                     ,
+	pub(crate) text_to_binary     : bool                     ,
+	pub(crate) require_protocol   : Option< u16             >,
+	pub(crate) timer              : SharedTimer              ,
+	pub(crate) spawner            : SharedSpawner            ,
+	pub(crate) backlog_threshold  : usize                    ,
+	pub(crate) stream_threshold   : Option< usize           >,
+	pub(crate) error_policy       : ErrorPolicy              ,
+	pub(crate) heartbeat          : Option< Heartbeat       >,
+	pub(crate) lag_warning        : Option< LagWarning      >,
+
+	#[ cfg( feature = "instrument" ) ]
+	//
+	pub(crate) instrument         : Option< u32             >,
+}
+
+
+
+/// The default for [WsConfig::max_message_size]: 64 MiB.
+//
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+
+
+/// The default for [WsConfig::subscriber_capacity]: 256 messages.
+//
+pub const DEFAULT_SUBSCRIBER_CAPACITY: usize = 256;
+
+
+/// The default for [WsConfig::backlog_threshold]: 64 messages.
+//
+pub const DEFAULT_BACKLOG_THRESHOLD: usize = 64;
+
+
+
+impl WsConfig
+{
+	/// The subprotocols to request from the server.
+	///
+	/// **Note**: Sending protocols to a server that doesn't support them will make the connection fail.
+	//
+	pub fn protocols( mut self, protocols: impl IntoIterator< Item = impl AsRef<str> > ) -> Self
+	{
+		self.protocols = Some( protocols.into_iter().map( |p| p.as_ref().to_string() ).collect() );
+		self
+	}
+
+
+	/// The maximum size of an incoming message. Bigger messages are not copied into wasm memory.
+	/// Instead [WsIo](crate::WsIo) will yield a [WsErrKind::MessageTooBig](crate::WsErrKind::MessageTooBig)
+	/// error in their place. `None` means unlimited. Defaults to [DEFAULT_MAX_MESSAGE_SIZE].
+	///
+	/// For binary messages this is the size in bytes. For text messages, the length of the string
+	/// in UTF-16 code units is compared to the limit, since that is what we can know before converting
+	/// the string.
+	//
+	pub fn max_message_size( mut self, max: impl Into<Option<usize>> ) -> Self
+	{
+		self.max_message_size = max.into();
+		self
+	}
+
+
+	/// Close the connection with code 1009 (Message Too Big) when a message exceeds
+	/// [WsConfig::max_message_size]. Defaults to `false`.
+	//
+	pub fn close_on_oversize( mut self, close: bool ) -> Self
+	{
+		self.close_on_oversize = close;
+		self
+	}
+
+
+	/// The maximum size of an outgoing message in bytes. For text messages, this is the length of the UTF-8
+	/// encoded string. Sending a bigger message will return [WsErrKind::MessageTooBig](crate::WsErrKind::MessageTooBig)
+	/// instead of sending it. This is useful if the server kills connections that send frames over a certain size.
+	/// `None` means unlimited, which is the default.
+	///
+	/// You can bypass the check for a specific message with [WsSender::send_unchecked](crate::WsSender::send_unchecked).
+	//
+	pub fn max_send_size( mut self, max: impl Into<Option<usize>> ) -> Self
+	{
+		self.max_send_size = max.into();
+		self
+	}
+
+
+	/// Apply backpressure when the browser has more than `bytes` of sent data queued, see
+	/// [WsStream::buffered_amount](crate::WsStream::buffered_amount). The `Sink` of [WsSender](crate::WsSender) and
+	/// [WsIo](crate::WsIo) is then not ready until the queue drains below it, and [WsSender::send_timeout](crate::WsSender::send_timeout)
+	/// times out if it doesn't in time. The browser has no event for this, so `bufferedAmount` is checked every
+	/// 10 milliseconds, using the [timer](WsConfig::timer). `None` means the sink is ready as soon as the connection
+	/// is open, which is the default.
+	//
+	pub fn send_high_water( mut self, bytes: impl Into<Option<u32>> ) -> Self
+	{
+		self.send_high_water = bytes.into();
+		self
+	}
+
+
+	/// How many messages a [WsSubscription](crate::WsSubscription) can buffer before it starts dropping
+	/// the oldest ones. Defaults to [DEFAULT_SUBSCRIBER_CAPACITY]. A capacity of 0 is treated as 1.
+	//
+	pub fn subscriber_capacity( mut self, capacity: usize ) -> Self
+	{
+		self.subscriber_capacity = capacity;
+		self
+	}
+
+
+	/// Call `hook` when a [WsSubscription](crate::WsSubscription) drops messages because it's buffer is full, eg. to show
+	/// that the application is falling behind. It's called with the number of messages dropped since the previous call,
+	/// at most once per `interval`. Drops that happen in between are counted in the next call, but note that there is
+	/// no call until a message is dropped again. See [WsIo::stats](crate::WsIo::stats) for the totals.
+	///
+	/// The hook is called from the onmessage callback of the connection, so keep it short.
+	///
+	/// Two configs are only equal if they share the same hook, like for the timer.
+	//
+	pub fn lag_warning( mut self, interval: Duration, hook: impl Fn(u64) + 'static ) -> Self
+	{
+		self.lag_warning = Some( LagWarning::new( interval, hook ) );
+		self
+	}
+
+
+	/// The number of unread messages on the [WsIo](crate::WsIo) stream from which [WsIo::is_backlogged](crate::WsIo::is_backlogged)
+	/// returns `true`. This doesn't limit the queue, messages are never dropped from it. Defaults to [DEFAULT_BACKLOG_THRESHOLD].
+	//
+	pub fn backlog_threshold( mut self, threshold: usize ) -> Self
+	{
+		self.backlog_threshold = threshold;
+		self
+	}
+
+
+	/// Close the connection with this code and reason when the page is unloaded (`pagehide`), so the server
+	/// sees a clean close instead of code 1006. The listener is removed when the connection closes or the
+	/// [WsIo](crate::WsIo) is dropped. In a worker, where there is no page, this does nothing.
+	///
+	/// **Note**: Browsers only let scripts close with code 1000 or 3000-4999. If the code is refused,
+	/// for example 1001 (Going Away), 1000 is used instead. The reason must be at most 123 bytes.
+	//
+	pub fn close_on_unload( mut self, code: u16, reason: impl Into<String> ) -> Self
+	{
+		self.close_on_unload = Some(( code, reason.into() ));
+		self
+	}
+
+
+	/// Deliver incoming binary messages as [WsMessage::Text](crate::WsMessage::Text), for servers that send UTF-8
+	/// text in binary frames. The decoding is done by the browser's `TextDecoder` before copying the data into wasm
+	/// memory, which is faster than `String::from_utf8` on large messages. Messages that aren't valid UTF-8 yield
+	/// a [WsErrKind::InvalidUtf8](crate::WsErrKind::InvalidUtf8) error. Defaults to `false`.
+	//
+	pub fn binary_to_text( mut self, convert: bool ) -> Self
+	{
+		self.binary_to_text = convert;
+		self
+	}
+
+
+	/// Deliver incoming text messages as [WsMessage::Binary](crate::WsMessage::Binary) containing the UTF-8 bytes,
+	/// using the browser's `TextEncoder`. Defaults to `false`.
+	//
+	pub fn text_to_binary( mut self, convert: bool ) -> Self
+	{
+		self.text_to_binary = convert;
+		self
+	}
+
+
+	/// Verify that the server selected one of the [WsConfig::protocols] we asked for. Servers that don't know the
+	/// protocols can just ignore them, in which case the browser opens the connection with an empty protocol. When
+	/// this is set, the connection is then closed with `close_code` and connecting fails with
+	/// [WsErrKind::ProtocolNegotiationFailed](crate::WsErrKind::ProtocolNegotiationFailed). Does nothing when no
+	/// protocols are requested. Defaults to `None`, not verifying anything.
+	///
+	/// **Note**: Browsers only let scripts close with code 1000 or 3000-4999. If the code is refused, for example
+	/// 1002 (Protocol Error), 1000 is used instead.
+	//
+	pub fn require_protocol( mut self, close_code: impl Into<Option<u16>> ) -> Self
+	{
+		self.require_protocol = close_code.into();
+		self
+	}
+
+
+	/// The [Timer] used by the connection, see [WsStream::ready](crate::WsStream::ready) and
+	/// [WsSender::send_timeout](crate::WsSender::send_timeout). Defaults to [JsTimer](crate::JsTimer).
+	///
+	/// Two configs with timers are only equal if they share the same timer, because a clone of the config refers to the same timer.
+	//
+	pub fn timer( mut self, timer: impl Timer + 'static ) -> Self
+	{
+		self.timer = SharedTimer::new( timer );
+		self
+	}
+
+
+	/// Emit `performance.mark` and `performance.measure` entries, to see what the connection does in the timeline of the
+	/// browser's developer tools. Requires the `instrument` feature.
+	///
+	/// Entry names start with `ws_stream_wasm:<id>`, where the id is unique for the page or worker:
+	///
+	/// - `:connect:start` and `:connect:open` marks and a `:connect` measure between them.
+	/// - for one in every `sample_every` incoming messages, `:msg:<n>:received` when it arrives, `:msg:<n>:delivered`
+	///   when it's taken from the [WsIo](crate::WsIo) stream and a `:msg:<n>` measure between them. Use 0 to only instrument
+	///   the connection.
+	///
+	/// Does nothing if the Performance API isn't available.
+	//
+	#[ cfg( feature = "instrument" ) ]
+	//
+	pub fn instrument( mut self, sample_every: u32 ) -> Self
+	{
+		self.instrument = Some( sample_every );
+		self
+	}
+
+
+	/// Receive binary messages bigger than `threshold` bytes as a [WsMessageStream](crate::WsMessageStream), which reads
+	/// the data in chunks, rather than as one `Vec<u8>`. Get them with [WsIo::next_incoming](crate::WsIo::next_incoming).
+	/// Defaults to `None`, receiving all messages in one piece.
+	///
+	/// When this is set, the browser gives us binary messages as a `Blob`. Smaller messages are then read into memory
+	/// asynchronously and arrive as [WsMessage::Binary](crate::WsMessage::Binary) like before, in order. They pass
+	/// through interceptors and subscribers once they have been read, so subscribers may see them after text messages
+	/// that arrived later. Streamed messages don't go to interceptors and subscribers.
+	///
+	/// [WsConfig::max_message_size] still applies and is checked first, so set it high enough.
+	//
+	pub fn stream_threshold( mut self, threshold: impl Into<Option<usize>> ) -> Self
+	{
+		self.stream_threshold = threshold.into();
+		self
+	}
+
+
+	/// What [WsIo](crate::WsIo) does with incoming messages that can't be converted, see [ErrorPolicy].
+	/// Defaults to [ErrorPolicy::YieldError].
+	//
+	pub fn error_policy( mut self, policy: ErrorPolicy ) -> Self
+	{
+		self.error_policy = policy;
+		self
+	}
+
+
+	/// Send a message at a regular interval to keep the connection open, see [Heartbeat]. Defaults to `None`.
+	/// The heartbeat uses the [timer](WsConfig::timer) and the [spawner](WsConfig::spawner) of this config.
+	//
+	pub fn heartbeat( mut self, heartbeat: impl Into<Option<Heartbeat>> ) -> Self
+	{
+		self.heartbeat = heartbeat.into();
+		self
+	}
+
+
+	/// The [Spawner] that runs the background tasks of the connection. Defaults to [RtSpawner](crate::RtSpawner).
+	///
+	/// Like for the timer, two configs are only equal if they share the same spawner.
+	//
+	pub fn spawner( mut self, spawner: impl Spawner + 'static ) -> Self
+	{
+		self.spawner = SharedSpawner::new( spawner );
+		self
+	}
+}
+
+
+
+impl Default for WsConfig
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			protocols          : None                            ,
+			max_message_size   : Some( DEFAULT_MAX_MESSAGE_SIZE ),
+			close_on_oversize  : false                           ,
+			max_send_size      : None                            ,
+			send_high_water    : None                            ,
+			subscriber_capacity: DEFAULT_SUBSCRIBER_CAPACITY     ,
+			close_on_unload    : None                            ,
+			binary_to_text     : false                           ,
+			text_to_binary     : false                           ,
+			require_protocol   : None                            ,
+			timer              : SharedTimer::default()          ,
+			spawner            : SharedSpawner::default()        ,
+			backlog_threshold  : DEFAULT_BACKLOG_THRESHOLD       ,
+			stream_threshold   : None                            ,
+			error_policy       : ErrorPolicy::default()          ,
+			heartbeat          : None                            ,
+			lag_warning        : None                            ,
+
+			#[ cfg( feature = "instrument" ) ]
+			//
+			instrument         : None                            ,
+		}
+	}
+}
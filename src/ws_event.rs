@@ -4,6 +4,7 @@ use crate::{ import::* };
 /// Events related to the WebSocket
 //
 #[ derive( Clone, Debug, PartialEq, Eq ) ]
+#[ cfg_attr( feature = "serde", derive( serde::Serialize, serde::Deserialize ) ) ]
 //
 pub enum WsEvent
 {
@@ -23,6 +24,15 @@ pub enum WsEvent
 	/// The connection is now Open
 	//
 	Open,
+
+	/// The browser lost network connectivity (the `offline` event). The connection will probably
+	/// fail soon, but the browser might take a while to notice.
+	//
+	NetworkOffline,
+
+	/// The browser regained network connectivity (the `online` event).
+	//
+	NetworkOnline,
 }
 
 
@@ -39,6 +49,9 @@ impl WsEvent
 			Self::Closing  => WsEventType::CLOSING ,
 			Self::Open     => WsEventType::OPEN    ,
 			Self::Error    => WsEventType::ERROR   ,
+
+			Self::NetworkOffline => WsEventType::NETWORK_OFFLINE,
+			Self::NetworkOnline  => WsEventType::NETWORK_ONLINE ,
 		}
 	}
 }
@@ -52,6 +65,7 @@ impl WsEvent
 // to be Send.
 //
 #[ derive( Clone, Debug, PartialEq, Eq ) ]
+#[ cfg_attr( feature = "serde", derive( serde::Serialize, serde::Deserialize ) ) ]
 //
 pub struct CloseEvent
 {
@@ -110,6 +124,14 @@ bitflags!
 		/// Error
 		//
 		const ERROR   = 0b1000;
+
+		/// NetworkOffline
+		//
+		const NETWORK_OFFLINE = 0b01_0000;
+
+		/// NetworkOnline
+		//
+		const NETWORK_ONLINE  = 0b10_0000;
 	}
 }
 
@@ -133,7 +155,7 @@ bitflags!
 ///
 /// let program = async
 /// {
-///    let (mut ws, wsio) = WsStream::connect( "127.0.0.1:3012", None ).await
+///    let (mut ws, wsio) = WsStream::connect( "127.0.0.1:3012" ).await
 ///
 ///       .expect_throw( "assume the connection succeeds" );
 ///
@@ -158,11 +180,7 @@ impl NextEvent
 	//
 	pub fn new( rx: UnboundedReceiver<WsEvent>, filter: impl Into<Option<WsEventType>> ) -> Self
 	{
-		let mut filter = filter.into();
-
-		if filter.is_none() { filter = Some( WsEventType::all() ) }
-
-		let filter = filter.unwrap();
+		let filter = filter.into().unwrap_or_else( WsEventType::all );
 
 
 		Self
@@ -175,6 +193,10 @@ impl NextEvent
 					WsEvent::Closing  if filter.contains( WsEventType::CLOSING ) => ready( Some( evt ) ),
 					WsEvent::Close(_) if filter.contains( WsEventType::CLOSE   ) => ready( Some( evt ) ),
 					WsEvent::Error    if filter.contains( WsEventType::ERROR   ) => ready( Some( evt ) ),
+
+					WsEvent::NetworkOffline if filter.contains( WsEventType::NETWORK_OFFLINE ) => ready( Some( evt ) ),
+					WsEvent::NetworkOnline  if filter.contains( WsEventType::NETWORK_ONLINE  ) => ready( Some( evt ) ),
+
 					_                                                             => ready( None        ),
 				}
 			}))
@@ -0,0 +1,99 @@
+use
+{
+	crate :: { import::*, WsMessage } ,
+};
+
+
+/// Hooks that can inspect, modify or drop messages as they go in and out of a connection.
+/// Install them with [WsIo::add_interceptor](crate::WsIo::add_interceptor).
+///
+/// Interceptors run for every [WsSender](crate::WsSender) of the connection and before incoming
+/// messages are handed to the [WsIo](crate::WsIo) stream and to subscribers, so everyone sees the same result.
+/// When several interceptors are installed, they run in the order they were added, both for sending and receiving.
+///
+/// Returning `None` drops the message, in which case the following interceptors don't see it. A dropped
+/// outgoing message is considered successfully sent.
+///
+/// Both methods pass the message through unchanged by default, so you only need to implement the one you need.
+///
+/// An interceptor can send messages itself, eg. reply from `on_receive` through a [WsSender](crate::WsSender), and it
+/// can install more interceptors. The message it sends goes through the other interceptors, but not through itself.
+/// Interceptors installed during a callback see the next message.
+//
+pub trait WsInterceptor
+{
+	/// Called for every message before it is sent.
+	//
+	fn on_send( &mut self, msg: WsMessage ) -> Option<WsMessage>
+	{
+		Some( msg )
+	}
+
+
+	/// Called for every message that is received.
+	//
+	fn on_receive( &mut self, msg: WsMessage ) -> Option<WsMessage>
+	{
+		Some( msg )
+	}
+}
+
+
+
+type Shared = Rc<RefCell< Box<dyn WsInterceptor> >>;
+
+
+// The interceptors of a connection, shared between WsIo, the onmessage callback and all WsSenders.
+//
+// We run them on a copy of the list, so an interceptor can send or add interceptors without the list being
+// borrowed. Each interceptor is borrowed while it runs, so a message it sends itself skips it.
+//
+#[ derive( Clone, Default ) ]
+//
+pub(crate) struct Interceptors
+{
+	list: Rc<RefCell< Vec<Shared> >>,
+}
+
+
+
+impl Interceptors
+{
+	pub(crate) fn push( &self, interceptor: Box<dyn WsInterceptor> )
+	{
+		self.list.borrow_mut().push( Rc::new( RefCell::new( interceptor ) ) );
+	}
+
+
+	pub(crate) fn is_empty( &self ) -> bool
+	{
+		self.list.borrow().is_empty()
+	}
+
+
+	pub(crate) fn on_send( &self, msg: WsMessage ) -> Option<WsMessage>
+	{
+		self.run( msg, |i, msg| i.on_send( msg ) )
+	}
+
+
+	pub(crate) fn on_receive( &self, msg: WsMessage ) -> Option<WsMessage>
+	{
+		self.run( msg, |i, msg| i.on_receive( msg ) )
+	}
+
+
+	fn run( &self, msg: WsMessage, f: impl Fn( &mut dyn WsInterceptor, WsMessage ) -> Option<WsMessage> ) -> Option<WsMessage>
+	{
+		let list = self.list.borrow().clone();
+
+		list.iter().try_fold( msg, |msg, i| match i.try_borrow_mut()
+		{
+			Ok ( mut i ) => f( &mut **i, msg ),
+
+			// It's running further up the stack and sent this message.
+			//
+			Err( _ ) => Some( msg ),
+		})
+	}
+}
@@ -1,10 +1,19 @@
 use
 {
-	crate :: { import::*, WsErr, WsErrKind, WsMessage, WsState, WsEvent, NextEvent, WsEventType },
+	crate :: { import::*, ws_message::message_size, ws_subscription::Subscribers, ws_interceptor::Interceptors, unload::UnloadGuard, text_codec::TextCodec } ,
+	crate :: { WsConfig, WsErr, WsErrKind, WsInterceptor, WsMessage, WsSender, WsState, WsEvent, WsSubscription } ,
+	crate :: { CloseEvent, instrument::Instrument, WsChannel, WsDecode, WsEncode, WsIncoming, WsMessageStream } ,
+	crate :: { ws_message_stream::read_blob, ErrorPolicy, error_policy, heartbeat } ,
+	crate :: { WsStats, ws_stats::LagReporter, ListenerGuard, NextEvent, WsEventType } ,
+	futures :: { future::poll_fn } ,
+	std     :: { cell::Cell      } ,
 };
 
 
-/// A futures 0.3 Sink/Stream of [WsMessage]. It further implements AsyncRead/AsyncWrite
+/// A futures 0.3 Sink/Stream of [WsMessage]. The stream yields `Result<WsMessage, WsErr>`, since
+/// an incoming message can be rejected, eg. when it exceeds [WsConfig::max_message_size]. Such an
+/// error does not end the stream. The stream ends when the connection closes or fails, after yielding the messages
+/// that were already received, also for a task that was waiting at that moment. It further implements AsyncRead/AsyncWrite
 /// that can be framed with codecs. You can use the compat layer from the futures library if you want to
 /// use tokio codecs. See the [integration tests](https://github.com/ws_stream_wasm/tree/master/tests/tokio_codec.rs)
 /// if you need an example.
@@ -17,15 +26,39 @@ pub struct WsIo
 {
 	ws     : Rc< WebSocket >                                ,
 
-	// The queue of received messages
+	// The queue of received messages and the waker of the reading task,
+	// shared with the onmessage callback.
 	//
-	queue  : Rc<RefCell< VecDeque<WsMessage> >>             ,
+	incoming: Rc<RefCell< Incoming >>                      ,
 
-	// Last waker of task that wants to read incoming messages
-	// to be woken up on a new message
+	// Other consumers of incoming messages, see WsIo::subscribe.
 	//
-	waker  : Rc<RefCell< Option<Waker>       >>             ,
+	subs   : Rc<RefCell< Subscribers >>                     ,
+	sub_cap: usize                                          ,
 
+	// See WsConfig::backlog_threshold.
+	//
+	backlog: usize                                          ,
+
+	// See WsConfig::stream_threshold.
+	//
+	stream_threshold: Option<usize>                         ,
+
+	// Shared with the onmessage callback and all senders.
+	//
+	icpt   : Interceptors                                   ,
+
+	// Removes the pagehide listener when dropped, see WsConfig::close_on_unload.
+	//
+	unload : Rc<RefCell< Option<UnloadGuard> >>             ,
+
+	// The close event, once the connection is closed. Shared with WsStream.
+	//
+	close_evt: Rc<RefCell< Option<CloseEvent> >>            ,
+
+	// Performance marks, see WsConfig::instrument.
+	//
+	instrument: Instrument                                  ,
 
 	// A pointer to the pharos of WsStream for when we
 	// need to listen to events
@@ -41,10 +74,13 @@ pub struct WsIo
 	//
 	on_mesg: Closure< dyn FnMut( MessageEvent ) + 'static > ,
 
-	// This allows us to store a future to poll when
-	// Sink::poll_close is called
+	// End the stream and the subscriptions on the close and error events.
+	//
+	on_end : [ ListenerGuard; 2 ]                           ,
+
+	// The Sink implementation is shared with WsSender
 	//
-	closer : Option< NextEvent >                            ,
+	sender : WsSender                                       ,
 }
 
 
@@ -52,25 +88,205 @@ impl WsIo
 {
 	/// Create a new WsIo.
 	//
-	pub fn new( ws: Rc<WebSocket>, pharos : Rc<RefCell< Pharos<WsEvent> >> ) -> Self
+	pub(crate) fn new
+	(
+		ws       : Rc<WebSocket>                        ,
+		pharos   : Rc<RefCell< Pharos<WsEvent> >>       ,
+		config   : &WsConfig                            ,
+		unload   : Rc<RefCell< Option<UnloadGuard> >>   ,
+		close_evt: Rc<RefCell< Option<CloseEvent> >>    ,
+		instrument: Instrument                           ,
+	)
+		-> Self
 	{
-		let waker: Rc<RefCell<Option<Waker>>> = Rc::new( RefCell::new( None ));
+		let state    = ReadState::PendingChunk;
+		let incoming = Rc::new( RefCell::new( Incoming::default() ) );
+		let inc2     = incoming.clone();
+		let subs     = Rc::new( RefCell::new( Subscribers::new( config.lag_warning.clone().map( LagReporter::new ) ) ) );
+		let subs2    = subs.clone();
+		let icpt     = Interceptors::default();
+		let icpt2    = icpt.clone();
+		let ws2      = ws.clone();
+		let ph2      = pharos.clone();
+		let inst2    = instrument.clone();
+		let spawner  = config.spawner.clone();
+		let ws3      = ws.clone();
+		let ph3      = pharos.clone();
+		let policy   = config.error_policy;
+		let activity = Rc::new( Cell::new( false ) );
+		let act2     = activity.clone();
+
+		let max_size          = config.max_message_size ;
+		let close_on_oversize = config.close_on_oversize;
+		let stream_threshold  = config.stream_threshold ;
+		let codec             = Rc::new( TextCodec::new( config.binary_to_text, config.text_to_binary ) );
+
+
+		// Let the interceptors have a look before anyone else, then give the message to the subscribers.
+		// Returns None if an interceptor dropped the message.
+		//
+		let process = Rc::new( move |item: Result<WsMessage, WsErr>| -> Option<Result<WsMessage, WsErr>>
+		{
+			match ( &item, policy )
+			{
+				( Err( e ), ErrorPolicy::SkipAndCount ) if error_policy::is_conversion( e ) =>
+				{
+					warn!( "WsStream: skipping message: {}", e );
+
+					subs2.borrow_mut().record_skip();
+					return None;
+				}
 
-		let state = ReadState::PendingChunk;
-		let queue = Rc::new( RefCell::new( VecDeque::new() ) );
-		let q2    = queue.clone();
-		let w2    = waker.clone();
+				( Err( e ), ErrorPolicy::CloseConnection( code ) ) if error_policy::is_conversion( e ) =>
+				{
+					error_policy::close( &ws3, &ph3, code, "Invalid message" );
+				}
 
+				_ => {}
+			}
 
-		// Send the incoming ws messages to the WsStream object
+
+			let item = match item
+			{
+				Ok( msg ) => match icpt2.on_receive( msg )
+				{
+					Some( msg ) => Ok( msg ),
+
+					None =>
+					{
+						trace!( "WsStream: incoming message dropped by interceptor" );
+						return None;
+					}
+				}
+
+				Err( e ) => Err( e ),
+			};
+
+
+			// Only clone the message if someone subscribed.
+			//
+			if let Ok( msg ) = &item
+			{
+				let ( wakers, lag ) =
+				{
+					let mut subs = subs2.borrow_mut();
+
+					if subs.is_empty() { ( Vec::new(), None ) }
+					else               { subs.broadcast( msg ) }
+				};
+
+				for w in wakers { w.wake() }
+
+				// The callback might want to look at WsIo::stats, so we don't hold the borrow.
+				//
+				if let Some(( hook, dropped )) = lag { hook( dropped ) }
+			}
+
+			Some( item )
+		});
+
+
+		// Push the incoming ws messages in the queue and wake up the reading task directly.
 		//
 		let on_mesg = Closure::wrap( Box::new( move |msg_evt: MessageEvent|
 		{
 			trace!( "WsStream: message received!" );
 
-			q2.borrow_mut().push_back( WsMessage::from( msg_evt ) );
+			// Anything we receive shows the connection is alive, see Heartbeat::timeout.
+			//
+			act2.set( true );
+
+			// Check the size before copying anything into wasm memory.
+			//
+			let item = match max_size
+			{
+				Some( limit ) =>
+				{
+					let size = message_size( &msg_evt.data() );
+
+					if size > limit
+					{
+						let err = WsErr::from( WsErrKind::MessageTooBig{ size, limit } );
+
+						error!( "{}", err );
+
+						if close_on_oversize
+						{
+							// Only fails if the connection is no longer open.
+							//
+							if ws2.close_with_code_and_reason( 1009, "Message too big" ).is_ok()
+							{
+								rt::block_on( ph2.borrow_mut().notify( &WsEvent::Closing ) );
+							}
+						}
+
+						Some( Err( err ) )
+					}
 
-			if let Some( w ) = w2.borrow_mut().take()
+					else { None }
+				}
+
+				None => None,
+			};
+
+
+			// We only get Blob's with WsConfig::stream_threshold. Big ones are streamed, the others are
+			// read in the background. Either way they keep their place in the queue.
+			//
+			let slot = match ( item, stream_threshold, msg_evt.data().dyn_into::< Blob >() )
+			{
+				( Some( err ), _, _ ) => match process( err )
+				{
+					Some( item ) => Slot::Ready( item ),
+					None         => return,
+				},
+
+				( None, Some( threshold ), Ok( blob ) ) if blob.size() as usize > threshold => Slot::Large( blob ),
+
+				( None, Some(_), Ok( blob ) ) =>
+				{
+					let reading  = Rc::new( RefCell::new( Reading::Pending ) );
+					let reading2 = reading.clone();
+					let process2 = process.clone();
+					let codec2   = codec.clone();
+					let inc3     = inc2.clone();
+
+					spawner.spawn_local( async move
+					{
+						let item = read_blob( &blob ).await.and_then( |buf| codec2.binary( &buf ) );
+
+						*reading2.borrow_mut() = match process2( item )
+						{
+							Some( item ) => Reading::Done( item ),
+							None         => Reading::Dropped     ,
+						};
+
+						let waker = inc3.borrow_mut().waker.take();
+
+						if let Some( w ) = waker { w.wake() }
+					});
+
+					Slot::Reading( reading )
+				}
+
+				( None, _, _ ) => match process( codec.message( msg_evt ) )
+				{
+					Some( item ) => Slot::Ready( item ),
+					None         => return,
+				},
+			};
+
+
+			let waker =
+			{
+				let mut inc = inc2.borrow_mut();
+
+				inc.queue.push_back( slot );
+				inst2.received();
+				inc.waker.take()
+			};
+
+			if let Some( w ) = waker
 			{
 				trace!( "WsStream: waking up task" );
 				w.wake()
@@ -84,15 +300,77 @@ impl WsIo
 		ws.set_onmessage  ( Some( on_mesg.as_ref().unchecked_ref() ) );
 
 
+		// No more messages will arrive, so wake up everyone who waits for one. They will get the messages
+		// that are still queued, and then the end of the stream.
+		//
+		let inc5  = incoming.clone();
+		let subs3 = subs.clone();
+
+		let end = Rc::new( move ||
+		{
+			let waker = { let mut inc = inc5.borrow_mut(); inc.ended = true; inc.waker.take() };
+			let subs  = subs3.borrow_mut().end();
+
+			waker.into_iter().chain( subs ).for_each( Waker::wake );
+		});
+
+		let end2   = end.clone();
+		let on_end =
+		[
+			ListenerGuard::new( &ws, "close", move |_| end () ),
+			ListenerGuard::new( &ws, "error", move |_| end2() ),
+		];
+
+
+		let sender = WsSender::new( ws.clone(), pharos.clone(), config.max_send_size, config.send_high_water, icpt.clone(), config.timer.clone() );
+
+
+		// The heartbeat stops by itself once the connection is no longer open.
+		//
+		if let Some( hb ) = config.heartbeat.clone()
+		{
+			let inc4 = incoming.clone();
+			let ws4  = ws.clone();
+			let ph4  = pharos.clone();
+
+			let on_timeout = move ||
+			{
+				let waker =
+				{
+					let mut inc = inc4.borrow_mut();
+
+					inc.queue.push_back( Slot::Ready( Err( WsErrKind::HeartbeatTimeout.into() ) ) );
+					inc.waker.take()
+				};
+
+				if let Some( w ) = waker { w.wake() }
+
+				error_policy::close( &ws4, &ph4, 1000, "Heartbeat timeout" );
+			};
+
+			let closed = NextEvent::new( pharos.borrow_mut().observe_unbounded(), WsEventType::CLOSE );
+
+			config.spawner.spawn_local( heartbeat::run( hb, sender.clone(), config.timer.clone(), activity, closed, on_timeout ) );
+		}
+
+
 		Self
 		{
+			sender  ,
 			ws      ,
-			queue   ,
+			incoming,
+			subs    ,
+			sub_cap : config.subscriber_capacity,
+			backlog : config.backlog_threshold  ,
+			stream_threshold: config.stream_threshold,
+			icpt    ,
+			unload  ,
+			close_evt,
+			instrument,
 			on_mesg ,
+			on_end  ,
 			state   ,
-			waker   ,
 			pharos  ,
-			closer: None,
 		}
 	}
 
@@ -111,6 +389,284 @@ impl WsIo
 
 
 
+	/// The [CloseEvent] of the connection, once it is closed, whoever closed it. It is kept after the event
+	/// so you can still find out why the stream ended. Returns `None` as long as the connection is not closed.
+	//
+	pub fn close_event( &self ) -> Option<CloseEvent>
+	{
+		self.close_evt.borrow().clone()
+	}
+
+
+
+	/// Take the next message if one has already arrived, without waiting. This never registers a waker, so it
+	/// can be called from synchronous code, like a `requestAnimationFrame` callback. Returns `None` when no
+	/// message is waiting, even if the connection is closed.
+	///
+	/// Messages are queued until someone takes them, so there is no race with a `next()` that is pending on the
+	/// same connection: it holds `&mut WsIo`, so you can only call this after it resolved or was dropped. A message
+	/// that arrives after a `next()` was dropped stays in the queue and is returned by the next call to either.
+	//
+	pub fn try_next_now( &mut self ) -> Option<Result<WsMessage, WsErr>>
+	{
+		match Incoming::pop( &self.incoming, &self.instrument )
+		{
+			Poll::Ready( Some( item ) ) =>
+			{
+				self.instrument.delivered();
+
+				Some( self.whole( item ) )
+			}
+
+			_ => None,
+		}
+	}
+
+
+
+	/// A copy of the next message, if one has already arrived, without taking it. Errors are reported as their
+	/// [WsErrKind]. The next call to `next()` or [WsIo::try_next_now] returns the same message.
+	///
+	/// This clones the message, so for big messages you might rather take it.
+	//
+	pub fn peek( &self ) -> Option<Result<WsMessage, WsErrKind>>
+	{
+		let inc = self.incoming.borrow();
+
+		if inc.streaming { return None }
+
+		let copy = |item: &Result<WsMessage, WsErr>| match item
+		{
+			Ok ( msg ) => Ok ( msg.clone()      ),
+			Err( e   ) => Err( e.kind().clone() ),
+		};
+
+		for slot in inc.queue.iter()
+		{
+			match slot
+			{
+				Slot::Ready  ( item ) => return Some( copy( item ) ),
+				Slot::Large  ( blob ) => return Some( Err( self.too_big( blob.size() as usize ) ) ),
+
+				Slot::Reading( r ) => match &*r.borrow()
+				{
+					Reading::Pending        => return None,
+					Reading::Done   ( item ) => return Some( copy( item ) ),
+					Reading::Dropped        => continue,
+				}
+			}
+		}
+
+		None
+	}
+
+
+
+	/// Wait for the next message, receiving binary messages over [WsConfig::stream_threshold] as a
+	/// [WsMessageStream]. Without that option, this always returns [WsIncoming::Message].
+	///
+	/// No other message is delivered while a [WsMessageStream] is alive, so messages keep their order. Read the
+	/// stream to the end or drop it to get the next message.
+	///
+	/// When you use [WsIo] as a `Stream` instead, the big messages are dropped and yield
+	/// [WsErrKind::MessageTooBig] with the threshold as limit.
+	//
+	pub async fn next_incoming( &mut self ) -> Option<Result<WsIncoming, WsErr>>
+	{
+		poll_fn( |cx| self.poll_incoming( cx ) ).await
+	}
+
+
+
+	fn poll_incoming( &self, cx: &mut Context ) -> Poll<Option< Result<WsIncoming, WsErr> >>
+	{
+		match Incoming::pop( &self.incoming, &self.instrument )
+		{
+			Poll::Ready( Some( item ) ) =>
+			{
+				self.instrument.delivered();
+
+				return Poll::Ready( Some( item ) );
+			}
+
+			// Once the queue is empty, check the state of the connection.
+			// When it is closing or closed, no more messages will arrive, so
+			// return Poll::Ready( None )
+			//
+			Poll::Ready( None ) =>
+			{
+				if self.incoming.borrow().ended { return Poll::Ready( None ) }
+
+				match self.ready_state()
+				{
+					WsState::Open | WsState::Connecting => {}
+					_                                   => return Poll::Ready( None ),
+				}
+			}
+
+			// A message is being read or streamed.
+			//
+			Poll::Pending => {}
+		}
+
+
+		let mut inc = self.incoming.borrow_mut();
+
+		// Avoid cloning the waker if we already have the right one.
+		//
+		match &inc.waker
+		{
+			Some( w ) if w.will_wake( cx.waker() ) => {}
+			_ => inc.waker = Some( cx.waker().clone() ),
+		}
+
+		Poll::Pending
+	}
+
+
+
+	// For the API's that only deliver whole messages.
+	//
+	fn whole( &self, item: Result<WsIncoming, WsErr> ) -> Result<WsMessage, WsErr>
+	{
+		match item
+		{
+			Ok( WsIncoming::Message( msg ) ) => Ok( msg ),
+
+			// Dropping the stream lets the next message through.
+			//
+			Ok( WsIncoming::Stream( stream ) ) =>
+			{
+				let err = WsErr::from( self.too_big( stream.size() as usize ) );
+
+				error!( "{}", err );
+
+				Err( err )
+			}
+
+			Err( e ) => Err( e ),
+		}
+	}
+
+
+
+	fn too_big( &self, size: usize ) -> WsErrKind
+	{
+		WsErrKind::MessageTooBig{ size, limit: self.stream_threshold.unwrap_or( 0 ) }
+	}
+
+
+
+	/// The number of messages (and errors) that have arrived, but that have not been taken from the [WsIo] stream yet.
+	/// This is exactly the number of items the stream will yield without waiting, except with [WsConfig::stream_threshold],
+	/// where it includes binary messages that are still being read. Messages dropped by interceptors
+	/// are not counted and subscriptions have their own buffers.
+	//
+	pub fn pending_messages( &self ) -> usize
+	{
+		self.incoming.borrow().queue.len()
+	}
+
+
+
+	/// Whether [WsIo::pending_messages] reached [WsConfig::backlog_threshold], which means the consumer is falling behind,
+	/// for example to skip optional work until it caught up.
+	//
+	pub fn is_backlogged( &self ) -> bool
+	{
+		self.pending_messages() >= self.backlog
+	}
+
+
+
+	/// How many messages were dropped from the subscriptions of this connection because they didn't keep up,
+	/// all subscribers together. The [WsIo] stream itself never drops messages, but it counts the ones it skipped
+	/// with [ErrorPolicy::SkipAndCount] in [WsStats::skipped]. See [WsSubscription::stats] for a single subscriber.
+	//
+	pub fn stats( &self ) -> WsStats
+	{
+		self.subs.borrow().stats()
+	}
+
+
+
+	/// Reset the [WsStats] of this connection. The stats of the subscriptions are not affected.
+	//
+	pub fn reset_stats( &self )
+	{
+		self.subs.borrow_mut().reset_stats();
+	}
+
+
+
+	// For the error policy of WsChannel.
+	//
+	pub(crate) fn close_for_policy( &self, code: u16 )
+	{
+		error_policy::close( &self.ws, &self.pharos, code, "Invalid message" );
+	}
+
+
+
+	/// Send text without allocating, see [WsSender::send_text_ref].
+	//
+	pub fn send_text_ref( &self, text: &str ) -> Result<(), WsErr>
+	{
+		self.sender.send_text_ref( text )
+	}
+
+
+
+	/// Get a [WsSender] for this connection. Senders implement `Sink<WsMessage>` through a shared
+	/// reference, so they can be shared without needing exclusive access to the [WsIo].
+	///
+	/// When the [WsIo] is dropped, the connection is closed and senders will return errors.
+	//
+	pub fn sender( &self ) -> WsSender
+	{
+		self.sender.clone()
+	}
+
+
+
+	/// Get a new [WsSubscription], a stream of all messages received from now on. This can be called as many times as
+	/// you want, every subscriber gets every message. The messages are shared, so this doesn't copy the data for every
+	/// subscriber. The [WsIo] stream keeps working as well, but note that messages will accumulate in it if you don't read them.
+	///
+	/// See [WsSubscription] for what happens when a subscriber falls behind.
+	//
+	pub fn subscribe( &self ) -> WsSubscription
+	{
+		self.subs.borrow_mut().subscribe( self.ws.clone(), self.sub_cap )
+	}
+
+
+
+	/// Install a [WsInterceptor] on this connection. Interceptors run in the order they are added. This
+	/// affects all senders of the connection, including the ones created before this call.
+	//
+	pub fn add_interceptor( &self, interceptor: impl WsInterceptor + 'static )
+	{
+		self.icpt.push( Box::new( interceptor ) );
+	}
+
+
+
+	/// Turn this into a typed [WsChannel] that sends `Tx` and receives `Rx`, converting messages with `codec`.
+	///
+	/// ```ignore
+	/// let mut channel = wsio.into_channel::<ClientCmd, ServerEvent, _>( MyCodec::default() );
+	/// ```
+	//
+	pub fn into_channel<Tx, Rx, C>( self, codec: C ) -> WsChannel<Tx, Rx, C>
+
+		where C: WsEncode<Tx> + WsDecode<Rx>
+	{
+		WsChannel::new( self, codec )
+	}
+
+
+
 	/// Access the wrapped [web_sys::WebSocket](https://docs.rs/web-sys/0.3.25/web_sys/struct.WebSocket.html) directly.
 	///
 	/// `ws_stream_wasm` tries to expose all useful functionality through an idiomatic rust API, so hopefully
@@ -148,13 +704,18 @@ impl Drop for WsIo
 	{
 		trace!( "Drop WsIo" );
 
-		// This can't fail
+		// This can't fail, 1000 is always allowed. We don't want to panic in drop just in case.
 		//
-		self.ws.close_with_code( 1000 ).expect( "WsIo::drop - close ws socket" );
+		if let Err( e ) = self.ws.close_with_code( 1000 )
+		{
+			error!( "WsIo::drop - close ws socket: {:?}", e );
+		}
 
 		rt::block_on( self.pharos.borrow_mut().notify( &WsEvent::Closing ) );
 
 		self.ws.set_onmessage( None );
+
+		let _ = self.unload.borrow_mut().take();
 	}
 }
 
@@ -162,33 +723,18 @@ impl Drop for WsIo
 
 impl Stream for WsIo
 {
-	type Item = WsMessage;
+	type Item = Result< WsMessage, WsErr >;
 
 	// Currently requires an unfortunate copy from Js memory to Wasm memory. Hopefully one
 	// day we will be able to receive the MessageEvt directly in Wasm.
 	//
-	fn poll_next( mut self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Option< Self::Item >>
+	fn poll_next( self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Option< Self::Item >>
 	{
 		trace!( "WsIo as Stream gets polled" );
 
-		// Once the queue is empty, check the state of the connection.
-		// When it is closing or closed, no more messages will arrive, so
-		// return Poll::Ready( None )
-		//
-		if self.queue.borrow().is_empty()
-		{
-			*self.waker.borrow_mut() = Some( cx.waker().clone() );
-
-			match self.ready_state()
-			{
-				WsState::Open | WsState::Connecting => Poll::Pending        ,
-				_                                   => Poll::Ready  ( None ),
-			}
-		}
+		let item = ready!( self.poll_incoming( cx ) );
 
-		// As long as there is things in the queue, just keep reading
-		//
-		else { Poll::Ready( self.queue.borrow_mut().pop_front() ) }
+		Poll::Ready( item.map( |item| self.whole( item ) ) )
 	}
 }
 
@@ -201,178 +747,192 @@ impl Sink<WsMessage> for WsIo
 	type Error = WsErr;
 
 
-	// Web API does not really seem to let us check for readiness, other than the connection state.
-	//
-	fn poll_ready( self: Pin<&mut Self>, _: &mut Context ) -> Poll<Result<(), Self::Error>>
+	fn poll_ready( self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Result<(), Self::Error>>
 	{
-		trace!( "Sink<WsMessage> for WsIo: poll_ready" );
-
-		match self.ready_state()
-		{
-			WsState::Connecting => Poll::Pending        ,
-			WsState::Open       => Poll::Ready( Ok(()) ),
-			_                   => Poll::Ready( Err( WsErrKind::ConnectionNotOpen.into() )),
-		}
+		Pin::new( &mut &self.sender ).poll_ready( cx )
 	}
 
 
 	fn start_send( self: Pin<&mut Self>, item: WsMessage ) -> Result<(), Self::Error>
 	{
-		trace!( "Sink<WsMessage> for WsIo: start_send" );
+		Pin::new( &mut &self.sender ).start_send( item )
+	}
 
-		match self.ready_state()
-		{
-			WsState::Open =>
-			{
-				// The send method can return 2 errors:
-				// - unpaired surrogates in UTF (we shouldn't get those in rust strings)
-				// - connection is already closed.
-				//
-				// So if this returns an error, we will return ConnectionNotOpen. In principle
-				// we just checked that it's open, but this guarantees correctness.
-				//
-				match item
-				{
-					WsMessage::Binary( mut d ) => { self.ws.send_with_u8_array( &mut d ).map_err( |_| WsErrKind::ConnectionNotOpen)?; }
-					WsMessage::Text  (     s ) => { self.ws.send_with_str     ( &    s ).map_err( |_| WsErrKind::ConnectionNotOpen)?; }
-				}
 
-				Ok(())
-			},
+	fn poll_flush( self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		Pin::new( &mut &self.sender ).poll_flush( cx )
+	}
 
 
-			// Connecting, Closing or Closed
-			//
-			_ => Err( WsErrKind::ConnectionNotOpen.into() ),
-		}
+	fn poll_close( self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		Pin::new( &mut &self.sender ).poll_close( cx )
+	}
+}
+
+
+
+
+
+impl AsyncWrite for WsIo
+{
+	fn poll_write( mut self: Pin<&mut Self>, cx: &mut Context, buf: &[u8] ) -> Poll<Result<usize, io::Error>>
+	{
+		// The WsErr is preserved inside the io::Error, see: `From<WsErr> for io::Error`.
+		//
+		ready!( self.as_mut().poll_ready( cx ) )?;
+
+		let n = buf.len();
+
+		self.start_send( WsMessage::Binary( buf.into() ) )?;
+
+		Poll::Ready( Ok(n) )
 	}
 
 
 
-	fn poll_flush( self: Pin<&mut Self>, _: &mut Context ) -> Poll<Result<(), Self::Error>>
+	fn poll_flush( self: Pin<&mut Self>, _cx: &mut Context ) -> Poll<Result<(), io::Error>>
+	{
+		Poll::Ready( Ok(()) )
+	}
+
+
+	fn poll_close( self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Result<(), io::Error>>
 	{
-		trace!( "Sink<WsMessage> for WsIo: poll_flush" );
+		let _ = ready!( < Self as Sink<WsMessage> >::poll_close( self, cx ) );
 
+		// WsIo poll_close is infallible
+		//
 		Poll::Ready( Ok(()) )
 	}
+}
 
 
 
-	// TODO: find a simpler implementation, notably this needs to spawn a future.
-	//       this can be done by creating a custom future. If we are going to implement
-	//       events with pharos, that's probably a good time to re-evaluate this.
+// State shared between the onmessage callback and the Stream implementation.
+//
+#[ derive( Default ) ]
+//
+struct Incoming
+{
+	// The queue of received messages
 	//
-	fn poll_close( mut self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Result<(), Self::Error>>
-	{
-		trace!( "Sink<WsMessage> for WsIo: poll_close" );
+	queue: VecDeque< Slot >,
 
-		let state = self.ready_state();
+	// Last waker of task that wants to read incoming messages
+	// to be woken up on a new message
+	//
+	waker: Option<Waker>,
 
+	// A WsMessageStream is alive, so the next message has to wait.
+	//
+	streaming: bool,
 
-		if state == WsState::Connecting
-		|| state == WsState::Open
-		{
-			// Can't fail
-			//
-			self.ws.close().unwrap_throw();
+	// The close or error event happened, so nothing more will be queued.
+	//
+	ended: bool,
+}
 
-			// notify observers
-			//
-			rt::block_on( self.pharos.borrow_mut().notify( &WsEvent::Closing ) );
-		}
 
 
-		match state
-		{
-			WsState::Closed =>
-			{
-				trace!( "WebSocket connection closed!" );
-				Poll::Ready( Ok(()) )
-			}
+// A message in the queue.
+//
+enum Slot
+{
+	Ready( Result<WsMessage, WsErr> ),
 
-			_ =>
-			{
-				// Create a future that will resolve with the close event, so we can
-				// poll it.
-				//
-				if self.closer.is_none()
-				{
-					let rx = self.pharos.borrow_mut().observe_unbounded();
-					self.closer = Some( NextEvent::new( rx, WsEventType::CLOSE ) );
-				}
+	// A binary message that is read from a Blob in the background, see WsConfig::stream_threshold.
+	//
+	Reading( Rc<RefCell< Reading >> ),
 
+	// A binary message over WsConfig::stream_threshold.
+	//
+	Large( Blob ),
+}
 
-				let _ = ready!( Pin::new( &mut self.closer.as_mut().unwrap() ).poll(cx) );
 
-				Poll::Ready( Ok(()) )
-			}
-		}
-	}
-}
 
+enum Reading
+{
+	Pending,
+	Done( Result<WsMessage, WsErr> ),
 
+	// An interceptor dropped it after reading.
+	//
+	Dropped,
+}
 
 
 
-impl AsyncWrite for WsIo
+impl Incoming
 {
-	fn poll_write( mut self: Pin<&mut Self>, cx: &mut Context, buf: &[u8] ) -> Poll<Result<usize, io::Error>>
+	// Take the next item from the queue. Returns Pending if there is one but it isn't ready yet,
+	// or when a WsMessageStream is still alive.
+	//
+	// Slots that were dropped after reading were counted as received, so they are counted as delivered
+	// here to keep the marks of the instrument paired up.
+	//
+	fn pop( this: &Rc<RefCell< Self >>, instrument: &Instrument ) -> Poll<Option< Result<WsIncoming, WsErr> >>
 	{
-		let res = ready!( self.as_mut().poll_ready( cx ) );
+		let mut inc = this.borrow_mut();
+
+		if inc.streaming { return Poll::Pending }
 
-		match res
+		while let Some( slot ) = inc.queue.pop_front()
 		{
-			Ok(_) =>
+			match slot
 			{
-				let n = buf.len();
+				Slot::Ready( item ) => return Poll::Ready( Some( item.map( WsIncoming::Message ) ) ),
 
-				match self.start_send( WsMessage::Binary( buf.into() ) )
+				Slot::Reading( r ) =>
 				{
-					Ok (_) => { return Poll::Ready( Ok(n) ); }
-					Err(e) =>
+					let state = std::mem::replace( &mut *r.borrow_mut(), Reading::Pending );
+
+					match state
 					{
-						match e.kind()
-						{
-							WsErrKind::ConnectionNotOpen =>
-							{
-								return Poll::Ready( Err( io::Error::from( io::ErrorKind::NotConnected )))
-							}
+						Reading::Done( item ) => return Poll::Ready( Some( item.map( WsIncoming::Message ) ) ),
+						Reading::Dropped      => { instrument.delivered(); continue }
 
-							// This shouldn't happen, so panic for early detection.
-							//
-							_ => unreachable!()
+						Reading::Pending =>
+						{
+							inc.queue.push_front( Slot::Reading( r ) );
+							return Poll::Pending;
 						}
 					}
 				}
-			}
 
-			Err(e) => match e.kind()
-			{
-				WsErrKind::ConnectionNotOpen =>
+				Slot::Large( blob ) =>
 				{
-					return Poll::Ready( Err( io::Error::from( io::ErrorKind::NotConnected )))
-				}
+					inc.streaming = true;
 
-				_ => unreachable!()
-			}
-		}
-	}
+					let weak = Rc::downgrade( this );
 
+					let stream = WsMessageStream::new( blob, move ||
+					{
+						let inc = match weak.upgrade()
+						{
+							Some( inc ) => inc,
+							None        => return,
+						};
 
+						let waker =
+						{
+							let mut inc = inc.borrow_mut();
 
-	fn poll_flush( self: Pin<&mut Self>, _cx: &mut Context ) -> Poll<Result<(), io::Error>>
-	{
-		Poll::Ready( Ok(()) )
-	}
+							inc.streaming = false;
+							inc.waker.take()
+						};
 
+						if let Some( w ) = waker { w.wake() }
+					});
 
-	fn poll_close( self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Result<(), io::Error>>
-	{
-		let _ = ready!( < Self as Sink<WsMessage> >::poll_close( self, cx ) );
+					return Poll::Ready( Some( Ok( WsIncoming::Stream( stream ) ) ) );
+				}
+			}
+		}
 
-		// WsIo poll_close is infallible
-		//
-		Poll::Ready( Ok(()) )
+		Poll::Ready( None )
 	}
 }
 
@@ -429,12 +989,19 @@ impl AsyncRead for WsIo
 					{
 						// We have a message
 						//
-						Poll::Ready( Some(chunk) ) =>
+						Poll::Ready( Some( Ok(chunk) ) ) =>
 						{
 							self.state = ReadState::Ready { chunk: chunk.into(), chunk_start: 0 };
 							continue;
 						}
 
+						// The message was rejected
+						//
+						Poll::Ready( Some( Err(e) ) ) =>
+						{
+							return Poll::Ready( Err( e.into() ) );
+						}
+
 						// The stream has ended
 						//
 						Poll::Ready( None ) =>
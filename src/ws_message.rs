@@ -1,9 +1,10 @@
-use crate::import::*;
+use crate::{ import::*, WsErr, WsErrKind };
 
 
 /// Represents a WebSocket Message, after converting from JavaScript type.
 //
 #[ derive( Debug, Clone, PartialEq, Eq, Hash ) ]
+#[ cfg_attr( feature = "serde", derive( serde::Serialize, serde::Deserialize ) ) ]
 //
 pub enum WsMessage
 {
@@ -22,56 +23,181 @@ impl From< MessageEvent > for WsMessage
 {
 	fn from( evt: MessageEvent ) -> Self
 	{
-		let data = evt.data();
+		// We have set the binary mode to array buffer, so normally we only get strings and
+		// ArrayBuffers. That is as long as this is used within the context of the WsStream constructor.
+		//
+		WsMessage::try_from( evt.data() )
+
+			.map_err( |e| error!( "JsWebSocket received data that can't be converted: {}", e ) )
+			.expect_throw( "From< MessageEvent > for WsMessage" )
+	}
+}
 
-		if data.is_instance_of::< ArrayBuffer >()
-		{
-			trace!( "JsWebSocket received binary message" );
 
-			let buf: &ArrayBuffer = data.unchecked_ref();
 
-			let     buffy = Uint8Array::new( buf );
-			let mut v     = vec![ 0; buffy.length() as usize ];
+/// Convert data coming from JavaScript. Strings become [WsMessage::Text]. `ArrayBuffer`s and views on them
+/// (typed arrays like `Uint8Array` and `DataView`) become [WsMessage::Binary]. For views, only the bytes in
+/// view are copied, respecting `byteOffset` and `byteLength`.
+///
+/// Anything else, including `Blob`, returns [WsErrKind::UnsupportedData].
+//
+impl TryFrom< JsValue > for WsMessage
+{
+	type Error = WsErr;
 
-			buffy.copy_to( &mut v ); // FIXME: get rid of this copy
+	fn try_from( data: JsValue ) -> Result< Self, Self::Error >
+	{
+		if let Some( buf ) = data.dyn_ref::< ArrayBuffer >()
+		{
+			trace!( "WsMessage: converting binary data" );
 
-			WsMessage::Binary( v )
+			return Ok( WsMessage::from_array_buffer( buf ) );
 		}
 
 
-		else if data.is_string()
+		if ArrayBuffer::is_view( &data )
 		{
-			// should never fail
+			trace!( "WsMessage: converting a view on binary data" );
+
+			// All views (typed arrays and DataView) have these properties.
 			//
-			let text = data.as_string().expect_throw( "From< &JsMsgEvent > for WsMessage: data.as_string()" );
+			let view  = data.unchecked_ref::< Uint8Array >();
+			let bytes = Uint8Array::new_with_byte_offset_and_length( &view.buffer(), view.byte_offset(), view.byte_length() );
 
-			WsMessage::Text( text )
+			return Ok( WsMessage::from_uint8array( &bytes ) );
 		}
 
 
-		// We have set the binary mode to array buffer, so normally this shouldn't happen. That is as long
-		// as this is used within the context of the WsStream constructor.
-		//
-		// FIXME: find a way to convert a blob...
-		//
-		else if data.is_instance_of::< Blob >()
+		if let Some( text ) = data.as_string()
 		{
-			error!( "JsWebSocket received a blob...unimplemented!" );
-
-			unreachable!();
+			return Ok( WsMessage::Text( text ) );
 		}
 
 
-		else
+		let kind = match data.is_instance_of::< Blob >()
+		{
+			true  => "Blob".to_string()     ,
+			false => format!( "{:?}", data ),
+		};
+
+		Err( WsErrKind::UnsupportedData( kind ).into() )
+	}
+}
+
+
+
+impl WsMessage
+{
+	/// Convert to a JavaScript value, for passing the message on to other JavaScript API's. Text becomes a JavaScript
+	/// string and binary data a new `Uint8Array`.
+	//
+	pub fn into_js( self ) -> JsValue
+	{
+		match self
 		{
-			error!( "JsWebSocket received data that is not string, nor ArrayBuffer, nor Blob, bailing..." );
+			WsMessage::Text  ( s ) => JsValue::from_str( &s ),
+			WsMessage::Binary( _ ) => self.to_uint8array().into(),
+		}
+	}
 
-			unreachable!();
+
+	/// Copy the data into a new `Uint8Array`, for passing it to JavaScript API's. Text is encoded as UTF-8.
+	//
+	pub fn to_uint8array( &self ) -> Uint8Array
+	{
+		match self
+		{
+			WsMessage::Text  ( s ) => Uint8Array::from( s.as_bytes() ),
+			WsMessage::Binary( v ) => Uint8Array::from( v.as_slice() ),
 		}
 	}
+
+
+	/// Copy the data into a new `ArrayBuffer`, for passing it to JavaScript API's. Text is encoded as UTF-8.
+	//
+	pub fn to_array_buffer( &self ) -> ArrayBuffer
+	{
+		self.to_uint8array().buffer()
+	}
+
+
+	/// Copy the content of an `ArrayBuffer` into a [WsMessage::Binary].
+	//
+	pub fn from_array_buffer( buf: &ArrayBuffer ) -> Self
+	{
+		WsMessage::Binary( Uint8Array::new( buf ).to_vec() )
+	}
+
+
+	/// Copy the bytes in view of a `Uint8Array` into a [WsMessage::Binary]. Only the part of the buffer
+	/// between `byteOffset` and `byteOffset + byteLength` is copied.
+	//
+	pub fn from_uint8array( bytes: &Uint8Array ) -> Self
+	{
+		WsMessage::Binary( bytes.to_vec() )
+	}
+}
+
+
+
+/// The size of a message in bytes, for text the length of the UTF-8 string.
+//
+pub(crate) fn byte_len( msg: &WsMessage ) -> usize
+{
+	match msg
+	{
+		WsMessage::Binary( d ) => d.len(),
+		WsMessage::Text  ( s ) => s.len(),
+	}
+}
+
+
+
+/// The size of the data of a MessageEvent without copying it. This is the byte length for ArrayBuffers
+/// and Blobs, and the length in UTF-16 code units for strings.
+//
+pub(crate) fn message_size( data: &JsValue ) -> usize
+{
+	if      let Some( buf ) = data.dyn_ref::< ArrayBuffer >() { buf.byte_length() as usize                           }
+	else if let Some( b   ) = data.dyn_ref::< Blob        >() { b.size() as usize                                    }
+	else if data.is_string()                                   { data.unchecked_ref::< JsString >().length() as usize }
+	else                                                       { 0                                                    }
+}
+
+
+
+/// Copies the string. To send text without allocating, see [WsSender::send_text_ref](crate::WsSender::send_text_ref).
+//
+impl From<&str> for WsMessage
+{
+	fn from( text: &str ) -> Self
+	{
+		WsMessage::Text( text.to_string() )
+	}
 }
 
 
+
+impl From<String> for WsMessage
+{
+	fn from( text: String ) -> Self
+	{
+		WsMessage::Text( text )
+	}
+}
+
+
+
+impl From<Vec<u8>> for WsMessage
+{
+	fn from( data: Vec<u8> ) -> Self
+	{
+		WsMessage::Binary( data )
+	}
+}
+
+
+
 impl From<WsMessage> for Vec<u8>
 {
 	fn from( msg: WsMessage ) -> Self
@@ -0,0 +1,271 @@
+use
+{
+	crate  :: { import::*, WsErr, WsErrKind, WsMessage } ,
+	js_sys :: { Promise                     } ,
+};
+
+
+/// The default for [WsMessageStream::chunk_size]: 1 MiB.
+//
+pub const DEFAULT_CHUNK_SIZE: usize = 1024 * 1024;
+
+
+
+type Handler = Closure< dyn FnMut( JsValue ) + 'static >;
+
+
+#[ derive( Default ) ]
+//
+struct ReadState
+{
+	result  : Option< Result<ArrayBuffer, WsErr> >,
+	waker   : Option< Waker >                     ,
+
+	// The promise calls one of these even when the BlobRead was dropped in the mean time, so they live here
+	// until then, rather than in BlobRead. The one that runs drops both.
+	//
+	handlers: Option<( Handler, Handler )>        ,
+}
+
+
+
+// A future for the data of a Blob, using `Blob.arrayBuffer()`.
+//
+pub(crate) struct BlobRead
+{
+	state: Rc<RefCell< ReadState >>,
+}
+
+
+
+pub(crate) fn read_blob( blob: &Blob ) -> BlobRead
+{
+	let state  = Rc::new( RefCell::new( ReadState::default() ) );
+	let state2 = state.clone();
+	let state3 = state.clone();
+
+	let finish = |state: &Rc<RefCell< ReadState >>, result|
+	{
+		let ( waker, handlers ) =
+		{
+			let mut state = state.borrow_mut();
+
+			state.result = Some( result );
+			( state.waker.take(), state.handlers.take() )
+		};
+
+		// wasm-bindgen only frees the running closure once it returns.
+		//
+		drop( handlers );
+
+		if let Some( w ) = waker { w.wake() }
+	};
+
+	let on_ok = Closure::wrap( Box::new( move |buf: JsValue|
+	{
+		finish( &state2, Ok( buf.unchecked_into::<ArrayBuffer>() ) );
+
+	}) as Box< dyn FnMut( JsValue ) > );
+
+	let on_err = Closure::wrap( Box::new( move |err: JsValue|
+	{
+		finish( &state3, Err( WsErr::from_js( WsErrKind::BlobRead( format!( "{:?}", err ) ), err ) ) );
+
+	}) as Box< dyn FnMut( JsValue ) > );
+
+
+	let promise: Promise = blob.array_buffer();
+	let _ = promise.then2( &on_ok, &on_err );
+
+	state.borrow_mut().handlers = Some(( on_ok, on_err ));
+
+	BlobRead { state }
+}
+
+
+
+impl Future for BlobRead
+{
+	type Output = Result<ArrayBuffer, WsErr>;
+
+	fn poll( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll<Self::Output>
+	{
+		let mut state = self.state.borrow_mut();
+
+		if let Some( result ) = state.result.take() { return Poll::Ready( result ) }
+
+		state.waker = Some( cx.waker().clone() );
+
+		Poll::Pending
+	}
+}
+
+
+
+/// A big binary message that is read in chunks, so it never has to be in wasm memory as a whole.
+/// See [WsConfig::stream_threshold](crate::WsConfig::stream_threshold).
+///
+/// Yields the data in chunks of [WsMessageStream::chunk_size] bytes, the last one may be smaller. If reading
+/// the data fails, the error is yielded and the stream ends.
+///
+/// No other message is delivered by [WsIo](crate::WsIo) until this stream has ended or was dropped.
+//
+pub struct WsMessageStream
+{
+	blob   : Blob                       ,
+	size   : u64                        ,
+	offset : u64                        ,
+	chunk  : u64                        ,
+	reading: Option< BlobRead >         ,
+	release: Option< Box< dyn FnOnce() > >,
+}
+
+
+
+impl WsMessageStream
+{
+	pub(crate) fn new( blob: Blob, release: impl FnOnce() + 'static ) -> Self
+	{
+		let size = blob.size() as u64;
+
+		Self
+		{
+			blob                                 ,
+			size                                 ,
+			offset : 0                           ,
+			chunk  : DEFAULT_CHUNK_SIZE as u64   ,
+			reading: None                        ,
+			release: Some( Box::new( release ) ) ,
+		}
+	}
+
+
+	/// The size of the whole message in bytes.
+	//
+	pub fn size( &self ) -> u64
+	{
+		self.size
+	}
+
+
+	/// The number of bytes that have not been yielded yet.
+	//
+	pub fn remaining( &self ) -> u64
+	{
+		self.size - self.offset
+	}
+
+
+	/// Set the size of the chunks. Only takes effect for chunks that haven't been requested yet. A size of 0
+	/// is treated as 1. Defaults to [DEFAULT_CHUNK_SIZE].
+	//
+	pub fn chunk_size( mut self, size: usize ) -> Self
+	{
+		self.chunk = cmp::max( size, 1 ) as u64;
+		self
+	}
+
+
+	// Let WsIo deliver the next message.
+	//
+	fn release( &mut self )
+	{
+		if let Some( release ) = self.release.take() { release() }
+	}
+}
+
+
+
+impl Stream for WsMessageStream
+{
+	type Item = Result<Vec<u8>, WsErr>;
+
+
+	fn poll_next( mut self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Option< Self::Item >>
+	{
+		if self.reading.is_none()
+		{
+			if self.offset >= self.size
+			{
+				self.release();
+				return Poll::Ready( None );
+			}
+
+			let end = cmp::min( self.offset + self.chunk, self.size );
+
+			// Only fails for invalid arguments.
+			//
+			let slice = self.blob.slice_with_f64_and_f64( self.offset as f64, end as f64 )
+
+				.expect_throw( "slice Blob" );
+
+			self.reading = Some( read_blob( &slice ) );
+		}
+
+
+		let result = ready!( Pin::new( self.reading.as_mut().unwrap_throw() ).poll( cx ) );
+
+		self.reading = None;
+
+		match result
+		{
+			Ok( buf ) =>
+			{
+				self.offset += u64::from( buf.byte_length() );
+
+				Poll::Ready( Some( Ok( Uint8Array::new( &buf ).to_vec() ) ) )
+			}
+
+			Err( e ) =>
+			{
+				// Don't try to read the rest.
+				//
+				self.offset = self.size;
+				Poll::Ready( Some( Err( e ) ) )
+			}
+		}
+	}
+}
+
+
+
+impl Drop for WsMessageStream
+{
+	fn drop( &mut self )
+	{
+		self.release();
+	}
+}
+
+
+
+impl fmt::Debug for WsMessageStream
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		f.debug_struct( "WsMessageStream" )
+
+			.field( "size"  , &self.size   )
+			.field( "offset", &self.offset )
+			.field( "chunk" , &self.chunk  )
+			.finish()
+	}
+}
+
+
+
+/// An incoming message as returned by [WsIo::next_incoming](crate::WsIo::next_incoming): either a whole message, or a
+/// binary message over [WsConfig::stream_threshold](crate::WsConfig::stream_threshold) that you read in chunks.
+//
+#[ derive( Debug ) ]
+//
+pub enum WsIncoming
+{
+	/// A message that was received in one piece.
+	//
+	Message( WsMessage ),
+
+	/// A big binary message.
+	//
+	Stream( WsMessageStream ),
+}
@@ -0,0 +1,421 @@
+use
+{
+	crate   :: { import::*, WsConfig, WsErr, WsErrKind, WsIo, WsMessage, WsSender, WsState, WsStream } ,
+	futures :: { future::{ join_all, LocalBoxFuture }                                                 } ,
+};
+
+
+/// How a [WsPool] chooses the connection for the next message.
+//
+#[ derive( Debug, Clone, Copy, PartialEq, Eq, Default ) ]
+//
+pub enum Balance
+{
+	/// Take turns.
+	//
+	#[ default ]
+	//
+	RoundRobin,
+
+	/// Use the connection with the least data waiting in the browser, see [WsStream::buffered_amount].
+	/// Ties go to the first connection.
+	//
+	LeastBuffered,
+}
+
+
+
+type Connecting = LocalBoxFuture< 'static, Result<(WsStream, WsIo), WsErr> >;
+
+
+// Either a connection, one that is being replaced or nothing. There are only a few slots,
+// so we don't box the connection.
+//
+#[ allow( clippy::large_enum_variant ) ]
+//
+enum Slot
+{
+	Open     ( WsStream, WsIo, WsSender ),
+	Replacing( Connecting               ),
+	Empty,
+}
+
+
+
+impl Slot
+{
+	// The sender is kept with the connection, so sending doesn't create one every time.
+	//
+	fn open( ws: WsStream, wsio: WsIo ) -> Self
+	{
+		let tx = wsio.sender();
+
+		Slot::Open( ws, wsio, tx )
+	}
+}
+
+
+
+/// Several connections to the same url, used as one, to send more than a single browser WebSocket can.
+///
+/// **Messages are NOT ordered across connections.** Two messages sent one after the other may arrive in a different
+/// order, both at the server and when they come back. Only messages on the same connection keep their order. Only use
+/// this for protocols where each message stands on its own.
+///
+/// The `Sink<WsMessage>` spreads messages over the open connections as configured with [WsPool::balance].
+/// The `Stream` merges the messages of all connections, tagged with the index of the connection they
+/// came in on. Indexes go from 0 to `size - 1` and a replacement connection takes the index of the one it replaces.
+///
+/// When a connection closes, it leaves the pool and the others carry on. With [WsPool::replace], a new connection
+/// is made in its place. This happens while the pool is polled, either as a stream or as a sink. The stream ends
+/// once all connections are gone and sending then fails with [WsErrKind::ConnectionNotOpen].
+//
+pub struct WsPool
+{
+	url    : String    ,
+	config : WsConfig  ,
+	slots  : Vec<Slot> ,
+	balance: Balance   ,
+	replace: bool      ,
+	closing: bool      ,
+
+	// Where round robin sends next and where polling for incoming messages starts, so one busy
+	// connection can't starve the others.
+	//
+	next_send: usize,
+	next_recv: usize,
+}
+
+
+
+impl WsPool
+{
+	/// Open `size` connections to `url` at the same time. This fails only when none of them could be opened, with the
+	/// error of the last one. The connections that failed leave an empty slot, which is filled when [WsPool::replace]
+	/// is enabled.
+	//
+	pub async fn connect( url: impl AsRef<str>, size: usize, config: WsConfig ) -> Result<Self, WsErr>
+	{
+		let url = url.as_ref().to_string();
+
+		let results = join_all( (0..size).map( |_| WsStream::connect_with_config( &url, config.clone() ) ) ).await;
+
+		let mut last_err = WsErr::from( WsErrKind::ConnectionNotOpen );
+
+		let slots: Vec<Slot> = results.into_iter().map( |res| match res
+		{
+			Ok (( ws, wsio )) => Slot::open( ws, wsio ),
+			Err( e          ) =>
+			{
+				warn!( "WsPool: connection failed: {}", e );
+
+				last_err = e;
+				Slot::Empty
+			}
+
+		}).collect();
+
+		if !slots.iter().any( |s| matches!( s, Slot::Open(..) ) ) { return Err( last_err ) }
+
+		Ok( Self
+		{
+			url                ,
+			config             ,
+			slots              ,
+			balance  : Balance::default(),
+			replace  : false   ,
+			closing  : false   ,
+			next_send: 0       ,
+			next_recv: 0       ,
+		})
+	}
+
+
+	/// How to spread outgoing messages. Defaults to [Balance::RoundRobin].
+	//
+	pub fn balance( mut self, balance: Balance ) -> Self
+	{
+		self.balance = balance;
+		self
+	}
+
+
+	/// Whether to open a new connection when one closes, and in the slots of connections that failed in
+	/// [WsPool::connect]. Defaults to `false`. A replacement that fails to connect leaves the slot empty.
+	//
+	pub fn replace( mut self, replace: bool ) -> Self
+	{
+		self.replace = replace;
+
+		if replace { (0..self.slots.len()).for_each( |i| if let Slot::Empty = self.slots[i] { self.start_replace( i ) } ) }
+
+		self
+	}
+
+
+	/// The number of open connections.
+	//
+	pub fn len( &self ) -> usize
+	{
+		self.slots.iter().filter( |s| matches!( s, Slot::Open(..) ) ).count()
+	}
+
+
+	/// Whether there are no open connections left.
+	//
+	pub fn is_empty( &self ) -> bool
+	{
+		self.len() == 0
+	}
+
+
+	/// The number of connections the pool was created with.
+	//
+	pub fn size( &self ) -> usize
+	{
+		self.slots.len()
+	}
+
+
+	/// The connection at `index`, if it's open.
+	//
+	pub fn stream( &self, index: usize ) -> Option<&WsStream>
+	{
+		match self.slots.get( index )
+		{
+			Some( Slot::Open( ws, .. ) ) => Some( ws ),
+			_                           => None      ,
+		}
+	}
+
+
+	fn start_replace( &mut self, index: usize )
+	{
+		if !self.replace || self.closing
+		{
+			self.slots[ index ] = Slot::Empty;
+			return;
+		}
+
+		debug!( "WsPool: replacing connection {}", index );
+
+		let url    = self.url   .clone();
+		let config = self.config.clone();
+
+		self.slots[ index ] = Slot::Replacing( Box::pin( async move { WsStream::connect_with_config( url, config ).await } ) );
+	}
+
+
+	// Poll the replacements and let closed connections go.
+	//
+	fn poll_slots( &mut self, cx: &mut Context )
+	{
+		// Connections that closed leave the pool, once the messages they received have been taken.
+		// This comes first, so a new replacement is polled right away.
+		//
+		for i in 0..self.slots.len()
+		{
+			if let Slot::Open( ws, wsio, _ ) = &self.slots[ i ]
+			{
+				if ws.ready_state() == WsState::Closed && wsio.pending_messages() == 0 { self.start_replace( i ) }
+			}
+		}
+
+		for slot in &mut self.slots
+		{
+			if let Slot::Replacing( fut ) = slot
+			{
+				if let Poll::Ready( res ) = fut.as_mut().poll( cx )
+				{
+					*slot = match res
+					{
+						Ok (( ws, wsio )) => Slot::open( ws, wsio ),
+						Err( e          ) => { warn!( "WsPool: replacement failed: {}", e ); Slot::Empty }
+					};
+				}
+			}
+		}
+	}
+
+
+	fn is_replacing( &self ) -> bool
+	{
+		self.slots.iter().any( |s| matches!( s, Slot::Replacing(_) ) )
+	}
+
+
+	// The open connections in the order we should try to send on them.
+	//
+	fn candidates( &self ) -> Vec<usize>
+	{
+		let open = |i: &usize| matches!( &self.slots[ *i ], Slot::Open( ws, .. ) if ws.ready_state() == WsState::Open );
+		let size = self.slots.len();
+
+		match self.balance
+		{
+			Balance::RoundRobin => ( 0..size ).map( |i| ( self.next_send + i ) % size ).filter( open ).collect(),
+
+			Balance::LeastBuffered =>
+			{
+				let mut list: Vec<usize> = ( 0..size ).filter( open ).collect();
+
+				list.sort_by_key( |i| match &self.slots[ *i ]
+				{
+					Slot::Open( ws, .. ) => ws.buffered_amount(),
+					_                   => u32::MAX            ,
+				});
+
+				list
+			}
+		}
+	}
+}
+
+
+
+impl fmt::Debug for WsPool
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		write!( f, "WsPool for {}: {} of {} connections open", self.url, self.len(), self.size() )
+	}
+}
+
+
+
+impl Stream for WsPool
+{
+	type Item = ( usize, Result<WsMessage, WsErr> );
+
+
+	fn poll_next( mut self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Option< Self::Item >>
+	{
+		let this = &mut *self;
+		let size = this.slots.len();
+
+		// A replacement that connects has to be polled for messages as well, so go around until nothing changes.
+		//
+		loop
+		{
+			let mut ended = false;
+
+			for n in 0..size
+			{
+				let i = ( this.next_recv + n ) % size;
+
+				if let Slot::Open( _, wsio, _ ) = &mut this.slots[ i ]
+				{
+					match Pin::new( wsio ).poll_next( cx )
+					{
+						Poll::Ready( Some( item ) ) =>
+						{
+							this.next_recv = ( i + 1 ) % size;
+
+							return Poll::Ready( Some( ( i, item ) ) );
+						}
+
+						// The connection is gone.
+						//
+						Poll::Ready( None ) => { this.start_replace( i ); ended = true; }
+
+						Poll::Pending => {}
+					}
+				}
+			}
+
+			let before = this.len();
+
+			this.poll_slots( cx );
+
+			if this.slots.iter().all( |s| matches!( s, Slot::Empty ) ) { return Poll::Ready( None ) }
+
+			if !ended && this.len() <= before { return Poll::Pending }
+		}
+	}
+}
+
+
+
+impl Sink<WsMessage> for WsPool
+{
+	type Error = WsErr;
+
+
+	fn poll_ready( mut self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		self.poll_slots( cx );
+
+		if !self.candidates().is_empty() { return Poll::Ready( Ok(()) ) }
+
+		match self.is_replacing()
+		{
+			// Only replacements are left, they wake us up when they are done.
+			//
+			true  => Poll::Pending,
+			false => Poll::Ready( Err( WsErrKind::ConnectionNotOpen.into() ) ),
+		}
+	}
+
+
+	/// When a connection turns out to be closed, the message goes to the next one.
+	//
+	fn start_send( mut self: Pin<&mut Self>, item: WsMessage ) -> Result<(), Self::Error>
+	{
+		let size = self.slots.len();
+
+		for i in self.candidates()
+		{
+			// WsSender only fails with ConnectionNotOpen when the connection isn't open, and interceptors take the
+			// message before that, so look at the state first rather than handing over a copy of the message.
+			//
+			let res = match &self.slots[ i ]
+			{
+				Slot::Open( ws, _, tx ) if ws.ready_state() == WsState::Open => Pin::new( &mut &*tx ).start_send( item ),
+				Slot::Open( .. )                                             => { self.start_replace( i ); continue }
+				_                                                            => continue,
+			};
+
+			if let Err( ref e ) = res
+			{
+				if e.kind() == &WsErrKind::ConnectionNotOpen { self.start_replace( i ) }
+			}
+
+			self.next_send = ( i + 1 ) % size;
+			return res;
+		}
+
+		Err( WsErrKind::ConnectionNotOpen.into() )
+	}
+
+
+	fn poll_flush( self: Pin<&mut Self>, _: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		Poll::Ready( Ok(()) )
+	}
+
+
+	/// Closes all connections and stops replacing them.
+	//
+	fn poll_close( mut self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		self.closing = true;
+
+		let mut done = true;
+
+		for slot in &mut self.slots
+		{
+			match slot
+			{
+				Slot::Open( _, wsio, _ ) => if Sink::poll_close( Pin::new( wsio ), cx ).is_pending() { done = false },
+				Slot::Replacing(_)    => *slot = Slot::Empty,
+				Slot::Empty           => {}
+			}
+		}
+
+		match done
+		{
+			true  => Poll::Ready( Ok(()) ),
+			false => Poll::Pending        ,
+		}
+	}
+}
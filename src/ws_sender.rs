@@ -0,0 +1,592 @@
+use
+{
+	crate   :: { import::*, ws_interceptor::Interceptors, timer::SharedTimer, WsErr, WsErrKind, WsMessage, WsState } ,
+	crate   :: { ws_message::byte_len                                                                             } ,
+	crate   :: { WsEvent, NextEvent, WsEventType, ws_stream::READY_POLL                                           } ,
+	futures :: { future::{ poll_fn, LocalBoxFuture }, task::{ waker, ArcWake }                                    } ,
+	std     :: { cell::Cell, time::Duration, sync::{ Arc, Mutex }                                                 } ,
+};
+
+
+/// A handle for sending [WsMessage] over a connection.
+///
+/// Sending on a browser WebSocket is just a method call on the JavaScript object, so this does not
+/// require exclusive access. `Sink<WsMessage>` is implemented both for `WsSender` and for `&WsSender`,
+/// much like `Read` and `Write` are implemented for `&TcpStream` in std. This means you can put a sender in an
+/// `Rc` and send from several places without needing a `RefCell`.
+///
+/// Obtained with [WsIo::sender](crate::WsIo::sender). Cloning a sender is cheap.
+///
+/// The sink is ready once the connection is open and, when [WsConfig::send_high_water](crate::WsConfig::send_high_water)
+/// is set, the browser has no more than that many bytes queued.
+///
+/// Note that dropping [WsIo](crate::WsIo) will close the connection, after which sending will return
+/// [WsErrKind::ConnectionNotOpen].
+//
+pub struct WsSender
+{
+	ws        : Rc< WebSocket >                 ,
+	pharos    : Rc<RefCell< Pharos<WsEvent> >>  ,
+	max_size  : Option<usize>                   ,
+	high_water: Option<u32>                     ,
+	icpt      : Interceptors                    ,
+	timer     : SharedTimer                     ,
+
+	// The sleep after which Sink::poll_ready checks bufferedAmount again, for all the tasks that poll it.
+	//
+	recheck: RefCell< Recheck >,
+
+	// How many messages expired before they could be sent, see WsSender::send_with_ttl.
+	//
+	expired: Rc<Cell< u64 >>,
+
+	// This allows us to store a future to poll when
+	// Sink::poll_close is called
+	//
+	closer: RefCell< Option< NextEvent > >,
+}
+
+
+
+impl WsSender
+{
+	pub(crate) fn new
+	(
+		ws        : Rc<WebSocket>                 ,
+		pharos    : Rc<RefCell< Pharos<WsEvent> >>,
+		max_size  : Option<usize>                 ,
+		high_water: Option<u32>                   ,
+		icpt      : Interceptors                  ,
+		timer     : SharedTimer                   ,
+	)
+		-> Self
+	{
+		Self
+		{
+			ws                           ,
+			pharos                       ,
+			max_size                     ,
+			high_water                   ,
+			icpt                         ,
+			timer                        ,
+			recheck: RefCell::default()  ,
+			expired: Rc::default()       ,
+			closer : RefCell::new( None ),
+		}
+	}
+
+
+	/// Verify the [WsState] of the connection.
+	//
+	pub fn ready_state( &self ) -> WsState
+	{
+		self.ws.ready_state().try_into().map_err( |e| error!( "{}", e ) )
+
+			// This can't throw unless the browser gives us an invalid ready state
+			//
+			.expect_throw( "Convert ready state from browser API" )
+	}
+
+
+
+	// Web API does not really seem to let us check for readiness, other than the connection state and bufferedAmount.
+	// There is no event for the buffer draining, so while it's above the high water mark, we check again every
+	// READY_POLL. Senders are only created once the connection is open, but if the state were still connecting,
+	// we check again the same way.
+	//
+	fn poll_ready_shared( &self, cx: &mut Context, recheck: &mut Recheck ) -> Poll<Result<(), WsErr>>
+	{
+		trace!( "Sink<WsMessage> for WsSender: poll_ready" );
+
+		loop
+		{
+			let state   = self.ready_state();
+			let waiting = state == WsState::Connecting
+			           || ( state == WsState::Open && self.over_high_water() );
+
+			if !waiting
+			{
+				return Poll::Ready( match state
+				{
+					WsState::Open => Ok(()),
+					_             => Err( WsErrKind::ConnectionNotOpen.into() ),
+				});
+			}
+
+			ready!( recheck.poll( &self.timer, cx ) );
+		}
+	}
+
+
+
+	// Whether the browser holds more than WsConfig::send_high_water bytes that haven't been sent yet.
+	//
+	fn over_high_water( &self ) -> bool
+	{
+		self.high_water.is_some_and( |hw| self.ws.buffered_amount() > hw )
+	}
+
+
+
+	/// Whether the sink would be ready right now: the connection is open and not over
+	/// [WsConfig::send_high_water](crate::WsConfig::send_high_water).
+	//
+	pub(crate) fn is_ready( &self ) -> bool
+	{
+		self.ready_state() == WsState::Open && !self.over_high_water()
+	}
+
+
+
+	fn start_send_shared( &self, item: WsMessage ) -> Result<(), WsErr>
+	{
+		trace!( "Sink<WsMessage> for WsSender: start_send" );
+
+		let item = match self.icpt.on_send( item )
+		{
+			Some( item ) => item,
+			None         => return Ok(()),
+		};
+
+		self.check_size( byte_len( &item ) )?;
+
+		self.send_raw( item )
+	}
+
+
+
+	/// Send a message right away, without checking [WsConfig::max_send_size](crate::WsConfig::max_send_size).
+	///
+	/// Interceptors still run. Returns [WsErrKind::ConnectionNotOpen] if the connection is not open.
+	//
+	pub fn send_unchecked( &self, item: WsMessage ) -> Result<(), WsErr>
+	{
+		match self.icpt.on_send( item )
+		{
+			Some( item ) => self.send_raw( item ),
+			None         => Ok(()),
+		}
+	}
+
+
+
+	/// Send text from a borrowed string. This is the way to send the same text many times, eg. protocol
+	/// constants like `"ping"`, since it doesn't allocate: the string is copied straight to the browser.
+	///
+	/// Checks [WsConfig::max_send_size](crate::WsConfig::max_send_size) and returns [WsErrKind::ConnectionNotOpen]
+	/// if the connection is not open. When interceptors are installed, they need an owned [WsMessage], so then the
+	/// text is copied into one first.
+	//
+	pub fn send_text_ref( &self, text: &str ) -> Result<(), WsErr>
+	{
+		if !self.icpt.is_empty()
+		{
+			return self.start_send_shared( WsMessage::Text( text.to_string() ) );
+		}
+
+		self.check_size( text.len() )?;
+
+		match self.ready_state()
+		{
+			WsState::Open => self.ws.send_with_str( text ).map_err( |_| WsErrKind::ConnectionNotOpen.into() ),
+			_             => Err( WsErrKind::ConnectionNotOpen.into() ),
+		}
+	}
+
+
+
+	// Verify WsConfig::max_send_size.
+	//
+	fn check_size( &self, size: usize ) -> Result<(), WsErr>
+	{
+		if let Some( limit ) = self.max_size
+		{
+			if size > limit
+			{
+				let e = WsErr::from( WsErrKind::MessageTooBig{ size, limit } );
+
+				error!( "{}", e );
+
+				return Err( e );
+			}
+		}
+
+		Ok(())
+	}
+
+
+
+	/// Send a text message. This is the same as sending a [WsMessage::Text] through the `Sink` implementation:
+	/// it waits while the connection is still connecting, checks [WsConfig::max_send_size](crate::WsConfig::max_send_size),
+	/// runs interceptors and returns [WsErrKind::ConnectionNotOpen] if the connection is closing or closed.
+	///
+	/// ```no_run
+	/// # #![ feature( async_await ) ]
+	/// use ws_stream_wasm::*;
+	///
+	/// # async fn example() -> Result<(), WsErr> {
+	/// let ( ws, _wsio ) = WsStream::connect( "ws://127.0.0.1:3012" ).await?;
+	///
+	/// ws.sender().send_text( "hello" ).await?;
+	/// # Ok(())
+	/// # }
+	/// ```
+	//
+	pub async fn send_text( &self, text: impl AsRef<str> ) -> Result<(), WsErr>
+	{
+		self.send_shared( WsMessage::Text( text.as_ref().to_string() ) ).await
+	}
+
+
+
+	/// Send a binary message. Like [WsSender::send_text], this behaves like the `Sink` implementation.
+	///
+	/// ```no_run
+	/// # #![ feature( async_await ) ]
+	/// use ws_stream_wasm::*;
+	///
+	/// # async fn example() -> Result<(), WsErr> {
+	/// let ( ws, _wsio ) = WsStream::connect( "ws://127.0.0.1:3012" ).await?;
+	/// let bytes         = vec![ 1, 2, 3 ];
+	///
+	/// ws.sender().send_binary( &bytes ).await?;
+	/// # Ok(())
+	/// # }
+	/// ```
+	//
+	pub async fn send_binary( &self, data: impl AsRef<[u8]> ) -> Result<(), WsErr>
+	{
+		self.send_shared( WsMessage::Binary( data.as_ref().to_vec() ) ).await
+	}
+
+
+
+	// What SinkExt::send does, on the same methods as the Sink implementation.
+	//
+	async fn send_shared( &self, item: WsMessage ) -> Result<(), WsErr>
+	{
+		let mut recheck = Recheck::default();
+
+		poll_fn( |cx| self.poll_ready_shared( cx, &mut recheck ) ).await?;
+
+		self.start_send_shared( item )
+	}
+
+
+
+	/// Send a message, giving up with [WsErrKind::Timeout] if the connection isn't ready to take it within `timeout`.
+	/// The time is measured with [WsConfig::timer](crate::WsConfig::timer).
+	///
+	/// The connection isn't ready while the browser has more than [WsConfig::send_high_water](crate::WsConfig::send_high_water)
+	/// bytes queued, so this can only time out when that is set. Without it, the message is sent right away or
+	/// [WsErrKind::ConnectionNotOpen] is returned.
+	///
+	/// When it times out, the message was not sent. When the connection becomes ready at the same time as the timeout
+	/// expires, the message is sent. Nothing is left pending on the sender after a timeout, so you can keep using it.
+	//
+	pub async fn send_timeout( &self, item: WsMessage, timeout: Duration ) -> Result<(), WsErr>
+	{
+		let mut sleep   = self.timer.sleep( timeout );
+		let mut recheck = Recheck::default();
+
+		poll_fn( |cx|
+		{
+			// Check the connection before the timer, so the send wins a tie.
+			//
+			if let Poll::Ready( res ) = self.poll_ready_shared( cx, &mut recheck ) { return Poll::Ready( res ) }
+
+			match sleep.as_mut().poll( cx )
+			{
+				Poll::Ready(_) => Poll::Ready( Err( WsErrKind::Timeout.into() ) ),
+				Poll::Pending  => Poll::Pending,
+			}
+
+		}).await?;
+
+		self.start_send_shared( item )
+	}
+
+
+
+	/// Send a message that is only worth sending within `ttl`, eg. a cursor position. When the connection isn't ready
+	/// to take it before `ttl` has passed, the message is silently dropped and counted in [WsSender::expired]. The time
+	/// is measured with [WsConfig::timer](crate::WsConfig::timer).
+	///
+	/// Readiness is the same as for [WsSender::send_timeout], so set [WsConfig::send_high_water](crate::WsConfig::send_high_water)
+	/// for messages to expire while the browser is still busy sending earlier ones.
+	///
+	/// The time to live only applies while we wait. Once a message is handed to the browser it can't be recalled,
+	/// even if the browser takes longer than `ttl` to put it on the network. See `bufferedAmount` on the WebSocket.
+	/// For messages waiting in a [SendBuffer](crate::SendBuffer), see [SendBuffer::push_with_ttl](crate::SendBuffer::push_with_ttl).
+	//
+	pub async fn send_with_ttl( &self, item: WsMessage, ttl: Duration ) -> Result<(), WsErr>
+	{
+		match self.send_timeout( item, ttl ).await
+		{
+			Err( ref e ) if e.kind() == &WsErrKind::Timeout =>
+			{
+				trace!( "WsSender: message expired before it could be sent" );
+
+				self.expired.set( self.expired.get() + 1 );
+				Ok(())
+			}
+
+			res => res,
+		}
+	}
+
+
+
+	/// How many messages passed to [WsSender::send_with_ttl] expired before they could be sent.
+	/// This is shared by all clones of the sender.
+	//
+	pub fn expired( &self ) -> u64
+	{
+		self.expired.get()
+	}
+
+
+
+	fn send_raw( &self, item: WsMessage ) -> Result<(), WsErr>
+	{
+		match self.ready_state()
+		{
+			WsState::Open =>
+			{
+				// The send method can return 2 errors:
+				// - unpaired surrogates in UTF (we shouldn't get those in rust strings)
+				// - connection is already closed.
+				//
+				// An InvalidStateError is turned into ConnectionNotOpen. In principle we just checked
+				// that it's open, but this guarantees correctness. Anything else is a browser bug,
+				// which we return rather than trap.
+				//
+				let res = match item
+				{
+					WsMessage::Binary( d ) => self.ws.send_with_u8_array( &d ),
+					WsMessage::Text  ( s ) => self.ws.send_with_str     ( &s ),
+				};
+
+				res.map_err( send_err )
+			},
+
+
+			// Connecting, Closing or Closed
+			//
+			_ => Err( WsErrKind::ConnectionNotOpen.into() ),
+		}
+	}
+
+
+
+	fn poll_close_shared( &self, cx: &mut Context ) -> Poll<Result<(), WsErr>>
+	{
+		trace!( "Sink<WsMessage> for WsSender: poll_close" );
+
+		let state = self.ready_state();
+
+
+		if state == WsState::Connecting
+		|| state == WsState::Open
+		{
+			// Without code and reason this can't throw, but if the browser does anyway, we return
+			// the error rather than trap.
+			//
+			if let Err( e ) = self.ws.close()
+			{
+				return Poll::Ready( Err( e.into() ) );
+			}
+
+			// notify observers
+			//
+			rt::block_on( self.pharos.borrow_mut().notify( &WsEvent::Closing ) );
+		}
+
+
+		match state
+		{
+			WsState::Closed =>
+			{
+				trace!( "WebSocket connection closed!" );
+				Poll::Ready( Ok(()) )
+			}
+
+			_ =>
+			{
+				let mut closer = self.closer.borrow_mut();
+
+				// Create a future that will resolve with the close event, so we can
+				// poll it.
+				//
+				let pharos = &self.pharos;
+				let closer = closer.get_or_insert_with( ||
+				{
+					NextEvent::new( pharos.borrow_mut().observe_unbounded(), WsEventType::CLOSE )
+				});
+
+
+				let _ = ready!( Pin::new( closer ).poll(cx) );
+
+				Poll::Ready( Ok(()) )
+			}
+		}
+	}
+}
+
+
+
+// The sleep after which poll_ready checks bufferedAmount again. A sleep only wakes the task that polled it last,
+// so when several tasks wait on the same sender, it is polled with a waker that wakes all of them.
+//
+#[ derive( Default ) ]
+//
+struct Recheck
+{
+	sleep  : Option< LocalBoxFuture<'static, ()> >,
+	waiters: Arc< Waiters >                       ,
+}
+
+
+impl Recheck
+{
+	fn poll( &mut self, timer: &SharedTimer, cx: &mut Context ) -> Poll<()>
+	{
+		self.waiters.register( cx.waker() );
+
+		let sleep = self.sleep.get_or_insert_with( || timer.sleep( READY_POLL ) );
+		let wake  = waker( self.waiters.clone() );
+
+		ready!( sleep.as_mut().poll( &mut Context::from_waker( &wake ) ) );
+
+		self.sleep = None;
+
+		Poll::Ready(())
+	}
+}
+
+
+
+#[ derive( Default ) ]
+//
+struct Waiters( Mutex< Vec<Waker> > );
+
+
+impl Waiters
+{
+	fn register( &self, w: &Waker )
+	{
+		let mut wakers = self.0.lock().expect_throw( "lock Waiters" );
+
+		if !wakers.iter().any( |x| x.will_wake( w ) ) { wakers.push( w.clone() ) }
+	}
+}
+
+
+impl ArcWake for Waiters
+{
+	fn wake_by_ref( arc_self: &Arc<Self> )
+	{
+		let wakers = std::mem::take( &mut *arc_self.0.lock().expect_throw( "lock Waiters" ) );
+
+		wakers.into_iter().for_each( Waker::wake );
+	}
+}
+
+
+
+// Errors from `WebSocket.send`.
+//
+fn send_err( e: JsValue ) -> WsErr
+{
+	match e.dyn_ref::<DomException>()
+	{
+		Some( de ) if de.code() == DomException::INVALID_STATE_ERR => WsErrKind::ConnectionNotOpen.into(),
+		_                                                          => e.into(),
+	}
+}
+
+
+
+impl Clone for WsSender
+{
+	fn clone( &self ) -> Self
+	{
+		Self
+		{
+			expired: self.expired.clone(),
+			..Self::new( self.ws.clone(), self.pharos.clone(), self.max_size, self.high_water, self.icpt.clone(), self.timer.clone() )
+		}
+	}
+}
+
+
+
+impl fmt::Debug for WsSender
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		write!( f, "WsSender for connection: {}", self.ws.url() )
+	}
+}
+
+
+
+impl Sink<WsMessage> for &WsSender
+{
+	type Error = WsErr;
+
+
+	fn poll_ready( self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		self.poll_ready_shared( cx, &mut self.recheck.borrow_mut() )
+	}
+
+
+	fn start_send( self: Pin<&mut Self>, item: WsMessage ) -> Result<(), Self::Error>
+	{
+		self.start_send_shared( item )
+	}
+
+
+	fn poll_flush( self: Pin<&mut Self>, _: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		Poll::Ready( Ok(()) )
+	}
+
+
+	fn poll_close( self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		self.poll_close_shared( cx )
+	}
+}
+
+
+
+impl Sink<WsMessage> for WsSender
+{
+	type Error = WsErr;
+
+
+	fn poll_ready( self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		self.poll_ready_shared( cx, &mut self.recheck.borrow_mut() )
+	}
+
+
+	fn start_send( self: Pin<&mut Self>, item: WsMessage ) -> Result<(), Self::Error>
+	{
+		self.start_send_shared( item )
+	}
+
+
+	fn poll_flush( self: Pin<&mut Self>, _: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		Poll::Ready( Ok(()) )
+	}
+
+
+	fn poll_close( self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		self.poll_close_shared( cx )
+	}
+}
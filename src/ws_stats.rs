@@ -0,0 +1,147 @@
+use
+{
+	crate :: { import::*      } ,
+	std   :: { time::Duration } ,
+};
+
+
+/// Statistics about messages that were dropped because a consumer didn't keep up, see [WsIo::stats](crate::WsIo::stats)
+/// and [WsSubscription::stats](crate::WsSubscription::stats). Messages are only dropped from subscriptions, the
+/// [WsIo](crate::WsIo) stream keeps all of them, so the numbers of a connection are those of all its subscribers together.
+/// The connection also counts the messages it skipped because they couldn't be converted.
+//
+#[ derive( Debug, Clone, Copy, Default, PartialEq ) ]
+//
+pub struct WsStats
+{
+	/// The number of messages dropped.
+	//
+	pub dropped: u64,
+
+	/// When the last message was dropped, in milliseconds since the unix epoch, as given by `Date.now()`.
+	//
+	pub last_drop: Option<f64>,
+
+	/// The number of incoming messages skipped because they couldn't be converted, with
+	/// [ErrorPolicy::SkipAndCount](crate::ErrorPolicy::SkipAndCount). Always 0 for a subscription.
+	//
+	pub skipped: u64,
+}
+
+
+
+impl WsStats
+{
+	// Count a dropped message.
+	//
+	pub(crate) fn record_drop( &mut self, now: f64 )
+	{
+		self.dropped  += 1;
+		self.last_drop = Some( now );
+	}
+
+
+	// Count a message skipped by the error policy.
+	//
+	pub(crate) fn record_skip( &mut self )
+	{
+		self.skipped += 1;
+	}
+}
+
+
+
+/// The callback of [WsConfig::lag_warning](crate::WsConfig::lag_warning). Two of these are equal if they are the
+/// same callback.
+//
+#[ derive( Clone ) ]
+//
+pub(crate) struct LagWarning
+{
+	hook    : Rc<dyn Fn(u64)>,
+	interval: Duration       ,
+}
+
+
+
+impl LagWarning
+{
+	pub(crate) fn new( interval: Duration, hook: impl Fn(u64) + 'static ) -> Self
+	{
+		Self { hook: Rc::new( hook ), interval }
+	}
+}
+
+
+impl PartialEq for LagWarning
+{
+	fn eq( &self, other: &Self ) -> bool
+	{
+		Rc::ptr_eq( &self.hook, &other.hook ) && self.interval == other.interval
+	}
+}
+
+
+impl Eq for LagWarning {}
+
+
+impl fmt::Debug for LagWarning
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		write!( f, "LagWarning every {:?}", self.interval )
+	}
+}
+
+
+
+// The WsConfig::lag_warning callback and the number of drops to pass it.
+//
+pub(crate) type LagCall = ( Rc<dyn Fn(u64)>, u64 );
+
+
+
+// Rate limits the calls to a LagWarning.
+//
+pub(crate) struct LagReporter
+{
+	warning   : LagWarning   ,
+	unreported: u64          ,
+	warned_at : Option<f64>  ,
+}
+
+
+
+impl LagReporter
+{
+	pub(crate) fn new( warning: LagWarning ) -> Self
+	{
+		Self { warning, unreported: 0, warned_at: None }
+	}
+
+
+	// Count `dropped` messages. Returns the callback and the number of drops to report if it's time to call it.
+	// We don't call it ourselves, so the caller can release it's borrows first.
+	//
+	pub(crate) fn record( &mut self, dropped: u64, now: f64 ) -> Option<LagCall>
+	{
+		self.unreported += dropped;
+
+		if self.unreported == 0 { return None }
+
+		let due = match self.warned_at
+		{
+			Some( at ) => now - at >= self.warning.interval.as_millis() as f64,
+			None       => true,
+		};
+
+		if !due { return None }
+
+		let count = self.unreported;
+
+		self.warned_at  = Some( now );
+		self.unreported = 0;
+
+		Some(( self.warning.hook.clone(), count ))
+	}
+}
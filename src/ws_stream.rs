@@ -1,23 +1,86 @@
 use
 {
-	crate :: { import::*, WsErr, WsErrKind, WsState, WsIo, WsEvent, CloseEvent, NextEvent, WsEventType },
+	crate   :: { import::*, timer::SharedTimer, spawner::SharedSpawner, unload::UnloadGuard                       } ,
+	crate   :: { instrument::Instrument                                                                           } ,
+	crate   :: { Extension, RetryPolicy, RetryProgress, WsConfig, WsErr, WsErrKind, WsState, WsIo, WsEvent        } ,
+	crate   :: { WsSender, RetryStatus, Watch, WatchStream                                                      } ,
+	crate   :: { CloseEvent, NextEvent, WsEventType, network::{ self, NetworkGuard }                              } ,
+	futures :: { stream::FuturesUnordered, future::Either                                                         } ,
+	futures :: { channel::mpsc::{ channel, unbounded }                                                            } ,
+	std     :: { time::Duration, cell::Cell                                                                       } ,
 };
 
 
+// How often WsStream::ready and WsSender check bufferedAmount.
+//
+pub(crate) const READY_POLL: Duration = Duration::from_millis( 10 );
+
+
+
 /// The meta data related to a websocket.
 ///
 /// Most of the methods on this type directly map to the web API. For more documentation, check the
 /// [MDN WebSocket documentation](https://developer.mozilla.org/en-US/docs/Web/API/WebSocket/WebSocket).
+///
+/// `WsStream` is a handle to the connection. Cloning it is cheap and all clones control the same connection,
+/// so you can hand one to every part of your application that needs to close it, check its state, listen to
+/// its events or send messages with [WsStream::sender]. All methods take `&self`. Reading messages is done
+/// with [WsIo], which has a single owner.
+///
+/// Closing the connection from one clone while another one, or a [WsSender], is sending is fine: the send
+/// returns [WsErrKind::ConnectionNotOpen] as soon as the browser started closing, it doesn't panic.
+///
+/// Our event handlers are removed when the last clone is dropped, so keep one around as long as you
+/// want to observe events or be notified when the connection closes.
 //
-#[ allow( dead_code ) ] // we need to store the closures to keep it form being dropped
+#[ allow( dead_code ) ] // we need to store the guards to keep them from being dropped
+#[ derive( Clone      ) ]
 //
 pub struct WsStream
 {
 	ws    : Rc<WebSocket>                             ,
 	pharos: Rc<RefCell< Pharos<WsEvent> >>            ,
 
-	on_open : Closure< dyn FnMut() + 'static > ,
-	on_error: Closure< dyn FnMut() + 'static > ,
+	// The close event, once the connection is closed
+	//
+	close_evt: Rc<RefCell< Option<CloseEvent> >>      ,
+
+	// Whether an error event happened, which is fatal after the connection opened.
+	//
+	errored: Rc<Cell<bool>>                           ,
+
+	// Forwards online/offline events to pharos until the connection closes.
+	//
+	network: Rc<RefCell< Option<NetworkGuard> >>      ,
+
+	// From the config, for WsStream::ready.
+	//
+	timer  : SharedTimer                               ,
+
+	// From the config, runs the background tasks of the connection.
+	//
+	spawner: SharedSpawner                             ,
+
+	// Shares the interceptors of the WsIo, see WsStream::sender.
+	//
+	sender : WsSender                                  ,
+
+	// Shared by all clones, removes our event listeners when the last one is dropped.
+	//
+	handlers: Rc< Handlers >                           ,
+}
+
+
+
+// The event listeners of a WsStream.
+//
+#[ allow( dead_code ) ] // we need to store the closures to keep them from being dropped
+//
+struct Handlers
+{
+	ws      : Rc<WebSocket>                            ,
+	on_open : Closure< dyn FnMut() + 'static >         ,
+	on_error: Closure< dyn FnMut() + 'static >         ,
 	on_close: Closure< dyn FnMut( JsCloseEvt ) + 'static > ,
 }
 
@@ -32,7 +95,7 @@ impl WsStream
 	/// a [WsIo] (AsyncRead/AsyncWrite + Stream/Sink over [WsMessage](crate::WsMessage)).
 	///
 	/// A WsStream instance is observable through the [`pharos::Observable`](https://docs.rs/pharos/0.2.0/pharos/trait.Observable.html) and [`pharos::ObservableUnbounded`](https://docs.rs/pharos/0.2.0/pharos/trait.UnboundedObservable.html) traits. The type of event is [WsEvent]. In the case of a Close event, there will be additional information included
-	/// as a [CloseEvent].
+	/// as a [CloseEvent]. Observers that subscribe after the connection closed still get the close event.
 	///
 	/// When you drop this, the connection does not get closed, however when you drop [WsIo] it does. Streams
 	/// of events will be dropped, so you will no longer receive events. One thing is possible if you really
@@ -41,7 +104,8 @@ impl WsStream
 	/// `on_error`. If you would do that while [WsStream] is still around, that would break the event system
 	/// and can lead to errors if you still call methods on [WsStream].
 	///
-	/// **Note**: Sending protocols to a server that doesn't support them will make the connection fail.
+	/// The url can be anything that implements `AsRef<str>`, like `&str`, `String`, `Cow<str>`, `Rc<str>` or
+	/// `url::Url`.
 	///
 	/// ## Errors
 	///
@@ -53,13 +117,38 @@ impl WsStream
 	/// When the connection fails (server port not open, wrong ip, wss:// on ws:// server, ... See the [HTML Living Standard](https://html.spec.whatwg.org/multipage/web-sockets.html#dom-websocket)
 	/// for details on all failure possibilities), a [WsErrKind::ConnectionFailed] is returned.
 	//
-	pub async fn connect( url: impl AsRef<str>, protocols: impl Into<Option<Vec<&str>>> )
+	pub async fn connect( url: impl AsRef<str> ) -> Result< (Self, WsIo), WsErr >
+	{
+		Self::connect_with_config( url, WsConfig::default() ).await
+	}
+
+
+
+	/// Connect to the server, requesting the given subprotocols. See [WsStream::connect] for more information.
+	///
+	/// **Note**: Sending protocols to a server that doesn't support them will make the connection fail.
+	//
+	pub async fn connect_with_protocols( url: impl AsRef<str>, protocols: impl IntoIterator< Item = impl AsRef<str> > )
 
 		-> Result< (Self, WsIo), WsErr >
 	{
-		let res = match protocols.into()
+		Self::connect_with_config( url, WsConfig::default().protocols( protocols ) ).await
+	}
+
+
+
+	/// Connect to the server with the options in [WsConfig]. See [WsStream::connect] for more information.
+	//
+	pub async fn connect_with_config( url: impl AsRef<str>, config: WsConfig ) -> Result< (Self, WsIo), WsErr >
+	{
+		let url        = url.as_ref();
+		let instrument = Instrument::new( &config );
+
+		instrument.connect_start();
+
+		let res = match &config.protocols
 		{
-			None => WebSocket::new( url.as_ref() ),
+			None => WebSocket::new( url ),
 
 			Some(v) =>
 			{
@@ -69,7 +158,7 @@ impl WsStream
 					acc
 				});
 
-				WebSocket::new_with_str_sequence( url.as_ref(), &js_protos )
+				WebSocket::new_with_str_sequence( url, &js_protos )
 			}
 		};
 
@@ -80,17 +169,15 @@ impl WsStream
 		{
 			Ok(ws) => ws,
 
-			Err(e) =>
+			// The spec only has these two, but browsers are free to throw something else, so we don't
+			// assume it's a DomException.
+			//
+			Err(e) => return Err( match e.dyn_ref::<DomException>().map( |de| de.code() )
 			{
-				let de: &DomException = e.unchecked_ref();
-
-				match de.code()
-				{
-					DomException::SECURITY_ERR => return Err( WsErrKind::ForbiddenPort.into()                         ),
-					DomException::SYNTAX_ERR   => return Err( WsErrKind::InvalidUrl( url.as_ref().to_string()).into() ),
-					_                          => unreachable!(),
-				};
-			}
+				Some( DomException::SECURITY_ERR ) => WsErrKind::ForbiddenPort.into()                 ,
+				Some( DomException::SYNTAX_ERR   ) => WsErrKind::InvalidUrl( url.to_string() ).into() ,
+				_                                  => WsErr::from( e )                                    ,
+			}),
 		};
 
 
@@ -102,6 +189,19 @@ impl WsStream
 		let ph3    = pharos.clone();
 		let ph4    = pharos.clone();
 
+		let errored   = Rc::new( Cell::new( false ) );
+		let err2      = errored.clone();
+
+		let close_evt = Rc::new( RefCell::new( None ) );
+		let ce2       = close_evt.clone();
+		let ce3       = close_evt.clone();
+
+		let unload    = Rc::new( RefCell::new( None ) );
+		let ul2       = unload.clone();
+
+		let network   = Rc::new( RefCell::new( None ) );
+		let net2      = network.clone();
+
 
 		// Setup our event listeners
 		//
@@ -118,6 +218,8 @@ impl WsStream
 		{
 			trace!( "websocket error event" );
 
+			err2.set( true );
+
 			rt::block_on( ph2.borrow_mut().notify( &WsEvent::Error ) );
 
 		}) as Box< dyn FnMut() > );
@@ -134,6 +236,13 @@ impl WsStream
 				was_clean: evt.was_clean(),
 			};
 
+			*ce2.borrow_mut() = Some( e.clone() );
+
+			// The connection is gone, no need to close it on unload anymore.
+			//
+			let _ = ul2 .borrow_mut().take();
+			let _ = net2.borrow_mut().take();
+
 			rt::block_on( ph3.borrow_mut().notify( &WsEvent::Close(e) ));
 
 		}) as Box< dyn FnMut( JsCloseEvt ) > );
@@ -144,6 +253,12 @@ impl WsStream
 		ws.set_onerror( Some( &on_error.as_ref().unchecked_ref() ));
 
 
+		// If this future is dropped before the connection opens, close the socket and
+		// remove our handlers, since the closures will be dropped.
+		//
+		let guard = ConnectGuard { ws: Some( &ws ) };
+
+
 
 		// Listen to the events to figure out whether the connection opens
 		// successfully. We don't want to deal with the error event. Either
@@ -160,35 +275,342 @@ impl WsStream
 		{
 			trace!( "WebSocket connection closed!" );
 
+			// Our closures get dropped when we return, the guard makes sure the browser won't call them.
+			//
 			return Err( WsErrKind::ConnectionFailed(evt).into() )
 		}
 
 
 		trace!( "WebSocket connection opened!" );
 
-		// We don't handle Blob's
+		instrument.connect_open();
+
+
+		if let ( Some( code ), Some( requested ) ) = ( config.require_protocol, &config.protocols )
+		{
+			let got = ws.protocol();
+
+			if !requested.contains( &got )
+			{
+				let err = WsErr::from( WsErrKind::ProtocolNegotiationFailed{ requested: requested.clone(), got } );
+
+				error!( "{}", err );
+
+				// We close here so the server sees the code, the guard will remove our handlers.
+				//
+				if ws.close_with_code_and_reason( code, "Subprotocol negotiation failed" ).is_err()
+				{
+					let _ = ws.close_with_code_and_reason( 1000, "Subprotocol negotiation failed" );
+				}
+
+				return Err( err );
+			}
+		}
+
+
+		guard.disarm();
+
+		// We only want Blob's for streaming big messages.
 		//
-		ws.set_binary_type( BinaryType::Arraybuffer );
+		match config.stream_threshold
+		{
+			Some(_) => ws.set_binary_type( BinaryType::Blob        ),
+			None    => ws.set_binary_type( BinaryType::Arraybuffer ),
+		}
 
 		let ws = Rc::new( ws );
 
+		let ph5 = pharos.clone();
+
+		*network.borrow_mut() = NetworkGuard::new( move |online|
+		{
+			let evt = if online { WsEvent::NetworkOnline } else { WsEvent::NetworkOffline };
+
+			trace!( "WsStream: {:?}", evt );
+
+			rt::block_on( ph5.borrow_mut().notify( &evt ) );
+		});
+
+
+		if let Some(( code, reason )) = &config.close_on_unload
+		{
+			*unload.borrow_mut() = UnloadGuard::new( ws.clone(), *code, reason.clone() );
+		}
+
+
+		let wsio     = WsIo::new( ws.clone(), ph4, &config, unload, ce3, instrument );
+		let handlers = Rc::new( Handlers { ws: ws.clone(), on_open, on_error, on_close } );
+
 		Ok
 		((
 			Self
 			{
-				ws      : ws.clone() ,
-				pharos               ,
-				on_open              ,
-				on_error             ,
-				on_close             ,
+				ws                               ,
+				pharos                           ,
+				close_evt                        ,
+				errored                          ,
+				network                          ,
+				timer   : config.timer  .clone() ,
+				spawner : config.spawner.clone() ,
+				sender  : wsio.sender()          ,
+				handlers                         ,
 			},
 
-			WsIo::new( ws, ph4 )
+			wsio
 		))
 	}
 
 
 
+	/// Connect to several urls concurrently and keep the first connection that opens. This is useful when the
+	/// same service is reachable through several hosts. The attempts are started `stagger` apart, in the order
+	/// of `urls`, so the first url is preferred if it's fast. Use `Duration::from_millis( 0 )` to start them
+	/// all at once.
+	///
+	/// As soon as one connection opens, all other attempts are cancelled: their sockets are closed and our
+	/// event handlers are removed, even if they were about to open as well.
+	///
+	/// If all attempts fail, [WsErrKind::AllConnectionsFailed] is returned with the error of each url.
+	//
+	pub async fn connect_race( urls: impl IntoIterator< Item = impl AsRef<str> >, stagger: Duration )
+
+		-> Result< (Self, WsIo), WsErr >
+	{
+		Self::connect_race_with_config( urls, stagger, WsConfig::default() ).await
+	}
+
+
+
+	/// Like [WsStream::connect_race], but every attempt connects with `config`. The stagger is measured with
+	/// [WsConfig::timer].
+	//
+	pub async fn connect_race_with_config( urls: impl IntoIterator< Item = impl AsRef<str> >, stagger: Duration, config: WsConfig )
+
+		-> Result< (Self, WsIo), WsErr >
+	{
+		let mut attempts: FuturesUnordered<_> = urls.into_iter().enumerate().map( |(i, url)|
+		{
+			let url    = url.as_ref().to_string();
+			let delay  = stagger * i as u32;
+			let config = config.clone();
+
+			async move
+			{
+				if i > 0 { config.timer.sleep( delay ).await; }
+
+				let res = Self::connect_with_config( &url, config ).await;
+
+				( url, res )
+			}
+
+		}).collect();
+
+
+		let mut errors = Vec::new();
+
+		// Dropping `attempts` when we return cancels the pending attempts.
+		//
+		while let Some(( url, res )) = attempts.next().await
+		{
+			match res
+			{
+				Ok( conn ) =>
+				{
+					debug!( "WsStream::connect_race: connected to {}", url );
+
+					return Ok( conn );
+				}
+
+				Err( e ) =>
+				{
+					debug!( "WsStream::connect_race: failed to connect to {}: {}", url, e );
+
+					errors.push(( url, e.kind().clone() ));
+				}
+			}
+		}
+
+		Err( WsErrKind::AllConnectionsFailed( errors ).into() )
+	}
+
+
+
+	/// Like [WsStream::connect_with_config], but also returns an `AbortHandle` (from the futures library) that can
+	/// be used to cancel the connection attempt from elsewhere. An aborted attempt resolves with
+	/// [WsErrKind::ConnectAborted].
+	///
+	/// Note that you can also just drop the future returned by any of the connect methods. When it is dropped
+	/// before the connection is established, the socket is closed and our event handlers are removed.
+	//
+	pub fn connect_abortable( url: impl AsRef<str>, config: WsConfig )
+
+		-> ( impl Future< Output = Result< (Self, WsIo), WsErr > >, AbortHandle )
+	{
+		let url = url.as_ref().to_string();
+
+		let (fut, handle) = abortable( async move { Self::connect_with_config( url, config ).await } );
+
+		let fut = async move
+		{
+			match fut.await
+			{
+				Ok ( res ) => res,
+
+				Err( _ ) =>
+				{
+					debug!( "WsStream::connect_abortable: connection attempt aborted" );
+
+					Err( WsErrKind::ConnectAborted.into() )
+				}
+			}
+		};
+
+		( fut, handle )
+	}
+
+
+
+	/// Connect to the server, retrying according to the [RetryPolicy] until the connection succeeds. This
+	/// resolves with the established connection or with the error of the last attempt. Once connected,
+	/// you get a normal connection, it will not reconnect if it gets closed later on.
+	///
+	/// Each failed attempt is reported to the callback set with [RetryPolicy::on_progress].
+	///
+	/// While the browser is offline (`navigator.onLine` is false), no attempts are made. When the
+	/// network comes back, we try again right away instead of waiting for the rest of the delay.
+	//
+	pub async fn connect_retry( url: impl AsRef<str>, policy: RetryPolicy ) -> Result< (Self, WsIo), WsErr >
+	{
+		Self::connect_retry_with_config( url, policy, WsConfig::default() ).await
+	}
+
+
+
+	/// Like [WsStream::connect_retry], but every attempt connects with `config`. The delays between attempts are
+	/// measured with [RetryPolicy::timer], not with [WsConfig::timer].
+	//
+	pub async fn connect_retry_with_config( url: impl AsRef<str>, policy: RetryPolicy, config: WsConfig )
+
+		-> Result< (Self, WsIo), WsErr >
+	{
+		Self::connect_failover_with_config( std::iter::once( url ), policy, config ).await
+	}
+
+
+
+	/// Like [WsStream::connect_retry], but rotates through a list of urls, in order. How we move on
+	/// to the next url is determined by [RetryPolicy::failover]. The number of attempts and the delays
+	/// count all attempts, whichever url they used.
+	///
+	/// A url that can never work ([WsErrKind::ForbiddenPort] or [WsErrKind::InvalidUrl]) is taken out
+	/// of the rotation. When there are no urls left, the error is returned.
+	///
+	/// Each [RetryProgress] tells which url failed. Once connected, [WsStream::url] tells you which one
+	/// is in use.
+	///
+	/// If `urls` is empty, returns [WsErrKind::AllConnectionsFailed] with an empty list.
+	//
+	pub async fn connect_failover( urls: impl IntoIterator< Item = impl AsRef<str> >, policy: RetryPolicy )
+
+		-> Result< (Self, WsIo), WsErr >
+	{
+		Self::connect_failover_with_config( urls, policy, WsConfig::default() ).await
+	}
+
+
+
+	/// Like [WsStream::connect_failover], but every attempt connects with `config`. The delays between attempts are
+	/// measured with [RetryPolicy::timer], not with [WsConfig::timer].
+	//
+	pub async fn connect_failover_with_config( urls: impl IntoIterator< Item = impl AsRef<str> >, policy: RetryPolicy, config: WsConfig )
+
+		-> Result< (Self, WsIo), WsErr >
+	{
+		let mut urls: Vec<String> = urls.into_iter().map( |u| u.as_ref().to_string() ).collect();
+
+		if urls.is_empty() { return Err( WsErrKind::AllConnectionsFailed( Vec::new() ).into() ) }
+
+		let mut attempt  = 0;
+		let mut current  = 0;
+		let mut failures = 0;
+
+		loop
+		{
+			attempt += 1;
+
+			let url = urls[ current ].clone();
+
+			policy.set_status( RetryStatus::Connecting{ attempt, url: url.clone() } );
+
+			let err = match Self::connect_with_config( &url, config.clone() ).await
+			{
+				Ok ( conn ) => { policy.set_status( RetryStatus::Connected{ attempt } ); return Ok( conn ) }
+				Err( err  ) => err,
+			};
+
+			failures += 1;
+
+
+			// Take urls that will never work out of the rotation if there are others.
+			//
+			let dead = RetryPolicy::is_permanent( err.kind() ) && urls.len() > 1;
+
+			let retry = if dead { policy.attempts_left( attempt ) } else { policy.should_retry( attempt, err.kind() ) };
+			let delay = policy.delay( attempt );
+
+			debug!( "WsStream::connect_retry: attempt {} to {} failed: {}", attempt, url, err );
+
+			policy.progress( &RetryProgress
+			{
+				attempt                                         ,
+				url                                             ,
+				error     : err.kind().clone()                  ,
+				next_delay: if retry { Some( delay ) } else { None },
+			});
+
+			if !retry
+			{
+				policy.set_status( RetryStatus::GaveUp{ attempt } );
+				return Err( err );
+			}
+
+			policy.set_status( RetryStatus::Waiting{ attempt, next_retry: delay } );
+
+
+			if dead
+			{
+				urls.remove( current );
+
+				current %= urls.len();
+				failures = 0;
+			}
+
+			else
+			{
+				let next = policy.next_url( current, failures, urls.len() );
+
+				if next != current { failures = 0 }
+
+				current = next;
+			}
+
+
+			// Stop waiting as soon as the network comes back.
+			//
+			select( policy.sleep( delay ), network::online() ).await;
+
+
+			if !network::is_online()
+			{
+				debug!( "WsStream::connect_retry: offline, waiting for the network to come back" );
+
+				network::online().await;
+			}
+		}
+	}
+
+
+
 	/// Close the socket. The future will resolve once the socket's state has become `WsState::CLOSED`.
 	/// See: [MDN Documentation](https://developer.mozilla.org/en-US/docs/Web/API/WebSocket/close)
 	//
@@ -205,18 +627,7 @@ impl WsStream
 		//
 		rt::block_on( self.pharos.borrow_mut().notify( &WsEvent::Closing ) );
 
-		let evts = NextEvent::new( self.pharos.borrow_mut().observe_unbounded(), WsEventType::CLOSE );
-
-
-		// We promised the user a CloseEvent, so we don't have much choice but to unwrap this.
-		// In any case, the stream will never end and this will hang if the browser fails to
-		// send a close event.
-		//
-		let ce = evts.await.expect_throw( "receive a close event" );
-		trace!( "WebSocket connection closed!" );
-
-		if let WsEvent::Close(e) = ce { e              }
-		else                          { unreachable!() }
+		self.closed().await
 	}
 
 
@@ -235,13 +646,7 @@ impl WsStream
 				//
 				rt::block_on( self.pharos.borrow_mut().notify( &WsEvent::Closing ) );
 
-				let evts = NextEvent::new( self.pharos.borrow_mut().observe_unbounded(), WsEventType::CLOSE );
-
-				let ce = evts.await.expect_throw( "receive a close event" );
-				trace!( "WebSocket connection closed!" );
-
-				if let WsEvent::Close(e) = ce { Ok(e)          }
-				else                          { unreachable!() }
+				Ok( self.closed().await )
 			}
 
 			Err(_) =>
@@ -280,13 +685,7 @@ impl WsStream
 				//
 				rt::block_on( self.pharos.borrow_mut().notify( &WsEvent::Closing ) );
 
-				let evts = NextEvent::new( self.pharos.borrow_mut().observe_unbounded(), WsEventType::CLOSE );
-
-				let ce = evts.await.expect_throw( "receive a close event" );
-				trace!( "WebSocket connection closed!" );
-
-				if let WsEvent::Close(e) = ce { Ok(e)          }
-				else                          { unreachable!() }
+				Ok( self.closed().await )
 			}
 
 			Err(_) =>
@@ -302,6 +701,111 @@ impl WsStream
 
 
 
+	/// A future that resolves with the [CloseEvent] when the connection terminates, whoever closes it. This
+	/// does not close the connection, nor does it consume any messages, so it can be used from a task that
+	/// supervises the connection.
+	///
+	/// Resolves immediately if the connection is already closed. You can call this as many times
+	/// as you want, each call returns a new future.
+	///
+	/// If both the [WsStream] and the [WsIo] are dropped before the connection closes, no close event
+	/// can be observed anymore and the future resolves with code 1006 (abnormal closure).
+	//
+	pub fn closed( &self ) -> impl Future< Output = CloseEvent >
+	{
+		let stored = self.close_evt.clone();
+
+		// Start observing right away, so we don't miss the event if it arrives
+		// before the future is polled.
+		//
+		let evts = match *stored.borrow()
+		{
+			Some(_) => None,
+			None    => Some( NextEvent::new( self.pharos.borrow_mut().observe_unbounded(), WsEventType::CLOSE ) ),
+		};
+
+		async move
+		{
+			if let Some( evts ) = evts
+			{
+				if let Some( WsEvent::Close(e) ) = evts.await
+				{
+					trace!( "WebSocket connection closed!" );
+
+					return e;
+				}
+			}
+
+			stored.borrow().clone().unwrap_or_else( || CloseEvent
+			{
+				code     : 1006         ,
+				reason   : String::new(),
+				was_clean: false        ,
+			})
+		}
+	}
+
+
+
+	/// A future that resolves once the connection is open and the browser has sent out all but `low_water` bytes
+	/// of the data that was queued, see [WsStream::buffered_amount]. Use `0` to wait until everything was sent, eg. before
+	/// starting a latency sensitive exchange after an initial burst of messages.
+	///
+	/// The browser has no event for the buffer draining, so `bufferedAmount` is checked every 10 milliseconds,
+	/// using the [WsConfig::timer].
+	///
+	/// Fails with [WsErrKind::ConnectionNotOpen] if the connection is closing or closed, or starts closing while waiting.
+	//
+	pub fn ready( &self, low_water: u32 ) -> impl Future< Output = Result<(), WsErr> >
+	{
+		let ws = self.ws.clone();
+
+		// Observe close right away, so a close that happens while we wait can't be missed.
+		//
+		let mut closed = Box::pin( self.closed() );
+		let     timer  = self.timer.clone();
+
+		async move
+		{
+			loop
+			{
+				let state: WsState = ws.ready_state().try_into().map_err( |e| error!( "{}", e ) )
+
+					// This can't throw unless the browser gives us an invalid ready state
+					//
+					.expect_throw( "Convert ready state from browser API" );
+
+
+				match state
+				{
+					WsState::Open if ws.buffered_amount() <= low_water => return Ok(()),
+
+					WsState::Open | WsState::Connecting => {}
+
+					WsState::Closing | WsState::Closed => return Err( WsErrKind::ConnectionNotOpen.into() ),
+				}
+
+
+				if let Either::Right(_) = select( timer.sleep( READY_POLL ), &mut closed ).await
+				{
+					return Err( WsErrKind::ConnectionNotOpen.into() );
+				}
+			}
+		}
+	}
+
+
+
+	/// The [CloseEvent] of the connection, once it is closed, whoever closed it. Unlike [WsStream::closed], this doesn't
+	/// wait. Returns `None` as long as the connection is not closed.
+	//
+	pub fn close_event( &self ) -> Option<CloseEvent>
+	{
+		self.close_evt.borrow().clone()
+	}
+
+
+
 	/// Verify the [WsState] of the connection.
 	//
 	pub fn ready_state( &self ) -> WsState
@@ -340,21 +844,32 @@ impl WsStream
 	}
 
 
-	/// The extensions selected by the server as negotiated during the connection.
+	/// The extensions selected by the server as negotiated during the connection, parsed from the
+	/// raw string the browser gives us. Use `wrapped().extensions()` if you need the raw string.
 	///
-	/// **NOTE**: This is an untested feature. The backend server we use for testing (tungstenite)
-	/// does not support Extensions.
+	/// **NOTE**: The backend server we use for testing (tungstenite) does not support Extensions,
+	/// so the parser is only tested on it's own.
+	//
+	pub fn extensions( &self ) -> Vec<Extension>
+	{
+		Extension::parse_list( &self.ws.extensions() )
+	}
+
+
+	/// Whether the server negotiated the `permessage-deflate` extension, in which case messages are
+	/// compressed by the browser and there is no point in compressing them in your application.
 	//
-	pub fn extensions( &self ) -> String
+	pub fn is_compressed( &self ) -> bool
 	{
-		self.ws.extensions()
+		self.extensions().iter().any( Extension::is_permessage_deflate )
 	}
 
 
 	/// The name of the subprotocol the server selected during the connection.
 	///
 	/// This will be one of the strings specified in the protocols parameter when
-	/// creating this WsStream instance.
+	/// creating this WsStream instance, or an empty string if the server ignored them.
+	/// Use [WsConfig::require_protocol] to refuse such connections.
 	//
 	pub fn protocol(&self) -> String
 	{
@@ -368,6 +883,122 @@ impl WsStream
 	{
 		self.ws.url()
 	}
+
+
+	/// Get a [WsSender] for this connection, the same as [WsIo::sender]. Use it to send messages from wherever
+	/// you have a clone of this handle. It keeps working after the [WsIo] is dropped, but then the connection
+	/// is closed and sending fails with [WsErrKind::ConnectionNotOpen].
+	//
+	pub fn sender( &self ) -> WsSender
+	{
+		self.sender.clone()
+	}
+
+
+	/// Send a text message, see [WsSender::send_text].
+	//
+	pub async fn send_text( &self, text: impl AsRef<str> ) -> Result<(), WsErr>
+	{
+		self.sender.send_text( text ).await
+	}
+
+
+	/// Send a binary message, see [WsSender::send_binary].
+	//
+	pub async fn send_binary( &self, data: impl AsRef<[u8]> ) -> Result<(), WsErr>
+	{
+		self.sender.send_binary( data ).await
+	}
+
+
+	/// Observe the events of the connection. This is what the `Observable` implementation does, but it only
+	/// needs `&self`, so it works on a shared handle.
+	//
+	pub fn events( &self, queue_size: usize ) -> Receiver<WsEvent>
+	{
+		let evt = match self.sticky_event()
+		{
+			Some( evt ) => evt,
+			None        => return self.pharos.borrow_mut().observe( queue_size ),
+		};
+
+		// A channel always has room for one message per sender.
+		//
+		let ( mut tx, rx ) = channel( queue_size );
+		let closed         = matches!( evt, WsEvent::Close(_) );
+
+		let _ = tx.try_send( evt );
+
+		if !closed
+		{
+			let events = self.pharos.borrow_mut().observe( queue_size );
+
+			self.spawner.spawn_local( async move { let _ = events.map( Ok ).forward( tx ).await; } );
+		}
+
+		rx
+	}
+
+
+	/// Like [WsStream::events], with an unbounded channel.
+	//
+	pub fn events_unbounded( &self ) -> UnboundedReceiver<WsEvent>
+	{
+		let evt = match self.sticky_event()
+		{
+			Some( evt ) => evt,
+			None        => return self.pharos.borrow_mut().observe_unbounded(),
+		};
+
+		let ( tx, rx ) = unbounded();
+		let closed     = matches!( evt, WsEvent::Close(_) );
+
+		let _ = tx.unbounded_send( evt );
+
+		if !closed
+		{
+			let events = self.pharos.borrow_mut().observe_unbounded();
+
+			self.spawner.spawn_local( async move { let _ = events.map( Ok ).forward( tx ).await; } );
+		}
+
+		rx
+	}
+
+
+	/// The state of the connection as a stream, for UI code that binds to it. It yields the current state right
+	/// away and then every change, never the same state twice in a row. It ends after [WsState::Closed].
+	///
+	/// The state is checked on every event of the connection. `Closing` is only seen when we start the close
+	/// handshake. When the server closes the connection, it goes from `Open` to `Closed`.
+	//
+	pub fn state_signal( &self ) -> WatchStream<WsState>
+	{
+		let watch  = Watch::new( self.ready_state() );
+		let signal = watch.subscribe();
+
+		if watch.get() != WsState::Closed
+		{
+			let mut events = self.pharos.borrow_mut().observe_unbounded();
+			let ws         = self.ws.clone();
+
+			// Dropping the watch when the connection closes ends the stream.
+			//
+			self.spawner.spawn_local( async move
+			{
+				while let Some( evt ) = events.next().await
+				{
+					let closed = matches!( evt, WsEvent::Close(_) );
+
+					watch.set( if closed { WsState::Closed } else { ws.ready_state().try_into().unwrap_or( WsState::Closed ) } );
+
+					if closed { break }
+				}
+			});
+		}
+
+		signal
+	}
 }
 
 
@@ -382,31 +1013,62 @@ impl fmt::Debug for WsStream
 
 
 
+impl WsStream
+{
+	// The terminal event that late observers should see first: the close event or, while the connection
+	// is going down, the error.
+	//
+	fn sticky_event( &self ) -> Option<WsEvent>
+	{
+		if let Some( evt ) = &*self.close_evt.borrow() { return Some( WsEvent::Close( evt.clone() ) ) }
+
+		if self.errored.get() { Some( WsEvent::Error ) } else { None }
+	}
+
+
+	pub(crate) fn spawner( &self ) -> &SharedSpawner
+	{
+		&self.spawner
+	}
+
+
+	pub(crate) fn timer( &self ) -> &SharedTimer
+	{
+		&self.timer
+	}
+}
+
+
+
+/// When the connection is already closed, the stream yields the [WsEvent::Close] event and ends. When an error
+/// happened but the connection isn't closed yet, it yields [WsEvent::Error] first, followed by all events from
+/// then on, forwarded by the [Spawner](crate::Spawner) of the connection. Other events are not replayed.
+//
 impl Observable<WsEvent> for WsStream
 {
 	fn observe( &mut self, queue_size: usize ) -> Receiver<WsEvent>
 	{
-		self.pharos.borrow_mut().observe( queue_size )
+		self.events( queue_size )
 	}
 }
 
 
 
+/// Terminal events are replayed to late observers, see the `Observable` implementation.
+//
 impl UnboundedObservable<WsEvent> for WsStream
 {
 	fn observe_unbounded( &mut self ) -> UnboundedReceiver<WsEvent>
 	{
-		self.pharos.borrow_mut().observe_unbounded()
+		self.events_unbounded()
 	}
 }
 
 
 
-impl Drop for WsStream
+impl Drop for Handlers
 {
-	// We don't block here, just tell the browser to close the connection and move on.
-	// TODO: is this necessary or would it be closed automatically when we drop the WebSocket
-	// object? Note that there is also the WsStream which holds a clone.
+	// Runs when the last clone of the WsStream is dropped.
 	//
 	fn drop( &mut self )
 	{
@@ -417,3 +1079,43 @@ impl Drop for WsStream
 		self.ws.set_onerror( None );
 	}
 }
+
+
+
+// Closes the socket and unsets the event handlers if connect is dropped before it finishes.
+//
+struct ConnectGuard<'a>
+{
+	ws: Option< &'a WebSocket >,
+}
+
+
+
+impl ConnectGuard<'_>
+{
+	fn disarm( mut self )
+	{
+		self.ws = None;
+	}
+}
+
+
+
+impl Drop for ConnectGuard<'_>
+{
+	fn drop( &mut self )
+	{
+		if let Some( ws ) = self.ws
+		{
+			trace!( "WsStream: connect dropped before the connection opened" );
+
+			ws.set_onopen ( None );
+			ws.set_onclose( None );
+			ws.set_onerror( None );
+
+			// Does nothing if the socket is already closing or closed.
+			//
+			let _ = ws.close();
+		}
+	}
+}
@@ -0,0 +1,272 @@
+use
+{
+	crate  :: { import::*, WsMessage, WsState, WsStats, ws_stats::{ LagReporter, LagCall } } ,
+	js_sys :: { Date                                                                      } ,
+	std    :: { rc::Weak, sync::Arc                                                       } ,
+};
+
+
+/// A message shared between all subscribers of a connection. Cloning it is cheap.
+//
+pub type SharedWsMessage = Arc< WsMessage >;
+
+
+
+/// A stream of every message received on a connection from the moment it was created.
+/// Obtained with [WsIo::subscribe](crate::WsIo::subscribe).
+///
+/// Every subscription has a bounded buffer, see [WsConfig::subscriber_capacity](crate::WsConfig::subscriber_capacity).
+/// When a subscriber does not keep up and the buffer is full, the oldest message is dropped to make room for the
+/// new one, so a slow subscriber never holds up the connection or the other subscribers. You can check how many
+/// messages were lost with [WsSubscription::dropped] or [WsSubscription::stats]. To be warned when this happens, use
+/// [WsConfig::lag_warning](crate::WsConfig::lag_warning).
+///
+/// Messages rejected by the connection, eg. because they exceed [WsConfig::max_message_size](crate::WsConfig::max_message_size),
+/// are only reported on the [WsIo](crate::WsIo) stream, not to subscribers.
+///
+/// The stream ends when the connection is closed. Dropping the subscription frees its buffer.
+//
+pub struct WsSubscription
+{
+	ws   : Rc< WebSocket >          ,
+	queue: Rc<RefCell< SubQueue >>  ,
+}
+
+
+
+impl WsSubscription
+{
+	/// The number of messages that were dropped from this subscription because it's buffer was full.
+	//
+	pub fn dropped( &self ) -> u64
+	{
+		self.queue.borrow().stats.dropped
+	}
+
+
+	/// The [WsStats] of this subscription.
+	//
+	pub fn stats( &self ) -> WsStats
+	{
+		self.queue.borrow().stats
+	}
+
+
+	/// Reset the [WsStats] of this subscription, which also resets [WsSubscription::dropped]. The stats of the
+	/// connection are not affected.
+	//
+	pub fn reset_stats( &self )
+	{
+		self.queue.borrow_mut().stats = WsStats::default();
+	}
+
+
+	fn ready_state( &self ) -> WsState
+	{
+		self.ws.ready_state().try_into().map_err( |e| error!( "{}", e ) )
+
+			// This can't throw unless the browser gives us an invalid ready state
+			//
+			.expect_throw( "Convert ready state from browser API" )
+	}
+}
+
+
+
+impl Stream for WsSubscription
+{
+	type Item = SharedWsMessage;
+
+
+	fn poll_next( self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Option< Self::Item >>
+	{
+		let mut queue = self.queue.borrow_mut();
+
+		if let Some( msg ) = queue.messages.pop_front()
+		{
+			return Poll::Ready( Some( msg ) );
+		}
+
+		if queue.ended { return Poll::Ready( None ) }
+
+
+		match self.ready_state()
+		{
+			WsState::Open | WsState::Connecting =>
+			{
+				match &queue.waker
+				{
+					Some( w ) if w.will_wake( cx.waker() ) => {}
+					_ => queue.waker = Some( cx.waker().clone() ),
+				}
+
+				Poll::Pending
+			}
+
+			_ => Poll::Ready( None ),
+		}
+	}
+}
+
+
+
+impl fmt::Debug for WsSubscription
+{
+	fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result
+	{
+		write!( f, "WsSubscription for connection: {}", self.ws.url() )
+	}
+}
+
+
+
+// The buffer of one subscriber.
+//
+struct SubQueue
+{
+	messages: VecDeque< SharedWsMessage >,
+	capacity: usize                      ,
+	stats   : WsStats                    ,
+	waker   : Option<Waker>              ,
+
+	// See Subscribers::end.
+	//
+	ended   : bool                       ,
+}
+
+
+
+// The list of subscribers, shared between WsIo and the onmessage callback. We only keep weak
+// references, so dropping a WsSubscription frees it's buffer.
+//
+#[ derive( Default ) ]
+//
+pub(crate) struct Subscribers
+{
+	queues: Vec< Weak<RefCell< SubQueue >> >,
+
+	// The drops of all subscribers together and the messages skipped by the connection, see WsIo::stats.
+	//
+	stats : WsStats                         ,
+	lag   : Option< LagReporter >           ,
+	ended : bool                            ,
+}
+
+
+
+impl Subscribers
+{
+	pub(crate) fn new( lag: Option<LagReporter> ) -> Self
+	{
+		Self { lag, ..Self::default() }
+	}
+
+
+	pub(crate) fn stats( &self ) -> WsStats
+	{
+		self.stats
+	}
+
+
+	pub(crate) fn reset_stats( &mut self )
+	{
+		self.stats = WsStats::default();
+	}
+
+
+	pub(crate) fn record_skip( &mut self )
+	{
+		self.stats.record_skip();
+	}
+
+
+	pub(crate) fn subscribe( &mut self, ws: Rc<WebSocket>, capacity: usize ) -> WsSubscription
+	{
+		let queue = Rc::new( RefCell::new( SubQueue
+		{
+			messages: VecDeque::new(),
+			capacity: cmp::max( capacity, 1 ),
+			stats   : WsStats::default(),
+			waker   : None,
+			ended   : self.ended,
+		}));
+
+		self.queues.push( Rc::downgrade( &queue ) );
+
+		WsSubscription { ws, queue }
+	}
+
+
+	// The connection is gone, end all subscriptions once they yielded what they have. Returns the wakers
+	// so they can be called after we release the borrow.
+	//
+	pub(crate) fn end( &mut self ) -> Vec<Waker>
+	{
+		self.ended = true;
+
+		self.queues.iter().filter_map( Weak::upgrade ).filter_map( |queue|
+		{
+			let mut queue = queue.borrow_mut();
+
+			queue.ended = true;
+			queue.waker.take()
+
+		}).collect()
+	}
+
+
+	pub(crate) fn is_empty( &self ) -> bool
+	{
+		self.queues.is_empty()
+	}
+
+
+	// Give every subscriber a copy of the message, dropping the oldest message of subscribers whose
+	// buffer is full. The wakers and the lag warning to call are returned so they can be called after
+	// we release the borrow.
+	//
+	pub(crate) fn broadcast( &mut self, msg: &WsMessage ) -> ( Vec<Waker>, Option<LagCall> )
+	{
+		// Forget about subscriptions that have been dropped.
+		//
+		self.queues.retain( |q| q.strong_count() > 0 );
+
+		let     shared  = Arc::new( msg.clone() );
+		let mut now     = None;
+		let mut dropped = 0;
+
+		let wakers = self.queues.iter().filter_map( Weak::upgrade ).filter_map( |queue|
+		{
+			let mut queue = queue.borrow_mut();
+
+			if queue.messages.len() >= queue.capacity
+			{
+				let time = *now.get_or_insert_with( Date::now );
+
+				queue.messages.pop_front();
+				queue.stats.record_drop( time );
+				dropped += 1;
+			}
+
+			queue.messages.push_back( shared.clone() );
+			queue.waker.take()
+
+		}).collect();
+
+
+		let call = match now
+		{
+			Some( time ) =>
+			{
+				self.stats.dropped  += dropped;
+				self.stats.last_drop = Some( time );
+
+				self.lag.as_mut().and_then( |lag| lag.record( dropped, time ) )
+			}
+
+			None => None,
+		};
+
+		( wakers, call )
+	}
+}
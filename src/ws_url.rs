@@ -0,0 +1,161 @@
+use crate::{ import::*, WsErr, WsIo, WsStream };
+
+
+/// A builder for websocket urls with query parameters.
+///
+/// Keys and values are percent-encoded, so you can pass arbitrary strings without worrying about
+/// spaces, `+`, `&` or unicode. Repeated keys are allowed and will appear in the order in which
+/// they were added.
+///
+/// The base url is used as is, so it may already contain a query string or be a relative url. The
+/// parameters are appended to whatever query is already present. A fragment (`#...`) is removed, because
+/// the `WebSocket` constructor rejects urls that have one.
+///
+/// ```
+/// use ws_stream_wasm::WsUrl;
+///
+/// let url = WsUrl::new( "wss://example.com/socket" )
+///
+///    .param ( "token", "s3cr3t"     )
+///    .param ( "room" , "the lounge" )
+///    .redact( "token"               )
+/// ;
+///
+/// assert_eq!( "wss://example.com/socket?token=s3cr3t&room=the%20lounge"    , url.url()      );
+/// assert_eq!( "wss://example.com/socket?token=REDACTED&room=the%20lounge"  , url.redacted() );
+/// ```
+//
+#[ derive( Clone, PartialEq, Eq ) ]
+//
+pub struct WsUrl
+{
+	base  : String               ,
+	params: Vec<(String, String)>,
+	redact: Vec<String>          ,
+}
+
+
+
+impl WsUrl
+{
+	/// Create a new url builder from a base url.
+	//
+	pub fn new( base: impl Into<String> ) -> Self
+	{
+		Self
+		{
+			base  : base.into() ,
+			params: Vec::new()  ,
+			redact: Vec::new()  ,
+		}
+	}
+
+
+	/// Append a query parameter. Both key and value will be percent-encoded.
+	//
+	pub fn param( mut self, key: impl AsRef<str>, value: impl AsRef<str> ) -> Self
+	{
+		self.params.push(( key.as_ref().to_string(), value.as_ref().to_string() ));
+		self
+	}
+
+
+	/// Mark a parameter as sensitive. Its value will be replaced by `REDACTED` in the output
+	/// of [WsUrl::redacted], as well as in the `Display` and `Debug` implementations.
+	//
+	pub fn redact( mut self, key: impl Into<String> ) -> Self
+	{
+		self.redact.push( key.into() );
+		self
+	}
+
+
+	/// The final url that will be used to connect.
+	//
+	pub fn url( &self ) -> String
+	{
+		self.build( false )
+	}
+
+
+	/// The final url with the values of redacted parameters replaced, suitable for logging.
+	//
+	pub fn redacted( &self ) -> String
+	{
+		self.build( true )
+	}
+
+
+	/// Connect to the url. See [WsStream::connect].
+	//
+	pub async fn connect( self ) -> Result< (WsStream, WsIo), WsErr >
+	{
+		WsStream::connect( self.url() ).await
+	}
+
+
+
+	fn build( &self, redacted: bool ) -> String
+	{
+		// The WebSocket constructor throws a SyntaxError for a url with a fragment.
+		//
+		let base = match self.base.find( '#' )
+		{
+			Some(i) => &self.base[ ..i ],
+			None    => self.base.as_str(),
+		};
+
+		let mut url = base.to_string();
+
+		for (key, value) in &self.params
+		{
+			if      !url.contains ( '?' )                           { url.push( '?' ) }
+			else if !url.ends_with( '?' ) && !url.ends_with( '&' ) { url.push( '&' ) }
+
+			encode_into( key, &mut url );
+			url.push( '=' );
+
+			if redacted && self.redact.contains( key ) { url.push_str( "REDACTED" ) }
+			else                                       { encode_into( value, &mut url ) }
+		}
+
+		url
+	}
+}
+
+
+
+impl fmt::Display for WsUrl
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		write!( f, "{}", self.redacted() )
+	}
+}
+
+
+
+impl fmt::Debug for WsUrl
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		write!( f, "WsUrl: {}", self.redacted() )
+	}
+}
+
+
+
+// Percent-encode everything but the unreserved characters from RFC 3986.
+//
+fn encode_into( input: &str, out: &mut String )
+{
+	for byte in input.bytes()
+	{
+		match byte
+		{
+			b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => out.push( byte as char ),
+
+			_ => out.push_str( &format!( "%{:02X}", byte ) ),
+		}
+	}
+}
@@ -0,0 +1,254 @@
+#![ feature( async_await, trait_alias )]
+wasm_bindgen_test_configure!(run_in_browser);
+
+
+
+// What's tested:
+//
+// Tests send to an echo server which just bounces back all data. Both ends of the bridge run in the
+// same context, connected by a MessageChannel.
+//
+// ✔ Text and binary messages go through the bridge in both directions and the open ack carries the url.
+// ✔ More messages than the initial credit get through, so credit is given back.
+// ✔ More oversized messages than the initial credit arrive as errors, and take and give back credit like messages.
+// ✔ Closing the remote propagates the close code and ends serve_port.
+// ✔ Messages still waiting for credit when the connection closes are delivered before the end of the stream.
+//
+use
+{
+	futures_01            :: Future as Future01,
+	futures::prelude      :: * ,
+	futures::future       :: join ,
+	wasm_bindgen::prelude :: * ,
+	wasm_bindgen_test     :: * ,
+	ws_stream_wasm        :: * ,
+	log                   :: * ,
+	web_sys               :: MessageChannel ,
+};
+
+
+
+const URL_TT: &str = "ws://127.0.0.1:3312/";
+
+
+
+// Text and binary messages go through the bridge in both directions and the open ack carries the url.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn bridge_echo() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: bridge_echo" );
+
+	async
+	{
+		let (ws, wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let channel    = MessageChannel::new().expect_throw( "create MessageChannel" );
+
+		let client = async
+		{
+			let mut remote = WsStreamRemote::connect( channel.port2() ).await.expect_throw( "connect remote" );
+
+			assert_eq!( URL_TT       , remote.url()         );
+			assert_eq!( WsState::Open, remote.ready_state() );
+
+			let text   = WsMessage::Text  ( "héllo 🦀".to_string() );
+			let binary = WsMessage::Binary( vec![ 0, 1, 2, 255 ]   );
+
+			remote.send( text  .clone() ).await.expect_throw( "send text"   );
+			remote.send( binary.clone() ).await.expect_throw( "send binary" );
+
+			assert_eq!( text  , remote.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" ) );
+			assert_eq!( binary, remote.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" ) );
+
+			remote.close().await
+		};
+
+		let (_, evt) = join( serve_port( ws, wsio, channel.port1() ), client ).await;
+
+		assert_eq!( 1000, evt.code );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// More messages than the initial credit get through, so credit is given back.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn bridge_credit() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Info );
+
+	info!( "starting test: bridge_credit" );
+
+	async
+	{
+		let (ws, wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let channel    = MessageChannel::new().expect_throw( "create MessageChannel" );
+
+		let client = async
+		{
+			let mut remote = WsStreamRemote::connect( channel.port2() ).await.expect_throw( "connect remote" );
+
+			for i in 0..200
+			{
+				remote.send( WsMessage::Text( i.to_string() ) ).await.expect_throw( "send" );
+			}
+
+			for i in 0..200
+			{
+				let msg = remote.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
+
+				assert_eq!( WsMessage::Text( i.to_string() ), msg );
+			}
+
+			remote.close().await;
+		};
+
+		join( serve_port( ws, wsio, channel.port1() ), client ).await;
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// More oversized messages than the initial credit arrive as errors, and take and give back credit like messages.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn bridge_credit_errors() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Info );
+
+	info!( "starting test: bridge_credit_errors" );
+
+	async
+	{
+		let config     = WsConfig::default().max_message_size( 4 );
+		let (ws, wsio) = WsStream::connect_with_config( URL_TT, config ).await.expect_throw( "Could not create websocket" );
+		let channel    = MessageChannel::new().expect_throw( "create MessageChannel" );
+
+		let client = async
+		{
+			let mut remote = WsStreamRemote::connect( channel.port2() ).await.expect_throw( "connect remote" );
+
+			for i in 0..200
+			{
+				remote.send( WsMessage::Text( format!( "too long {}", i ) ) ).await.expect_throw( "send" );
+			}
+
+			remote.send( WsMessage::from( "end" ) ).await.expect_throw( "send" );
+
+			for _ in 0..200
+			{
+				let err = remote.next().await.expect_throw( "Stream closed" ).unwrap_err();
+
+				assert!( matches!( err.kind(), WsErrKind::Remote(_) ) );
+			}
+
+			assert_eq!( WsMessage::from( "end" ), remote.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" ) );
+
+			remote.close().await;
+		};
+
+		join( serve_port( ws, wsio, channel.port1() ), client ).await;
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Closing the remote propagates the close code and ends serve_port.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn bridge_close() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: bridge_close" );
+
+	async
+	{
+		let (ws, wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let channel    = MessageChannel::new().expect_throw( "create MessageChannel" );
+
+		let client = async
+		{
+			let mut remote = WsStreamRemote::connect( channel.port2() ).await.expect_throw( "connect remote" );
+
+			let evt = remote.close_reason( 4000, "done" ).await;
+
+			assert_eq!( 4000           , evt.code             );
+			assert_eq!( "done"         , evt.reason           );
+			assert_eq!( WsState::Closed, remote.ready_state() );
+
+			// The stream has ended and sending fails.
+			//
+			assert!( remote.next().await.is_none() );
+			assert!( remote.send( WsMessage::Text( "late".to_string() ) ).await.is_err() );
+		};
+
+		join( serve_port( ws, wsio, channel.port1() ), client ).await;
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Messages still waiting for credit when the connection closes are delivered before the end of the stream.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn bridge_close_pending() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Info );
+
+	info!( "starting test: bridge_close_pending" );
+
+	async
+	{
+		let (ws, wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let channel    = MessageChannel::new().expect_throw( "create MessageChannel" );
+
+		let client = async
+		{
+			let mut remote = WsStreamRemote::connect( channel.port2() ).await.expect_throw( "connect remote" );
+
+			// We don't read before the close, so no credit is given back and part of the echoes stay in the worker.
+			//
+			for i in 0..200
+			{
+				remote.send( WsMessage::Text( i.to_string() ) ).await.expect_throw( "send" );
+			}
+
+			remote.close().await;
+
+			for i in 0..200
+			{
+				let msg = remote.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
+
+				assert_eq!( WsMessage::Text( i.to_string() ), msg );
+			}
+
+			assert!( remote.next().await.is_none() );
+		};
+
+		join( serve_port( ws, wsio, channel.port1() ), client ).await;
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
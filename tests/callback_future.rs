@@ -0,0 +1,189 @@
+#![ feature( async_await, trait_alias )]
+wasm_bindgen_test_configure!(run_in_browser);
+
+
+
+// What's tested:
+//
+// Events are dispatched synchronously on a plain EventTarget.
+//
+// ✔ listen yields events in order and the stream ends when the guard is dropped
+// ✔ Removing one listener doesn't affect another one for the same event
+// ✔ once resolves with an event dispatched before it was polled
+// ✔ once ignores events after the first one, before and after it resolved
+// ✔ Dropping once before the event removes the listener, so a later event is safe
+//
+use
+{
+	futures_01            :: Future as Future01,
+	futures::prelude      :: * ,
+	wasm_bindgen::prelude :: * ,
+	wasm_bindgen_test     :: * ,
+	ws_stream_wasm        :: * ,
+	log                   :: * ,
+	web_sys               :: { Event, EventTarget } ,
+};
+
+
+
+fn fire( target: &EventTarget, event: &str )
+{
+	let evt = Event::new( event ).expect_throw( "create event" );
+
+	target.dispatch_event( &evt ).expect_throw( "dispatch event" );
+}
+
+
+
+// listen yields events in order and the stream ends when the guard is dropped
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn listen_events() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: listen_events" );
+
+	async
+	{
+		let target                = EventTarget::new().expect_throw( "create EventTarget" );
+		let ( mut events, guard ) = listen( &target, "ping" );
+
+		fire( &target, "ping" );
+		fire( &target, "pong" );
+		fire( &target, "ping" );
+
+		assert_eq!( "ping", events.next().await.expect_throw( "event" ).type_() );
+		assert_eq!( "ping", events.next().await.expect_throw( "event" ).type_() );
+
+		drop( guard );
+
+		fire( &target, "ping" );
+
+		assert!( events.next().await.is_none() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Removing one listener doesn't affect another one for the same event
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn independent() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: independent" );
+
+	async
+	{
+		let target              = EventTarget::new().expect_throw( "create EventTarget" );
+		let ( a, _guard_a )     = listen( &target, "ping" );
+		let ( mut b, _guard_b ) = listen( &target, "ping" );
+
+		drop( a );
+
+		fire( &target, "ping" );
+
+		assert_eq!( "ping", b.next().await.expect_throw( "event" ).type_() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// once resolves with an event dispatched before it was polled
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn once_event() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: once_event" );
+
+	async
+	{
+		let target = EventTarget::new().expect_throw( "create EventTarget" );
+		let next   = once( &target, "ping" );
+
+		fire( &target, "ping" );
+
+		assert_eq!( "ping", next.await.type_() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// once ignores events after the first one, before and after it resolved
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn once_double_fire() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: once_double_fire" );
+
+	async
+	{
+		let target = EventTarget::new().expect_throw( "create EventTarget" );
+		let next   = once( &target, "ping" );
+
+		fire( &target, "ping" );
+		fire( &target, "ping" );
+
+		assert_eq!( "ping", next.await.type_() );
+
+		fire( &target, "ping" );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Dropping once before the event removes the listener, so a later event is safe
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn once_early_drop() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: once_early_drop" );
+
+	async
+	{
+		let target = EventTarget::new().expect_throw( "create EventTarget" );
+		let mut p  = Box::pin( once( &target, "ping" ) );
+
+		assert!( p.as_mut().now_or_never().is_none() );
+
+		drop( p );
+
+		fire( &target, "ping" );
+
+		// Another listener for the same event still works.
+		//
+		let next = once( &target, "ping" );
+
+		fire( &target, "ping" );
+
+		assert_eq!( "ping", next.await.type_() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
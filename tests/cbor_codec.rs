@@ -0,0 +1,57 @@
+#![ cfg( feature = "cbor" ) ]
+
+// What's tested:
+//
+// These don't need a backend, so they are regular tests.
+//
+// ✔ Round trip of a serde type through CborCodec
+// ✔ Garbage and text messages yield Decode errors
+//
+use
+{
+	serde          :: { Serialize, Deserialize } ,
+	ws_stream_wasm :: { *                      } ,
+};
+
+
+
+#[ derive( Debug, PartialEq, Serialize, Deserialize ) ]
+//
+enum Cmd
+{
+	Join { room: String },
+	Leave,
+}
+
+
+
+// Round trip of a serde type through CborCodec
+//
+#[ test ]
+//
+fn round_trip()
+{
+	let cmd = Cmd::Join{ room: "lobby".to_string() };
+	let msg = CborCodec.encode( &cmd ).expect( "encode" );
+
+	assert!( matches!( msg, WsMessage::Binary(_) ) );
+
+	let back: Cmd = CborCodec.decode( msg ).expect( "decode" );
+
+	assert_eq!( cmd, back );
+}
+
+
+
+// Garbage and text messages yield Decode errors
+//
+#[ test ]
+//
+fn decode_errors()
+{
+	let res: Result<Cmd, _> = CborCodec.decode( WsMessage::Binary( vec![ 0xff, 0x00 ] ) );
+	assert!( matches!( res.unwrap_err().kind(), WsErrKind::Decode(_) ) );
+
+	let res: Result<Cmd, _> = CborCodec.decode( WsMessage::Text( "Leave".to_string() ) );
+	assert!( matches!( res.unwrap_err().kind(), WsErrKind::Decode(_) ) );
+}
@@ -0,0 +1,157 @@
+#![ feature( async_await, trait_alias )]
+wasm_bindgen_test_configure!(run_in_browser);
+
+
+
+// What's tested:
+//
+// ✔ connect_race skips a url that fails and connects to the one that works.
+// ✔ connect_race returns the error of every url when all of them fail.
+// ✔ connect_race with a stagger prefers the first url when both work.
+// ✔ connect_race_with_config measures the stagger with the timer of the config and connects with the config. (feature mock)
+//
+use
+{
+	futures_01            :: Future as Future01,
+	futures::prelude      :: * ,
+	wasm_bindgen::prelude :: * ,
+	wasm_bindgen_test     :: * ,
+	ws_stream_wasm        :: * ,
+	log                   :: * ,
+	std                   :: time::Duration ,
+};
+
+#[ cfg( feature = "mock" ) ]
+//
+use futures::future::join;
+
+
+
+const URL      : &str = "ws://127.0.0.1:3212/";
+const URL_TT   : &str = "ws://127.0.0.1:3312/";
+const URL_WRONG: &str = "ws://127.0.0.1:33212/";
+
+
+
+// connect_race skips a url that fails and connects to the one that works.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn race_fallback() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: race_fallback" );
+
+	async
+	{
+		let (ws, _wsio) = WsStream::connect_race( &[ URL_WRONG, URL ], Duration::from_millis( 0 ) ).await
+
+			.expect_throw( "Could not create websocket" );
+
+		assert_eq!( URL, ws.url() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// connect_race returns the error of every url when all of them fail.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn race_all_fail() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: race_all_fail" );
+
+	async
+	{
+		let res = WsStream::connect_race( &[ URL_WRONG, "ws://127.0.0.1:6666/" ], Duration::from_millis( 0 ) ).await;
+
+		match res.unwrap_err().kind()
+		{
+			WsErrKind::AllConnectionsFailed( errors ) =>
+			{
+				assert_eq!( 2, errors.len() );
+
+				assert!( errors.iter().any( |(url, e)| url == URL_WRONG              && matches!( e, WsErrKind::ConnectionFailed(_) ) ) );
+				assert!( errors.iter().any( |(url, e)| url == "ws://127.0.0.1:6666/" && *e == WsErrKind::ForbiddenPort            ) );
+			}
+
+			e => panic!( "unexpected error: {}", e ),
+		}
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// connect_race with a stagger prefers the first url when both work.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn race_stagger() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: race_stagger" );
+
+	async
+	{
+		let (ws, _wsio) = WsStream::connect_race( &[ URL_TT, URL ], Duration::from_secs( 2 ) ).await
+
+			.expect_throw( "Could not create websocket" );
+
+		assert_eq!( URL_TT, ws.url() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// connect_race_with_config measures the stagger with the timer of the config and connects with the config.
+//
+#[ cfg( feature = "mock" ) ]
+#[ wasm_bindgen_test(async) ]
+//
+pub fn race_config() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: race_config" );
+
+	async
+	{
+		let timer  = MockTimer::new();
+		let config = WsConfig::default().timer( timer.clone() ).max_send_size( 4 );
+
+		// The second attempt only starts when the mock clock reaches the stagger.
+		//
+		let race    = WsStream::connect_race_with_config( &[ URL_WRONG, URL ], Duration::from_secs( 3600 ), config );
+		let advance = async
+		{
+			JsTimer.sleep( Duration::from_millis( 100 ) ).await;
+			timer.advance( Duration::from_secs( 3600 ) );
+		};
+
+		let ( res, _ ) = join( race, advance ).await;
+		let (ws, _wsio) = res.expect_throw( "Could not create websocket" );
+
+		assert_eq!( URL, ws.url() );
+
+		let err = ws.send_text( "hello" ).await.unwrap_err();
+
+		assert_eq!( &WsErrKind::MessageTooBig{ size: 5, limit: 4 }, err.kind() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
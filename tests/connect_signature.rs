@@ -0,0 +1,48 @@
+#![ feature( async_await ) ]
+
+// What's tested:
+//
+// These are compile tests. The functions are never run, but if type inference breaks
+// for common call sites of the connect methods, this file won't compile.
+//
+// ✔ WsStream::connect accepts &str, String, Cow<str> and Rc<str>
+// ✔ WsStream::connect_with_protocols accepts vectors, slices and arrays of &str and String
+// ✔ WsStream::connect_with_protocols accepts an empty list
+//
+#![ allow( dead_code ) ]
+
+use
+{
+	std            :: { borrow::Cow, rc::Rc } ,
+	ws_stream_wasm :: *                      ,
+};
+
+
+const URL: &str = "ws://127.0.0.1:3212/";
+
+
+
+async fn connect_url_types()
+{
+	let _ = WsStream::connect( URL                          ).await;
+	let _ = WsStream::connect( URL.to_string()              ).await;
+	let _ = WsStream::connect( &URL.to_string()             ).await;
+	let _ = WsStream::connect( Cow::from( URL )             ).await;
+	let _ = WsStream::connect( Rc::<str>::from( URL )       ).await;
+	let _ = WsStream::connect( WsUrl::new( URL ).url()      ).await;
+}
+
+
+
+async fn connect_protocol_types()
+{
+	let protos = vec![ "chat".to_string() ];
+
+	let _ = WsStream::connect_with_protocols( URL, vec![ "chat", "superchat" ]     ).await;
+	let _ = WsStream::connect_with_protocols( URL, &[ "chat" ]                     ).await;
+	let _ = WsStream::connect_with_protocols( URL, [ "chat" ].iter()               ).await;
+	let _ = WsStream::connect_with_protocols( URL, &protos                         ).await;
+	let _ = WsStream::connect_with_protocols( URL, protos                          ).await;
+	let _ = WsStream::connect_with_protocols( URL, Some( "chat" )                  ).await;
+	let _ = WsStream::connect_with_protocols( URL.to_string(), Vec::<&str>::new()  ).await;
+}
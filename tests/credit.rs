@@ -0,0 +1,80 @@
+#![ feature( async_await, trait_alias )]
+wasm_bindgen_test_configure!(run_in_browser);
+
+
+
+// What's tested:
+//
+// Tests send to an echo server which just bounces back all data, so our grants come back as messages too.
+//
+// ✔ The first grant is sent when first polled, and credit is topped up at the low water mark
+//
+use
+{
+	futures_01            :: Future as Future01,
+	futures::prelude      :: * ,
+	wasm_bindgen::prelude :: * ,
+	wasm_bindgen_test     :: * ,
+	ws_stream_wasm        :: * ,
+	log                   :: * ,
+};
+
+
+
+const URL_TT: &str = "ws://127.0.0.1:3312/";
+
+
+
+fn text( msg: Option<Result<WsMessage, WsErr>> ) -> String
+{
+	match msg
+	{
+		Some( Ok( WsMessage::Text( t ) ) ) => t,
+		other                              => panic!( "unexpected: {:?}", other ),
+	}
+}
+
+
+
+// window 4, low water 1:
+// - first poll grants 4, which comes back: outstanding 3
+// - "a" and "b" bring it to 1, so we grant 3, which comes back as well
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn credit_grants() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: credit_grants" );
+
+	async
+	{
+		let (_ws, wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let mut sender  = wsio.sender();
+
+		let mut credit = CreditFlow::new( wsio, sender.clone(), 4, 1, |n| WsMessage::Text( format!( "credit:{}", n ) ) );
+
+		assert_eq!( "credit:4", text( credit.next().await ) );
+		assert_eq!( 4, credit.granted()     );
+		assert_eq!( 3, credit.outstanding() );
+
+		sender.send( WsMessage::Text( "a".to_string() ) ).await.expect_throw( "send" );
+		sender.send( WsMessage::Text( "b".to_string() ) ).await.expect_throw( "send" );
+
+		assert_eq!( "a", text( credit.next().await ) );
+		assert_eq!( 4, credit.granted()     );
+		assert_eq!( 2, credit.outstanding() );
+
+		assert_eq!( "b", text( credit.next().await ) );
+		assert_eq!( 7, credit.granted()     );
+		assert_eq!( 4, credit.outstanding() );
+
+		assert_eq!( "credit:3", text( credit.next().await ) );
+		assert_eq!( 3, credit.outstanding() );
+		assert_eq!( 4, credit.consumed()    );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
@@ -0,0 +1,193 @@
+#![ feature( async_await, trait_alias )]
+wasm_bindgen_test_configure!(run_in_browser);
+
+
+
+// What's tested:
+//
+// Tests send to an echo server which just bounces back all data. The malformed frame is a binary message that
+// isn't valid UTF-8 with binary_to_text, or a text the codec can't parse, followed by a good one.
+//
+// ✔ YieldError yields the error and continues
+// ✔ SkipAndCount drops the message and counts it
+// ✔ CloseConnection yields the error and the stream ends
+// ✔ The same policies on the decode errors of a WsChannel
+//
+use
+{
+	futures_01            :: Future as Future01,
+	futures::prelude      :: * ,
+	wasm_bindgen::prelude :: * ,
+	wasm_bindgen_test     :: * ,
+	ws_stream_wasm        :: * ,
+	log                   :: * ,
+};
+
+
+
+const URL   : &str = "ws://127.0.0.1:3212/";
+const URL_TT: &str = "ws://127.0.0.1:3312/";
+
+
+
+// Connect with binary_to_text and send a bad and a good frame.
+//
+async fn connect( policy: ErrorPolicy ) -> ( WsStream, WsIo )
+{
+	let config = WsConfig::default().binary_to_text( true ).error_policy( policy );
+
+	let ( ws, mut wsio ) = WsStream::connect_with_config( URL, config ).await.expect_throw( "Could not create websocket" );
+
+	wsio.send( WsMessage::Binary( vec![ 0xff ]    ) ).await.expect_throw( "send" );
+	wsio.send( WsMessage::Binary( b"ok".to_vec() ) ).await.expect_throw( "send" );
+
+	( ws, wsio )
+}
+
+
+
+// YieldError yields the error and continues
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn yield_error() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: yield_error" );
+
+	async
+	{
+		let (_ws, mut wsio) = connect( ErrorPolicy::YieldError ).await;
+
+		assert_eq!( &WsErrKind::InvalidUtf8, wsio.next().await.unwrap_throw().unwrap_err().kind() );
+		assert_eq!( WsMessage::from( "ok" ), wsio.next().await.unwrap_throw().expect_throw( "good frame" ) );
+		assert_eq!( 0, wsio.stats().skipped );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// SkipAndCount drops the message and counts it
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn skip_and_count() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: skip_and_count" );
+
+	async
+	{
+		let (_ws, mut wsio) = connect( ErrorPolicy::SkipAndCount ).await;
+
+		assert_eq!( WsMessage::from( "ok" ), wsio.next().await.unwrap_throw().expect_throw( "good frame" ) );
+		assert_eq!( 1, wsio.stats().skipped );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// CloseConnection yields the error and the stream ends
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn close_connection() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: close_connection" );
+
+	async
+	{
+		let (ws, mut wsio) = connect( ErrorPolicy::CloseConnection( 4000 ) ).await;
+
+		assert_eq!( &WsErrKind::InvalidUtf8, wsio.next().await.unwrap_throw().unwrap_err().kind() );
+		assert!   ( wsio.next().await.is_none() );
+		assert_ne!( WsState::Open, ws.ready_state() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+struct Number;
+
+impl WsEncode<u32> for Number
+{
+	fn encode( &mut self, item: &u32 ) -> Result<WsMessage, WsErr>
+	{
+		Ok( WsMessage::Text( item.to_string() ) )
+	}
+}
+
+impl WsDecode<u32> for Number
+{
+	fn decode( &mut self, msg: WsMessage ) -> Result<u32, WsErr>
+	{
+		match msg
+		{
+			WsMessage::Text( t ) => t.parse().map_err( |_| WsErrKind::Decode( t ).into() ),
+			_                    => Err( WsErrKind::Decode( "binary".to_string() ).into() ),
+		}
+	}
+}
+
+
+
+// The same policies on the decode errors of a WsChannel
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn channel() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: channel" );
+
+	async
+	{
+		for &policy in &[ ErrorPolicy::YieldError, ErrorPolicy::SkipAndCount, ErrorPolicy::CloseConnection( 4000 ) ]
+		{
+			let (_ws, wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+			let mut sender  = wsio.sender();
+			let mut channel = wsio.into_channel::<u32, u32, _>( Number ).error_policy( policy );
+
+			sender .send( WsMessage::from( "x" ) ).await.expect_throw( "send" );
+			channel.send( 5                      ).await.expect_throw( "send" );
+
+			match policy
+			{
+				ErrorPolicy::YieldError =>
+				{
+					assert_eq!( &WsErrKind::Decode( "x".to_string() ), channel.next().await.unwrap_throw().unwrap_err().kind() );
+					assert_eq!( 5, channel.next().await.unwrap_throw().expect_throw( "good frame" ) );
+				}
+
+				ErrorPolicy::SkipAndCount =>
+				{
+					assert_eq!( 5, channel.next().await.unwrap_throw().expect_throw( "good frame" ) );
+					assert_eq!( 1, channel.skipped_messages() );
+				}
+
+				ErrorPolicy::CloseConnection(_) =>
+				{
+					assert_eq!( &WsErrKind::Decode( "x".to_string() ), channel.next().await.unwrap_throw().unwrap_err().kind() );
+					assert!( channel.next().await.is_none() );
+				}
+			}
+		}
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
@@ -6,6 +6,7 @@ wasm_bindgen_test_configure!(run_in_browser);
 // Tests send to an echo server which just bounces back all data.
 //
 // ✔ Verify close events are emitted.
+// ✔ Verify the close event is replayed to observers that subscribe after the connection closed.
 //
 use
 {
@@ -15,7 +16,7 @@ use
 	wasm_bindgen_test     :: { *                  } ,
 	log                   :: { *                  } ,
 	ws_stream_wasm        :: { * } ,
-	pharos                :: { UnboundedObservable } ,
+	pharos                :: { Observable, UnboundedObservable } ,
 	// web_sys               :: { console::log_1 as dbg               } ,
 };
 
@@ -36,7 +37,7 @@ pub fn close_events() -> impl Future01<Item = (), Error = JsValue>
 
 	async
 	{
-		let (mut ws, _wsio) = WsStream::connect( URL, None ).await.expect_throw( "Could not create websocket" );
+		let (mut ws, _wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
 
 		let mut evts = ws.observe_unbounded();
 
@@ -50,3 +51,36 @@ pub fn close_events() -> impl Future01<Item = (), Error = JsValue>
 	}.boxed_local().compat()
 }
 
+
+
+// Verify the close event is replayed to observers that subscribe after the connection closed.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn sticky_close() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: sticky_close" );
+
+	async
+	{
+		let (mut ws, _wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
+
+		let evt = ws.close_code( 4000 ).await.expect_throw( "close" );
+
+		// Subscribing late gives the close event, but not the closing event, and the stream ends.
+		//
+		let mut late    = ws.observe_unbounded();
+		let mut bounded = ws.observe( 0 );
+
+		assert_eq!( Some( WsEvent::Close( evt.clone() ) ), late.next().await );
+		assert_eq!( None                                 , late.next().await );
+
+		assert_eq!( Some( WsEvent::Close( evt ) ), bounded.next().await );
+		assert_eq!( None                         , bounded.next().await );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
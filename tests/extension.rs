@@ -0,0 +1,145 @@
+// What's tested:
+//
+// These don't need a backend, so they are regular tests.
+//
+// ✔ Empty header
+// ✔ Response headers from common servers as seen by Chrome and Firefox
+// ✔ Several extensions
+// ✔ Quoted values, including separators inside quotes and escapes
+// ✔ Odd whitespace and empty entries
+// ✔ Looking up parameters
+//
+use
+{
+	ws_stream_wasm :: * ,
+};
+
+
+
+fn ext( name: &str, params: &[ (&str, Option<&str>) ] ) -> Extension
+{
+	Extension
+	{
+		name  : name.to_string(),
+		params: params.iter().map( |(k, v)| ( k.to_string(), v.map( str::to_string ) ) ).collect(),
+	}
+}
+
+
+
+// Empty header
+//
+#[ test ]
+//
+fn empty()
+{
+	assert!( Extension::parse_list( ""    ).is_empty() );
+	assert!( Extension::parse_list( "  "  ).is_empty() );
+	assert!( Extension::parse_list( " , " ).is_empty() );
+}
+
+
+
+// Response headers from common servers as seen by Chrome and Firefox
+//
+#[ test ]
+//
+fn real_world()
+{
+	// What Chrome and Firefox report against a server that accepts their offer
+	// of `permessage-deflate; client_max_window_bits`.
+	//
+	assert_eq!
+	(
+		vec![ ext( "permessage-deflate", &[ ( "client_max_window_bits", Some( "15" ) ) ] ) ],
+		Extension::parse_list( "permessage-deflate; client_max_window_bits=15" )
+	);
+
+	// nginx/node ws with context takeover disabled.
+	//
+	assert_eq!
+	(
+		vec![ ext( "permessage-deflate", &[ ( "server_no_context_takeover", None ), ( "client_no_context_takeover", None ) ] ) ],
+		Extension::parse_list( "permessage-deflate; server_no_context_takeover; client_no_context_takeover" )
+	);
+
+	// node ws default.
+	//
+	assert_eq!
+	(
+		vec![ ext( "permessage-deflate", &[ ( "client_max_window_bits", None ) ] ) ],
+		Extension::parse_list( "permessage-deflate; client_max_window_bits" )
+	);
+}
+
+
+
+// Several extensions
+//
+#[ test ]
+//
+fn several()
+{
+	assert_eq!
+	(
+		vec!
+		[
+			ext( "permessage-deflate"    , &[ ( "server_max_window_bits", Some( "10" ) ) ] ),
+			ext( "x-webkit-deflate-frame", &[ ( "no_context_takeover"   , None         ) ] ),
+		],
+
+		Extension::parse_list( "permessage-deflate; server_max_window_bits=10, x-webkit-deflate-frame; no_context_takeover" )
+	);
+}
+
+
+
+// Quoted values, including separators inside quotes and escapes
+//
+#[ test ]
+//
+fn quoted()
+{
+	assert_eq!
+	(
+		vec![ ext( "permessage-deflate", &[ ( "client_max_window_bits", Some( "10" ) ) ] ) ],
+		Extension::parse_list( r#"permessage-deflate; client_max_window_bits="10""# )
+	);
+
+	assert_eq!
+	(
+		vec![ ext( "foo", &[ ( "bar", Some( "a,b;c" ) ), ( "baz", Some( r#"say "hi""# ) ) ] ), ext( "qux", &[] ) ],
+		Extension::parse_list( r#"foo; bar="a,b;c"; baz="say \"hi\"", qux"# )
+	);
+}
+
+
+
+// Odd whitespace and empty entries
+//
+#[ test ]
+//
+fn whitespace()
+{
+	assert_eq!
+	(
+		vec![ ext( "permessage-deflate", &[ ( "server_max_window_bits", Some( "10" ) ) ] ) ],
+		Extension::parse_list( "  permessage-deflate ;server_max_window_bits = 10 ;; ," )
+	);
+}
+
+
+
+// Looking up parameters
+//
+#[ test ]
+//
+fn params()
+{
+	let ext = &Extension::parse_list( "Permessage-Deflate; server_no_context_takeover; client_max_window_bits=12" )[0];
+
+	assert!   ( ext.is_permessage_deflate()                                      );
+	assert_eq!( Some( None         ), ext.param( "server_no_context_takeover" ) );
+	assert_eq!( Some( Some( "12" ) ), ext.param( "client_max_window_bits"     ) );
+	assert_eq!( None                , ext.param( "server_max_window_bits"     ) );
+}
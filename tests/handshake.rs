@@ -0,0 +1,142 @@
+#![ feature( async_await, trait_alias )]
+wasm_bindgen_test_configure!(run_in_browser);
+
+
+
+// What's tested:
+//
+// Tests send to an echo server which just bounces back all data, so the server accepts by echoing the hello.
+//
+// ✔ The validator gets the first message, and messages sent after the hello stay queued
+// ✔ A rejected handshake closes the connection and returns the error of the validator
+// ✔ Without answer, the handshake times out and closes the connection
+//
+use
+{
+	futures_01            :: Future as Future01,
+	futures::prelude      :: * ,
+	futures::future       :: join ,
+	wasm_bindgen::prelude :: * ,
+	wasm_bindgen_test     :: * ,
+	ws_stream_wasm        :: * ,
+	log                   :: * ,
+	std                   :: { time::Duration } ,
+};
+
+
+
+const URL_TT: &str = "ws://127.0.0.1:3312/";
+
+
+
+fn validate( msg: &WsMessage ) -> Result<u8, WsErr>
+{
+	match msg
+	{
+		WsMessage::Text( t ) if t == "v1" => Ok( 1 ),
+		_                                 => Err( WsErrKind::HandshakeRejected( format!( "{:?}", msg ) ).into() ),
+	}
+}
+
+
+
+// The validator gets the first message, and messages sent after the hello stay queued
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn accept() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: accept" );
+
+	async
+	{
+		let conn   = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let sender = conn.1.sender();
+
+		// The handshake is polled first, so the hello goes out before "after".
+		//
+		let hs   = handshake( conn, WsMessage::from( "v1" ), validate, Duration::from_secs( 2 ) );
+		let send = async { sender.send_text( "after" ).await.expect_throw( "send" ) };
+
+		let ( res, _ )                = join( hs, send ).await;
+		let ( (_ws, mut wsio), vers ) = res.expect_throw( "handshake" );
+
+		assert_eq!( 1, vers );
+		assert_eq!( WsMessage::from( "after" ), wsio.next().await.unwrap_throw().expect_throw( "receive" ) );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// A rejected handshake closes the connection and returns the error of the validator
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn reject() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: reject" );
+
+	async
+	{
+		let conn = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let ws   = conn.0.clone();
+
+		let err = Handshake::new( WsMessage::from( "v2" ), Duration::from_secs( 2 ) )
+
+			.close_code( 4000 )
+			.run( conn, validate )
+			.await
+			.unwrap_err()
+		;
+
+		assert!( matches!( err.kind(), WsErrKind::HandshakeRejected(_) ) );
+		assert_eq!( WsState::Closed, ws.ready_state() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Without answer, the handshake times out and closes the connection. An interceptor drops the hello,
+// so the echo server has nothing to answer.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn timeout() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: timeout" );
+
+	struct Swallow;
+
+	impl WsInterceptor for Swallow
+	{
+		fn on_send( &mut self, _: WsMessage ) -> Option<WsMessage> { None }
+	}
+
+	async
+	{
+		let conn = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let ws   = conn.0.clone();
+
+		conn.1.add_interceptor( Swallow );
+
+		let err = handshake( conn, WsMessage::from( "v1" ), validate, Duration::from_millis( 50 ) ).await.unwrap_err();
+
+		assert_eq!( &WsErrKind::Timeout, err.kind() );
+		assert_eq!( WsState::Closed, ws.ready_state() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
@@ -0,0 +1,93 @@
+#![ feature( async_await, trait_alias )]
+wasm_bindgen_test_configure!(run_in_browser);
+
+
+
+// What's tested:
+//
+// Tests send to an echo server which just bounces back all data, so the heartbeat comes back to us.
+// The page of the test runner is visible, so the visibility settings don't change anything here.
+//
+// ✔ Heartbeats are sent at the interval
+// ✔ Without answer, the stream yields HeartbeatTimeout and ends
+//
+use
+{
+	futures_01            :: Future as Future01,
+	futures::prelude      :: * ,
+	wasm_bindgen::prelude :: * ,
+	wasm_bindgen_test     :: * ,
+	ws_stream_wasm        :: * ,
+	log                   :: * ,
+	std                   :: { time::Duration } ,
+};
+
+
+
+const URL_TT: &str = "ws://127.0.0.1:3312/";
+
+
+
+// Heartbeats are sent at the interval
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn beat() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: beat" );
+
+	async
+	{
+		let hb = Heartbeat::new( Duration::from_millis( 20 ), WsMessage::from( "hb" ) )
+
+			.timeout   ( Duration::from_millis( 500 ) )
+			.visibility( Visibility::PauseWhileHidden  )
+		;
+
+		let config = WsConfig::default().heartbeat( hb );
+
+		let (_ws, mut wsio) = WsStream::connect_with_config( URL_TT, config ).await.expect_throw( "Could not create websocket" );
+
+		assert_eq!( WsMessage::from( "hb" ), wsio.next().await.unwrap_throw().expect_throw( "heartbeat" ) );
+		assert_eq!( WsMessage::from( "hb" ), wsio.next().await.unwrap_throw().expect_throw( "heartbeat" ) );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Without answer, the stream yields HeartbeatTimeout and ends. The heartbeat is too big to be sent,
+// so the echo server has nothing to answer.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn timeout() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: timeout" );
+
+	async
+	{
+		let hb = Heartbeat::new( Duration::from_millis( 20 ), WsMessage::from( "hb" ) )
+
+			.timeout( Duration::from_millis( 50 ) )
+		;
+
+		let config = WsConfig::default().heartbeat( hb ).max_send_size( 1 );
+
+		let (ws, mut wsio) = WsStream::connect_with_config( URL_TT, config ).await.expect_throw( "Could not create websocket" );
+
+		assert_eq!( &WsErrKind::HeartbeatTimeout, wsio.next().await.unwrap_throw().unwrap_err().kind() );
+		assert!( wsio.next().await.is_none() );
+
+		assert_eq!( 1000, ws.closed().await.code );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
@@ -0,0 +1,120 @@
+#![ cfg( feature = "instrument" ) ]
+#![ feature( async_await, trait_alias )]
+wasm_bindgen_test_configure!(run_in_browser);
+
+
+
+// What's tested:
+//
+// Tests send to an echo server which just bounces back all data. Run them with `--features instrument`.
+//
+// ✔ Connecting creates the connect marks and measure.
+// ✔ Only sampled messages get marks and measures.
+//
+use
+{
+	futures_01            :: Future as Future01,
+	futures::prelude      :: * ,
+	wasm_bindgen::prelude :: * ,
+	wasm_bindgen_test     :: * ,
+	ws_stream_wasm        :: * ,
+	log                   :: * ,
+	web_sys               :: { window, Performance } ,
+};
+
+
+
+const URL_TT: &str = "ws://127.0.0.1:3312/";
+
+
+
+fn perf() -> Performance
+{
+	window().expect_throw( "window" ).performance().expect_throw( "performance" )
+}
+
+
+// The names of all entries of this type that start with prefix.
+//
+fn names( kind: &str, prefix: &str ) -> Vec<String>
+{
+	perf().get_entries_by_type( kind ).iter()
+
+		.filter_map( |e| js_sys::Reflect::get( &e, &"name".into() ).ok()?.as_string() )
+		.filter    ( |n| n.starts_with( prefix ) )
+		.collect()
+}
+
+
+// All entries of all our connections, because we don't know the id of a connection.
+//
+fn count( kind: &str, suffix: &str ) -> usize
+{
+	names( kind, "ws_stream_wasm:" ).iter().filter( |n| n.ends_with( suffix ) ).count()
+}
+
+
+
+// Connecting creates the connect marks and measure.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn connect_marks() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: connect_marks" );
+
+	async
+	{
+		let before = count( "measure", ":connect" );
+
+		let _conn = WsStream::connect_with_config( URL_TT, WsConfig::default().instrument( 0 ) ).await.expect_throw( "Could not create websocket" );
+
+		assert_eq!( before + 1, count( "measure", ":connect" ) );
+
+		assert!( count( "mark", ":connect:start" ) >= 1 );
+		assert!( count( "mark", ":connect:open"  ) >= 1 );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Only sampled messages get marks and measures.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn message_marks() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: message_marks" );
+
+	async
+	{
+		let before = count( "mark", ":delivered" );
+
+		let config          = WsConfig::default().instrument( 2 );
+		let (_ws, mut wsio) = WsStream::connect_with_config( URL_TT, config ).await.expect_throw( "Could not create websocket" );
+
+		for i in 0..4
+		{
+			wsio.send( WsMessage::Text( i.to_string() ) ).await.expect_throw( "send" );
+		}
+
+		for _ in 0..4
+		{
+			wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
+		}
+
+		// Messages 0 and 2.
+		//
+		assert_eq!( before + 2, count( "mark", ":delivered" ) );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
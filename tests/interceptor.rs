@@ -0,0 +1,222 @@
+#![ feature( async_await, trait_alias )]
+wasm_bindgen_test_configure!(run_in_browser);
+
+
+
+// What's tested:
+//
+// Tests send to an echo server which just bounces back all data.
+//
+// ✔ Modify outgoing and incoming messages.
+// ✔ Drop outgoing and incoming messages.
+// ✔ Several interceptors run in the order they were added.
+// ✔ An interceptor can reply from on_receive, the reply goes through the other interceptors.
+//
+use
+{
+	futures_01            :: Future as Future01,
+	futures::prelude      :: * ,
+	wasm_bindgen::prelude :: * ,
+	wasm_bindgen_test     :: * ,
+	ws_stream_wasm        :: * ,
+	log                   :: * ,
+};
+
+
+
+const URL_TT: &str = "ws://127.0.0.1:3312/";
+
+
+
+fn text( s: &str ) -> WsMessage
+{
+	WsMessage::Text( s.to_string() )
+}
+
+
+fn append( msg: WsMessage, tail: &str ) -> WsMessage
+{
+	match msg
+	{
+		WsMessage::Text( s ) => WsMessage::Text( s + tail ),
+		other                => other,
+	}
+}
+
+
+
+// Appends a marker to messages in both directions.
+//
+struct Tag( &'static str );
+
+impl WsInterceptor for Tag
+{
+	fn on_send   ( &mut self, msg: WsMessage ) -> Option<WsMessage> { Some( append( msg, &format!( " send-{}", self.0 ) ) ) }
+	fn on_receive( &mut self, msg: WsMessage ) -> Option<WsMessage> { Some( append( msg, &format!( " recv-{}", self.0 ) ) ) }
+}
+
+
+
+// Drops outgoing messages that say "drop out" and incoming messages that say "drop in".
+//
+struct Filter;
+
+impl WsInterceptor for Filter
+{
+	fn on_send( &mut self, msg: WsMessage ) -> Option<WsMessage>
+	{
+		if msg == text( "drop out" ) { None } else { Some( msg ) }
+	}
+
+	fn on_receive( &mut self, msg: WsMessage ) -> Option<WsMessage>
+	{
+		if msg == text( "drop in" ) { None } else { Some( msg ) }
+	}
+}
+
+
+
+// Modify outgoing and incoming messages.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn modify() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: modify" );
+
+	async
+	{
+		let (_ws, mut wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+
+		// Also applies to senders created before the interceptor.
+		//
+		let mut tx = wsio.sender();
+
+		wsio.add_interceptor( Tag( "a" ) );
+
+		tx.send( text( "hi" ) ).await.expect_throw( "send" );
+
+		let msg = wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
+
+		assert_eq!( text( "hi send-a recv-a" ), msg );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Drop outgoing and incoming messages.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn drop_messages() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: drop_messages" );
+
+	async
+	{
+		let (_ws, mut wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+
+		wsio.add_interceptor( Filter );
+
+		wsio.send( text( "drop out" ) ).await.expect_throw( "send" );
+		wsio.send( text( "drop in"  ) ).await.expect_throw( "send" );
+		wsio.send( text( "keep"     ) ).await.expect_throw( "send" );
+
+		// Only the last one should come through.
+		//
+		let msg = wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
+
+		assert_eq!( text( "keep" ), msg );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Several interceptors run in the order they were added.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn ordering() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: ordering" );
+
+	async
+	{
+		let (_ws, mut wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+
+		wsio.add_interceptor( Tag( "1" ) );
+		wsio.add_interceptor( Tag( "2" ) );
+
+		wsio.send( text( "hi" ) ).await.expect_throw( "send" );
+
+		let msg = wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
+
+		assert_eq!( text( "hi send-1 send-2 recv-1 recv-2" ), msg );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Replies "pong" to messages that start with "ping".
+//
+struct Reply( WsSender );
+
+impl WsInterceptor for Reply
+{
+	fn on_receive( &mut self, msg: WsMessage ) -> Option<WsMessage>
+	{
+		if let WsMessage::Text( t ) = &msg
+		{
+			if t.starts_with( "ping" ) { self.0.send_text_ref( "pong" ).expect_throw( "reply" ) }
+		}
+
+		Some( msg )
+	}
+}
+
+
+
+// An interceptor can reply from on_receive, the reply goes through the other interceptors.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn reply() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: reply" );
+
+	async
+	{
+		let (_ws, mut wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+
+		wsio.add_interceptor( Tag( "t" ) );
+		wsio.add_interceptor( Reply( wsio.sender() ) );
+
+		wsio.send( text( "ping" ) ).await.expect_throw( "send" );
+
+		let ping = wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
+		let pong = wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
+
+		assert_eq!( text( "ping send-t recv-t" ), ping );
+		assert_eq!( text( "pong send-t recv-t" ), pong );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
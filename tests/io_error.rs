@@ -0,0 +1,90 @@
+// What's tested:
+//
+// These don't need a backend, so they are regular tests.
+//
+// ✔ WsErrKind is mapped to the right io::ErrorKind
+// ✔ The WsErr can be recovered from the io::Error
+// ✔ from_io_error returns None for io errors that don't wrap a WsErr
+//
+use
+{
+	std            :: { io } ,
+	ws_stream_wasm :: { *  } ,
+};
+
+
+
+fn to_io( kind: WsErrKind ) -> io::Error
+{
+	io::Error::from( WsErr::from( kind ) )
+}
+
+
+
+// WsErrKind is mapped to the right io::ErrorKind
+//
+#[ test ]
+//
+fn kind_mapping()
+{
+	let close = CloseEvent{ code: 1006, reason: String::new(), was_clean: false };
+
+	assert_eq!( io::ErrorKind::NotConnected, to_io( WsErrKind::ConnectionNotOpen                    ).kind() );
+	assert_eq!( io::ErrorKind::InvalidData , to_io( WsErrKind::InvalidWsState( 9 )                  ).kind() );
+	assert_eq!( io::ErrorKind::InvalidData , to_io( WsErrKind::MessageTooBig{ size: 2, limit: 1 }   ).kind() );
+	assert_eq!( io::ErrorKind::InvalidData , to_io( WsErrKind::InvalidUtf8                          ).kind() );
+	assert_eq!( io::ErrorKind::InvalidData , to_io( WsErrKind::UnsupportedData( "x".to_string() )   ).kind() );
+	assert_eq!( io::ErrorKind::InvalidData , to_io( WsErrKind::UnexpectedBinary                     ).kind() );
+	assert_eq!( io::ErrorKind::InvalidData , to_io( WsErrKind::UnexpectedText                       ).kind() );
+	assert_eq!( io::ErrorKind::InvalidData , to_io( WsErrKind::Decode( "x".to_string() )            ).kind() );
+	assert_eq!( io::ErrorKind::InvalidData , to_io( WsErrKind::ReplayMismatch{ expected: None, got: WsMessage::from( "x" ) } ).kind() );
+	assert_eq!( io::ErrorKind::Other       , to_io( WsErrKind::ForbiddenPort                        ).kind() );
+	assert_eq!( io::ErrorKind::Other       , to_io( WsErrKind::InvalidUrl( "x".to_string() )        ).kind() );
+	assert_eq!( io::ErrorKind::Other       , to_io( WsErrKind::InvalidCloseCode( 5 )                ).kind() );
+	assert_eq!( io::ErrorKind::Other       , to_io( WsErrKind::ReasonStringToLong                   ).kind() );
+	assert_eq!( io::ErrorKind::Other       , to_io( WsErrKind::ConnectionFailed( close )            ).kind() );
+	assert_eq!( io::ErrorKind::Other       , to_io( WsErrKind::BlobRead( "x".to_string() )          ).kind() );
+	assert_eq!( io::ErrorKind::Other       , to_io( WsErrKind::Browser( "x".to_string() )           ).kind() );
+	assert_eq!( io::ErrorKind::Other       , to_io( WsErrKind::SendBufferFull                       ).kind() );
+	assert_eq!( io::ErrorKind::Other       , to_io( WsErrKind::HandshakeRejected( "x".to_string() ) ).kind() );
+	assert_eq!( io::ErrorKind::Other       , to_io( WsErrKind::SharedUnavailable                    ).kind() );
+	assert_eq!( io::ErrorKind::Other       , to_io( WsErrKind::ProtocolNegotiationFailed{ requested: vec![ "chat".to_string() ], got: String::new() } ).kind() );
+
+	assert_eq!( io::ErrorKind::ConnectionAborted, to_io( WsErrKind::ConnectAborted   ).kind() );
+	assert_eq!( io::ErrorKind::TimedOut         , to_io( WsErrKind::Timeout          ).kind() );
+	assert_eq!( io::ErrorKind::TimedOut         , to_io( WsErrKind::HeartbeatTimeout ).kind() );
+	assert_eq!( io::ErrorKind::InvalidInput     , to_io( WsErrKind::Encode( "x".to_string() ) ).kind() );
+}
+
+
+
+// The WsErr can be recovered from the io::Error
+//
+#[ test ]
+//
+fn round_trip()
+{
+	let err = to_io( WsErrKind::InvalidUrl( "http://example.com".to_string() ) );
+
+	assert_eq!
+	(
+		Some( &WsErrKind::InvalidUrl( "http://example.com".to_string() ) ),
+		WsErr::from_io_error( &err ).map( WsErr::kind )
+	);
+
+	// The message isn't lost either.
+	//
+	assert!( err.to_string().contains( "http://example.com" ) );
+}
+
+
+
+// from_io_error returns None for io errors that don't wrap a WsErr
+//
+#[ test ]
+//
+fn foreign_io_error()
+{
+	assert!( WsErr::from_io_error( &io::Error::from( io::ErrorKind::NotConnected )       ).is_none() );
+	assert!( WsErr::from_io_error( &io::Error::new ( io::ErrorKind::InvalidInput, "something" ) ).is_none() );
+}
@@ -0,0 +1,150 @@
+#![ cfg( feature = "js_api" ) ]
+#![ feature( async_await, trait_alias )]
+wasm_bindgen_test_configure!(run_in_browser);
+
+
+
+// What's tested:
+//
+// Tests send to an echo server which just bounces back all data. Run them with `--features js_api`.
+// The JavaScript side is in the snippet below, it only uses the exported class.
+//
+// ✔ JavaScript sends text and binary and receives them with onMessage, while rust still reads the WsIo.
+// ✔ close resolves with the close event, onClose is called, readyState is CLOSED and send rejects with a WsErr.
+// ✔ The static connect opens a connection that JavaScript can use on it's own.
+//
+use
+{
+	futures_01            :: Future as Future01,
+	futures::prelude      :: * ,
+	futures::compat       :: Future01CompatExt ,
+	js_sys                :: { Array, Promise, Uint8Array } ,
+	wasm_bindgen::prelude :: * ,
+	wasm_bindgen_futures  :: JsFuture ,
+	wasm_bindgen_test     :: * ,
+	ws_stream_wasm        :: * ,
+	log                   :: * ,
+};
+
+
+
+const URL_TT: &str = "ws://127.0.0.1:3312/";
+
+
+
+#[ wasm_bindgen( inline_js = "
+
+	export function echo( conn )
+	{
+		return new Promise( ( resolve, reject ) =>
+		{
+			let got = [];
+
+			conn.onMessage( msg =>
+			{
+				got.push( msg );
+
+				if( got.length === 2 ) resolve( got );
+			});
+
+			conn.send( 'hello'                  ).catch( reject );
+			conn.send( new Uint8Array([ 1, 2, 3 ]) ).catch( reject );
+		});
+	}
+
+
+	export async function close( conn )
+	{
+		let closed = new Promise( resolve => conn.onClose( resolve ) );
+		let evt    = await conn.close( 1000, 'bye' );
+		let seen   = await closed;
+		let kind   = await conn.send( 'late' ).then( () => null, e => e.kind );
+
+		return [ conn.readyState, evt.code, evt.reason, seen.code, kind ];
+	}
+
+" ) ]
+//
+extern "C"
+{
+	fn echo ( conn: &JsValue ) -> Promise;
+	fn close( conn: &JsValue ) -> Promise;
+}
+
+
+
+async fn run( promise: Promise ) -> JsValue
+{
+	JsFuture::from( promise ).compat().await.expect_throw( "promise rejected" )
+}
+
+
+
+// Let JavaScript use the connection and check what it saw.
+//
+async fn exercise( conn: &JsValue )
+{
+	let got: Array = run( echo( conn ) ).await.into();
+
+	assert_eq!( Some( "hello".to_string() ), got.get( 0 ).as_string() );
+	assert_eq!( vec![ 1, 2, 3 ], Uint8Array::from( got.get( 1 ) ).to_vec() );
+
+
+	let res: Array = run( close( conn ) ).await.into();
+
+	assert_eq!( Some( 3.0                              ), res.get( 0 ).as_f64()    );
+	assert_eq!( Some( 1000.0                           ), res.get( 1 ).as_f64()    );
+	assert_eq!( Some( "bye".to_string()                ), res.get( 2 ).as_string() );
+	assert_eq!( Some( 1000.0                           ), res.get( 3 ).as_f64()    );
+	assert_eq!( Some( "ConnectionNotOpen".to_string()  ), res.get( 4 ).as_string() );
+}
+
+
+
+// JavaScript sends text and binary and receives them with onMessage, while rust still reads the WsIo.
+// close resolves with the close event, onClose is called, readyState is CLOSED and send rejects with a WsErr.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn shared_with_js() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: shared_with_js" );
+
+	async
+	{
+		let ( ws, mut wsio ) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let conn             = JsValue::from( JsWsConnection::new( &ws, &wsio ) );
+
+		exercise( &conn ).await;
+
+		assert_eq!( WsMessage::from( "hello" ), wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "receive" ) );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// The static connect opens a connection that JavaScript can use on it's own.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn connect_from_js() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: connect_from_js" );
+
+	async
+	{
+		let conn = run( JsWsConnection::connect( URL_TT.to_string() ) ).await;
+
+		exercise( &conn ).await;
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
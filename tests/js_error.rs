@@ -0,0 +1,95 @@
+#![ feature( async_await, trait_alias )]
+wasm_bindgen_test_configure!(run_in_browser);
+
+
+
+// What's tested:
+//
+// These run in the browser, because they create JavaScript objects, but they don't need a backend.
+//
+// ✔ A WsErr becomes a JavaScript Error with name, message and kind.
+// ✔ The close code and the sizes are available as properties.
+// ✔ An exception of the browser is kept in the WsErr and becomes the cause of the Error.
+//
+use
+{
+	js_sys                :: { Error, Reflect } ,
+	web_sys               :: { DomException   } ,
+	wasm_bindgen::prelude :: * ,
+	wasm_bindgen_test     :: * ,
+	ws_stream_wasm        :: * ,
+};
+
+
+
+fn prop( obj: &JsValue, key: &str ) -> JsValue
+{
+	Reflect::get( obj, &key.into() ).expect_throw( "get property" )
+}
+
+
+
+// A WsErr becomes a JavaScript Error with name, message and kind.
+//
+#[ wasm_bindgen_test ]
+//
+fn error_shape()
+{
+	let err = WsErr::from( WsErrKind::ConnectionNotOpen );
+	let msg = err.to_string();
+	let js  = JsValue::from( err );
+
+	assert!( js.is_instance_of::<Error>() );
+
+	assert_eq!( Some( "WsErr"            .to_string() ), prop( &js, "name"    ).as_string() );
+	assert_eq!( Some( msg                             ), prop( &js, "message" ).as_string() );
+	assert_eq!( Some( "ConnectionNotOpen".to_string() ), prop( &js, "kind"    ).as_string() );
+}
+
+
+
+// The close code and the sizes are available as properties.
+//
+#[ wasm_bindgen_test ]
+//
+fn error_fields()
+{
+	let js = JsValue::from( WsErr::from( WsErrKind::InvalidCloseCode( 5 ) ) );
+
+	assert_eq!( Some( "InvalidCloseCode".to_string() ), prop( &js, "kind" ).as_string() );
+	assert_eq!( Some( 5.0 )                          , prop( &js, "code" ).as_f64()    );
+
+
+	let js = JsValue::from( WsErr::from( WsErrKind::MessageTooBig{ size: 20, limit: 10 } ) );
+
+	assert_eq!( Some( 20.0 ), prop( &js, "size"  ).as_f64() );
+	assert_eq!( Some( 10.0 ), prop( &js, "limit" ).as_f64() );
+}
+
+
+
+// An exception of the browser is kept in the WsErr and becomes the cause of the Error.
+//
+#[ wasm_bindgen_test ]
+//
+fn error_cause()
+{
+	let dom: JsValue = DomException::new_with_message( "boom" ).expect_throw( "DomException" ).into();
+	let err          = WsErr::from( dom.clone() );
+
+	assert!( matches!( err.kind(), WsErrKind::Browser(_) ) );
+	assert_eq!( Some( &dom ), err.js_error().as_ref() );
+
+	let js = JsValue::from( err );
+
+	assert_eq!( Some( "Browser".to_string() ), prop( &js, "kind" ).as_string() );
+	assert_eq!( dom, prop( &js, "cause" ) );
+	assert!( prop( &js, "cause" ).is_instance_of::<DomException>() );
+
+
+	// Other errors have no cause.
+	//
+	let js = JsValue::from( WsErr::from( WsErrKind::ConnectionNotOpen ) );
+
+	assert!( prop( &js, "cause" ).is_undefined() );
+}
@@ -0,0 +1,130 @@
+#![ cfg( feature = "mock" ) ]
+
+// What's tested:
+//
+// These don't need a backend, so they are regular tests. Run them with `--features mock`.
+//
+// ✔ A sleep only resolves once the clock passed it's deadline.
+// ✔ Clones share the clock and a sleep counts from when it was created.
+// ✔ Sleeps of different length resolve in order.
+// ✔ A sleep keeps one waker however often it's polled, and none once it's done or dropped.
+//
+use
+{
+	futures        :: { executor::block_on, future::{ select, ready, Either, LocalBoxFuture } } ,
+	std            :: { time::Duration                                                } ,
+	ws_stream_wasm :: { MockTimer, Timer                                              } ,
+};
+
+
+
+fn is_done( fut: &mut LocalBoxFuture<'static, ()> ) -> bool
+{
+	matches!( block_on( select( fut, ready(()) ) ), Either::Left(_) )
+}
+
+
+
+// A sleep only resolves once the clock passed it's deadline.
+//
+#[ test ]
+//
+fn deadline()
+{
+	let timer     = MockTimer::new();
+	let mut sleep = timer.sleep( Duration::from_millis( 500 ) );
+
+	assert!( !is_done( &mut sleep ) );
+
+	timer.advance( Duration::from_millis( 499 ) );
+	assert!( !is_done( &mut sleep ) );
+
+	timer.advance( Duration::from_millis( 1 ) );
+	assert!( is_done( &mut sleep ) );
+
+	assert_eq!( Duration::from_millis( 500 ), timer.now() );
+}
+
+
+
+// Clones share the clock and a sleep counts from when it was created.
+//
+#[ test ]
+//
+fn shared_clock()
+{
+	let timer = MockTimer::new();
+	let clone = timer.clone();
+
+	timer.advance( Duration::from_secs( 10 ) );
+
+	let mut sleep = clone.sleep( Duration::from_secs( 1 ) );
+
+	assert_eq!( Duration::from_secs( 10 ), clone.now() );
+	assert!( !is_done( &mut sleep ) );
+
+	timer.advance( Duration::from_secs( 1 ) );
+	assert!( is_done( &mut sleep ) );
+}
+
+
+
+// Sleeps of different length resolve in order.
+//
+#[ test ]
+//
+fn order()
+{
+	let timer     = MockTimer::new();
+	let mut short = timer.sleep( Duration::from_secs( 1 ) );
+	let mut long  = timer.sleep( Duration::from_secs( 2 ) );
+
+	assert!( !is_done( &mut short ) );
+	assert!( !is_done( &mut long  ) );
+
+	timer.advance( Duration::from_secs( 1 ) );
+
+	assert!(  is_done( &mut short ) );
+	assert!( !is_done( &mut long  ) );
+
+	timer.advance( Duration::from_secs( 1 ) );
+
+	assert!( is_done( &mut long ) );
+}
+
+
+
+// A sleep keeps one waker however often it's polled, and none once it's done or dropped.
+//
+#[ test ]
+//
+fn wakers()
+{
+	let timer     = MockTimer::new();
+	let mut sleep = timer.sleep( Duration::from_secs( 1 ) );
+	let mut other = timer.sleep( Duration::from_secs( 2 ) );
+
+	for _ in 0..100 { assert!( !is_done( &mut sleep ) ); }
+
+	assert!( !is_done( &mut other ) );
+	assert_eq!( 2, timer.pending() );
+
+	drop( other );
+	assert_eq!( 1, timer.pending() );
+
+	timer.advance( Duration::from_secs( 1 ) );
+	assert_eq!( 0, timer.pending() );
+	assert!( is_done( &mut sleep ) );
+
+
+	// A sleep that is dropped without ever being due doesn't stay behind either.
+	//
+	for _ in 0..100
+	{
+		let mut sleep = timer.sleep( Duration::from_secs( 5 ) );
+
+		assert!( !is_done( &mut sleep ) );
+	}
+
+	assert_eq!( 0, timer.pending() );
+}
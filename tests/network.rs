@@ -0,0 +1,114 @@
+#![ feature( async_await, trait_alias )]
+wasm_bindgen_test_configure!(run_in_browser);
+
+
+
+// What's tested:
+//
+// We can't take the test machine offline, so we dispatch synthetic online/offline events on the window.
+//
+// ✔ online and offline events are forwarded to the observers of WsStream.
+// ✔ connect_retry doesn't wait for the rest of the delay when the network comes back.
+//
+use
+{
+	futures_01            :: Future as Future01,
+	futures::prelude      :: * ,
+	futures::channel      :: mpsc::unbounded ,
+	futures::future       :: join ,
+	pharos                :: * ,
+	wasm_bindgen::prelude :: * ,
+	wasm_bindgen_test     :: * ,
+	ws_stream_wasm        :: * ,
+	log                   :: * ,
+	js_sys                :: Date ,
+	web_sys               :: { window, Event } ,
+	std                   :: time::Duration ,
+};
+
+
+
+const URL      : &str = "ws://127.0.0.1:3212/";
+const URL_WRONG: &str = "ws://127.0.0.1:33212/";
+
+
+
+fn dispatch( name: &str )
+{
+	let evt = Event::new( name ).expect_throw( "create event" );
+
+	window().expect_throw( "window" ).dispatch_event( &evt ).expect_throw( "dispatch event" );
+}
+
+
+
+// online and offline events are forwarded to the observers of WsStream.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn network_events() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: network_events" );
+
+	async
+	{
+		let (mut ws, _wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
+		let mut evts        = ws.observe_unbounded();
+
+		dispatch( "offline" );
+		dispatch( "online"  );
+
+		assert_eq!( WsEvent::NetworkOffline, evts.next().await.expect_throw( "event" ) );
+		assert_eq!( WsEvent::NetworkOnline , evts.next().await.expect_throw( "event" ) );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// connect_retry doesn't wait for the rest of the delay when the network comes back.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn retry_when_online() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: retry_when_online" );
+
+	async
+	{
+		let (tx, mut rx) = unbounded();
+
+		let policy = RetryPolicy::default()
+
+			.delays      ( vec![ Duration::from_secs( 10 ) ]                  )
+			.max_attempts( 2                                                 )
+			.on_progress ( move |p| tx.unbounded_send( p.attempt ).unwrap_throw() )
+		;
+
+		let start = Date::now();
+
+		let connect = WsStream::connect_retry( URL_WRONG, policy );
+
+		let wake = async move
+		{
+			// The first attempt failed, connect_retry is now sleeping.
+			//
+			rx.next().await;
+			dispatch( "online" );
+		};
+
+		let (res, _) = join( connect, wake ).await;
+
+		assert!( res.is_err() );
+		assert!( Date::now() - start < 5000.0 );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
@@ -0,0 +1,79 @@
+#![ feature( async_await, trait_alias )]
+wasm_bindgen_test_configure!(run_in_browser);
+
+
+
+// What's tested:
+//
+// Tests send to an echo server which just bounces back all data. Replay itself is tested in tests/replay.rs.
+//
+// ✔ Messages and events are recorded in order and the recording replays the session
+//
+use
+{
+	futures_01            :: Future as Future01,
+	futures::prelude      :: * ,
+	wasm_bindgen::prelude :: * ,
+	wasm_bindgen_test     :: * ,
+	ws_stream_wasm        :: * ,
+	log                   :: * ,
+	std                   :: { time::Duration } ,
+};
+
+
+
+const URL_TT: &str = "ws://127.0.0.1:3312/";
+
+
+
+// Messages and events are recorded in order and the recording replays the session
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn record_replay() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: record_replay" );
+
+	async
+	{
+		let (ws, mut wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let recorder       = Recorder::start( &ws, &wsio );
+
+		wsio.send( WsMessage::from( "hi" ) ).await.expect_throw( "send" );
+		wsio.next().await.unwrap_throw().expect_throw( "echo" );
+
+		let evt = ws.close().await;
+
+		// Events are forwarded by a task, give it a chance to run.
+		//
+		JsTimer.sleep( Duration::from_millis( 10 ) ).await;
+
+		let recording = recorder.recording();
+		let items: Vec<_> = recording.entries.iter().map( |e| e.item.clone() ).collect();
+
+		assert_eq!( vec!
+		[
+			Recorded::Sent    ( WsMessage::from( "hi" ) ),
+			Recorded::Received( WsMessage::from( "hi" ) ),
+			Recorded::Event   ( WsEvent::Closing        ),
+			Recorded::Event   ( WsEvent::Close( evt )   ),
+		],
+		items );
+
+		assert!( recording.entries.windows( 2 ).all( |w| w[0].at <= w[1].at ) );
+
+
+		let mut replay = Replay::new( recording );
+
+		replay.send( WsMessage::from( "hi" ) ).await.expect_throw( "matches" );
+
+		assert_eq!( WsMessage::from( "hi" ), replay.next().await.unwrap_throw().expect_throw( "replayed" ) );
+		assert!( replay.next().await.is_none() );
+		assert!( replay.unsent().is_empty() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
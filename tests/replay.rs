@@ -0,0 +1,192 @@
+// What's tested:
+//
+// These don't need a backend, so they are regular tests.
+// The recording of the browser side is tested in tests/record.rs.
+//
+// ✔ Received messages are yielded once the messages sent before them were sent
+// ✔ Strict matching fails on a wrong message and on one too many
+// ✔ Lenient matching counts mismatches
+// ✔ A recorded close event ends the stream
+// ✔ Timing::Original waits like the recording did, on a MockTimer
+// ✔ A recording survives serialization
+//
+use
+{
+	futures        :: { executor::block_on, future::{ ready, select, Either }, prelude::* } ,
+	ws_stream_wasm :: { *                                                                } ,
+	std            :: { time::Duration                                                   } ,
+};
+
+
+
+fn text( t: &str ) -> WsMessage
+{
+	WsMessage::from( t )
+}
+
+
+fn entry( ms: u64, item: Recorded ) -> Entry
+{
+	Entry { at: Duration::from_millis( ms ), item }
+}
+
+
+// hello, then a request and it's answer and the connection closes.
+//
+fn recording() -> Recording
+{
+	Recording { entries: vec!
+	[
+		entry(  0, Recorded::Event   ( WsEvent::Open         ) ),
+		entry(  1, Recorded::Received( text( "hello" )       ) ),
+		entry( 10, Recorded::Sent    ( text( "question" )    ) ),
+		entry( 20, Recorded::Received( text( "answer" )      ) ),
+		entry( 30, Recorded::Event   ( WsEvent::Close( CloseEvent{ code: 1000, reason: String::new(), was_clean: true } ) ) ),
+		entry( 40, Recorded::Received( text( "too late" )    ) ),
+	]}
+}
+
+
+
+// Received messages are yielded once the messages sent before them were sent
+//
+#[ test ]
+//
+fn follows_sends()
+{
+	let mut replay = Replay::new( recording() );
+
+	block_on( async
+	{
+		assert_eq!( text( "hello" ), replay.next().await.unwrap().unwrap() );
+
+		// The answer waits for the question.
+		//
+		assert!( matches!( select( replay.next(), ready(()) ).await, Either::Right(_) ) );
+
+		replay.send( text( "question" ) ).await.expect( "matches" );
+
+		assert!( replay.unsent().is_empty() );
+		assert_eq!( text( "answer" ), replay.next().await.unwrap().unwrap() );
+	});
+}
+
+
+
+// Strict matching fails on a wrong message and on one too many
+//
+#[ test ]
+//
+fn strict()
+{
+	let mut replay = Replay::new( recording() );
+
+	block_on( async
+	{
+		assert_eq!( vec![ text( "question" ) ], replay.unsent() );
+
+		let err = replay.send( text( "wrong" ) ).await.unwrap_err();
+
+		assert_eq!( &WsErrKind::ReplayMismatch{ expected: Some( text( "question" ) ), got: text( "wrong" ) }, err.kind() );
+
+		let err = replay.send( text( "more" ) ).await.unwrap_err();
+
+		assert_eq!( &WsErrKind::ReplayMismatch{ expected: None, got: text( "more" ) }, err.kind() );
+	});
+}
+
+
+
+// Lenient matching counts mismatches
+//
+#[ test ]
+//
+fn lenient()
+{
+	let mut replay = Replay::new( recording() ).matching( Matching::Lenient );
+
+	block_on( async
+	{
+		replay.send( text( "wrong" ) ).await.expect( "lenient" );
+		replay.send( text( "more"  ) ).await.expect( "lenient" );
+
+		assert_eq!( 2, replay.mismatches() );
+
+		// The wrong message still stands in for the question.
+		//
+		assert_eq!( text( "hello"  ), replay.next().await.unwrap().unwrap() );
+		assert_eq!( text( "answer" ), replay.next().await.unwrap().unwrap() );
+	});
+}
+
+
+
+// A recorded close event ends the stream
+//
+#[ test ]
+//
+fn close_ends()
+{
+	let mut replay = Replay::new( recording() );
+
+	block_on( async
+	{
+		replay.send( text( "question" ) ).await.expect( "matches" );
+
+		let msgs: Vec<_> = replay.map( Result::unwrap ).collect().await;
+
+		assert_eq!( vec![ text( "hello" ), text( "answer" ) ], msgs );
+	});
+}
+
+
+
+// Timing::Original waits like the recording did, on a MockTimer
+//
+#[ cfg( feature = "mock" ) ]
+//
+#[ test ]
+//
+fn original_timing()
+{
+	let timer      = MockTimer::new();
+	let mut replay = Replay::new( recording() ).timing( Timing::Original ).timer( timer.clone() );
+
+	block_on( async
+	{
+		assert!( matches!( select( replay.next(), ready(()) ).await, Either::Right(_) ) );
+
+		timer.advance( Duration::from_millis( 1 ) );
+
+		assert_eq!( text( "hello" ), replay.next().await.unwrap().unwrap() );
+
+		replay.send( text( "question" ) ).await.expect( "matches" );
+
+		// The answer came 19ms after the hello, sends don't move the clock. The wait starts when we poll.
+		//
+		assert!( matches!( select( replay.next(), ready(()) ).await, Either::Right(_) ) );
+
+		timer.advance( Duration::from_millis( 18 ) );
+
+		assert!( matches!( select( replay.next(), ready(()) ).await, Either::Right(_) ) );
+
+		timer.advance( Duration::from_millis( 1 ) );
+
+		assert_eq!( text( "answer" ), replay.next().await.unwrap().unwrap() );
+	});
+}
+
+
+
+// A recording survives serialization
+//
+#[ cfg( feature = "cbor" ) ]
+//
+#[ test ]
+//
+fn serialize()
+{
+	let bytes = serde_cbor::to_vec( &recording() ).expect( "serialize" );
+
+	assert_eq!( recording(), serde_cbor::from_slice::<Recording>( &bytes ).expect( "deserialize" ) );
+}
@@ -0,0 +1,351 @@
+#![ feature( async_await, trait_alias )]
+wasm_bindgen_test_configure!(run_in_browser);
+
+
+
+// What's tested:
+//
+// Tests connect to an echo server which just bounces back all data.
+//
+// ✔ connect_retry succeeds on the first attempt without reporting progress
+// ✔ connect_retry_with_config and connect_failover_with_config connect with the config
+// ✔ connect_retry gives up after max_attempts and reports every attempt
+// ✔ connect_retry stops on an abort code
+// ✔ connect_retry doesn't retry a forbidden port
+// ✔ connect_failover with round robin moves on to the next url after every failure
+// ✔ connect_failover with sticky tries each url n times before moving on
+// ✔ connect_failover takes a forbidden port out of the rotation
+// ✔ status_signal follows the attempts and ends when the policy is dropped
+//
+use
+{
+	futures_01            :: Future as Future01,
+	futures::prelude      :: * ,
+	futures::future       :: join ,
+	wasm_bindgen::prelude :: * ,
+	wasm_bindgen_test     :: * ,
+	ws_stream_wasm        :: * ,
+	log                   :: * ,
+	std                   :: { rc::Rc, cell::RefCell, time::Duration } ,
+};
+
+
+
+const URL       : &str = "ws://127.0.0.1:3212/";
+const URL_WRONG : &str = "ws://127.0.0.1:33212/";
+const URL_WRONG2: &str = "ws://127.0.0.1:33213/";
+
+
+
+fn recording_policy() -> ( RetryPolicy, Rc<RefCell< Vec<RetryProgress> >> )
+{
+	let progress = Rc::new( RefCell::new( Vec::new() ) );
+	let p2       = progress.clone();
+
+	let policy = RetryPolicy::default()
+
+		.delays     ( vec![ Duration::from_millis( 10 ) ]    )
+		.on_progress( move |p| p2.borrow_mut().push( p.clone() ) )
+	;
+
+	( policy, progress )
+}
+
+
+
+// connect_retry succeeds on the first attempt without reporting progress
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn retry_success() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: retry_success" );
+
+	async
+	{
+		let (policy, progress) = recording_policy();
+
+		let (ws, _wsio) = WsStream::connect_retry( URL, policy ).await.expect_throw( "Could not create websocket" );
+
+		assert_eq!( WsState::Open, ws.ready_state() );
+		assert!   ( progress.borrow().is_empty()    );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// connect_retry_with_config and connect_failover_with_config connect with the config
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn retry_config() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: retry_config" );
+
+	async
+	{
+		let config = WsConfig::default().max_send_size( 4 );
+
+		let (ws, _wsio) = WsStream::connect_retry_with_config( URL, recording_policy().0, config.clone() ).await
+
+			.expect_throw( "Could not create websocket" );
+
+		let err = ws.send_text( "hello" ).await.unwrap_err();
+
+		assert_eq!( &WsErrKind::MessageTooBig{ size: 5, limit: 4 }, err.kind() );
+
+
+		let (ws, _wsio) = WsStream::connect_failover_with_config( &[ URL_WRONG, URL ], recording_policy().0, config ).await
+
+			.expect_throw( "Could not create websocket" );
+
+		let err = ws.send_text( "hello" ).await.unwrap_err();
+
+		assert_eq!( URL, ws.url() );
+		assert_eq!( &WsErrKind::MessageTooBig{ size: 5, limit: 4 }, err.kind() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// connect_retry gives up after max_attempts and reports every attempt
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn retry_max_attempts() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: retry_max_attempts" );
+
+	async
+	{
+		let (policy, progress) = recording_policy();
+
+		let err = WsStream::connect_retry( URL_WRONG, policy.max_attempts( 3 ) ).await.unwrap_err();
+
+		match err.kind()
+		{
+			WsErrKind::ConnectionFailed( evt ) => assert_eq!( 1006, evt.code ),
+			_                                  => panic!( "wrong error: {}", err ),
+		}
+
+		let progress = progress.borrow();
+
+		assert_eq!( 3, progress.len() );
+
+		assert_eq!( 1, progress[0].attempt );
+		assert_eq!( 2, progress[1].attempt );
+		assert_eq!( 3, progress[2].attempt );
+
+		assert_eq!( Some( Duration::from_millis( 10 ) ), progress[0].next_delay );
+		assert_eq!( None                               , progress[2].next_delay );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// connect_retry stops on an abort code
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn retry_abort_code() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: retry_abort_code" );
+
+	async
+	{
+		let (policy, progress) = recording_policy();
+
+		let res = WsStream::connect_retry( URL_WRONG, policy.abort_on( 1006 ) ).await;
+
+		assert!   ( res.is_err()                          );
+		assert_eq!( 1   , progress.borrow().len()         );
+		assert_eq!( None, progress.borrow()[0].next_delay );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// connect_retry doesn't retry a forbidden port
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn retry_forbidden_port() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: retry_forbidden_port" );
+
+	async
+	{
+		let (policy, progress) = recording_policy();
+
+		let err = WsStream::connect_retry( "ws://127.0.0.1:6666/", policy ).await.unwrap_err();
+
+		assert_eq!( &WsErrKind::ForbiddenPort, err.kind()              );
+		assert_eq!( 1                        , progress.borrow().len() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+fn urls( progress: &[ RetryProgress ] ) -> Vec<&str>
+{
+	progress.iter().map( |p| p.url.as_str() ).collect()
+}
+
+
+
+// connect_failover with round robin moves on to the next url after every failure
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn failover_round_robin() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: failover_round_robin" );
+
+	async
+	{
+		let (policy, progress) = recording_policy();
+
+		let (ws, _wsio) = WsStream::connect_failover( &[ URL_WRONG, URL_WRONG2, URL ], policy ).await
+
+			.expect_throw( "Could not create websocket" );
+
+		assert_eq!( URL                          , ws.url()                      );
+		assert_eq!( vec![ URL_WRONG, URL_WRONG2 ], urls( &progress.borrow() )    );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// connect_failover with sticky tries each url n times before moving on
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn failover_sticky() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: failover_sticky" );
+
+	async
+	{
+		let (policy, progress) = recording_policy();
+		let policy             = policy.failover( Failover::Sticky( 2 ) ).max_attempts( 5 );
+
+		let res = WsStream::connect_failover( &[ URL_WRONG, URL_WRONG2 ], policy ).await;
+
+		assert!( res.is_err() );
+
+		assert_eq!
+		(
+			vec![ URL_WRONG, URL_WRONG, URL_WRONG2, URL_WRONG2, URL_WRONG ],
+			urls( &progress.borrow() )
+		);
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// connect_failover takes a forbidden port out of the rotation
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn failover_forbidden_port() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: failover_forbidden_port" );
+
+	async
+	{
+		let (policy, progress) = recording_policy();
+		let policy             = policy.max_attempts( 3 );
+
+		let forbidden = "ws://127.0.0.1:6666/";
+
+		let res = WsStream::connect_failover( &[ forbidden, URL_WRONG ], policy ).await;
+
+		assert!( res.is_err() );
+
+		assert_eq!( vec![ forbidden, URL_WRONG, URL_WRONG ], urls( &progress.borrow() ) );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// status_signal follows the attempts and ends when the policy is dropped
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn retry_status() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: retry_status" );
+
+	async
+	{
+		let (policy, _) = recording_policy();
+		let status      = policy.status_signal();
+
+		let ( res, status ) = join
+		(
+			WsStream::connect_retry( URL_WRONG, policy.max_attempts( 2 ) ),
+			status.collect::<Vec<_>>(),
+		).await;
+
+		assert!( res.is_err() );
+
+		assert_eq!
+		(
+			vec!
+			[
+				RetryStatus::Idle,
+				RetryStatus::Connecting{ attempt: 1, url: URL_WRONG.to_string() },
+				RetryStatus::Waiting   { attempt: 1, next_retry: Duration::from_millis( 10 ) },
+				RetryStatus::Connecting{ attempt: 2, url: URL_WRONG.to_string() },
+				RetryStatus::GaveUp    { attempt: 2 },
+			],
+
+			status
+		);
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
@@ -0,0 +1,345 @@
+// What's tested:
+//
+// These don't need a backend, so they are regular tests.
+// A channel stands in for the new connection.
+//
+// ✔ Flushed messages keep their order
+// ✔ max_messages with Overflow::Error and Overflow::DropOldest
+// ✔ max_messages( 0 ) keeps one message with Overflow::DropOldest
+// ✔ max_bytes counts UTF-8 bytes and refuses messages that can never fit
+// ✔ clear drops everything
+// ✔ A failed flush keeps the messages
+// ✔ High priority messages are flushed first, keeping the order within a lane
+// ✔ fairness lets normal messages through
+// ✔ DropOldest drops normal messages before high priority ones
+// ✔ A message flushed within it's ttl is sent                        (feature mock)
+// ✔ Expired messages are dropped, counted and don't count to limits  (feature mock)
+// ✔ A message that expires while flush waits for the sink is dropped (feature mock)
+//
+use
+{
+	futures        :: { channel::mpsc, executor::block_on, prelude::* } ,
+	ws_stream_wasm :: { *                                             } ,
+};
+
+#[ cfg( feature = "mock" ) ]
+//
+use
+{
+	futures :: { future::{ select, ready, Either } } ,
+	std     :: { time::Duration                    } ,
+};
+
+
+
+fn text( t: &str ) -> WsMessage
+{
+	WsMessage::from( t )
+}
+
+
+
+// Flushed messages keep their order
+//
+#[ test ]
+//
+fn order()
+{
+	let mut buffer     = SendBuffer::new();
+	let ( mut tx, rx ) = mpsc::unbounded();
+
+	for t in &[ "a", "b", "c" ] { buffer.push( text( t ) ).expect( "push" ) }
+
+	assert_eq!( 3, buffer.len  () );
+	assert_eq!( 3, buffer.bytes() );
+
+	block_on( buffer.flush( &mut tx ) ).expect( "flush" );
+	drop( tx );
+
+	assert!( buffer.is_empty() );
+	assert_eq!( vec![ text( "a" ), text( "b" ), text( "c" ) ], block_on( rx.collect::<Vec<_>>() ) );
+}
+
+
+
+// max_messages with Overflow::Error and Overflow::DropOldest
+//
+#[ test ]
+//
+fn max_messages()
+{
+	let mut buffer = SendBuffer::new().max_messages( 2 );
+
+	buffer.push( text( "a" ) ).expect( "push" );
+	buffer.push( text( "b" ) ).expect( "push" );
+
+	assert_eq!( &WsErrKind::SendBufferFull, buffer.push( text( "c" ) ).unwrap_err().kind() );
+	assert_eq!( 2, buffer.len() );
+
+
+	let mut buffer     = SendBuffer::new().max_messages( 2 ).overflow( Overflow::DropOldest );
+	let ( mut tx, rx ) = mpsc::unbounded();
+
+	for t in &[ "a", "b", "c" ] { buffer.push( text( t ) ).expect( "push" ) }
+
+	block_on( buffer.flush( &mut tx ) ).expect( "flush" );
+	drop( tx );
+
+	assert_eq!( vec![ text( "b" ), text( "c" ) ], block_on( rx.collect::<Vec<_>>() ) );
+}
+
+
+
+// max_messages( 0 ) keeps one message with Overflow::DropOldest
+//
+#[ test ]
+//
+fn max_messages_zero()
+{
+	let mut buffer     = SendBuffer::new().max_messages( 0 ).overflow( Overflow::DropOldest );
+	let ( mut tx, rx ) = mpsc::unbounded();
+
+	buffer.push( text( "a" ) ).expect( "push" );
+	buffer.push( text( "b" ) ).expect( "push" );
+
+	assert_eq!( 1, buffer.len() );
+
+	block_on( buffer.flush( &mut tx ) ).expect( "flush" );
+	drop( tx );
+
+	assert_eq!( vec![ text( "b" ) ], block_on( rx.collect::<Vec<_>>() ) );
+}
+
+
+
+
+// max_bytes counts UTF-8 bytes and refuses messages that can never fit
+//
+#[ test ]
+//
+fn max_bytes()
+{
+	let mut buffer = SendBuffer::new().max_bytes( 4 ).overflow( Overflow::DropOldest );
+
+	buffer.push( WsMessage::Binary( vec![ 1, 2 ] ) ).expect( "push" );
+
+	// 2 bytes in UTF-8, so the binary message has to go.
+	//
+	buffer.push( text( "é"  ) ).expect( "push" );
+	buffer.push( text( "éé" ) ).expect( "push" );
+
+	assert_eq!( 1, buffer.len  () );
+	assert_eq!( 4, buffer.bytes() );
+
+	assert_eq!( &WsErrKind::SendBufferFull, buffer.push( text( "12345" ) ).unwrap_err().kind() );
+	assert_eq!( 1, buffer.len() );
+}
+
+
+
+// clear drops everything
+//
+#[ test ]
+//
+fn clear()
+{
+	let mut buffer = SendBuffer::new();
+
+	buffer.push( text( "a" ) ).expect( "push" );
+	buffer.clear();
+
+	assert!( buffer.is_empty() );
+	assert_eq!( 0, buffer.bytes() );
+}
+
+
+
+// A failed flush keeps the messages
+//
+#[ test ]
+//
+fn failed_flush()
+{
+	let mut buffer     = SendBuffer::new();
+	let ( mut tx, rx ) = mpsc::channel( 1 );
+
+	buffer.push( text( "a" ) ).expect( "push" );
+	buffer.push( text( "b" ) ).expect( "push" );
+
+	drop( rx );
+
+	assert!( block_on( buffer.flush( &mut tx ) ).is_err() );
+	assert_eq!( 2, buffer.len() );
+}
+
+
+
+// High priority messages are flushed first, keeping the order within a lane
+//
+#[ test ]
+//
+fn priority()
+{
+	let mut buffer     = SendBuffer::new();
+	let ( mut tx, rx ) = mpsc::unbounded();
+
+	buffer.push              ( text( "a" )                 ).expect( "push" );
+	buffer.push_with_priority( text( "1" ), Priority::High ).expect( "push" );
+	buffer.push              ( text( "b" )                 ).expect( "push" );
+	buffer.push_with_priority( text( "2" ), Priority::High ).expect( "push" );
+
+	block_on( buffer.flush( &mut tx ) ).expect( "flush" );
+	drop( tx );
+
+	assert_eq!
+	(
+		vec![ text( "1" ), text( "2" ), text( "a" ), text( "b" ) ],
+		block_on( rx.collect::<Vec<_>>() )
+	);
+}
+
+
+
+// fairness lets normal messages through
+//
+#[ test ]
+//
+fn fairness()
+{
+	let mut buffer     = SendBuffer::new().fairness( 2 );
+	let ( mut tx, rx ) = mpsc::unbounded();
+
+	for t in &[ "a", "b" ] { buffer.push( text( t ) ).expect( "push" ) }
+
+	for t in &[ "1", "2", "3", "4", "5" ]
+	{
+		buffer.push_with_priority( text( t ), Priority::High ).expect( "push" );
+	}
+
+	block_on( buffer.flush( &mut tx ) ).expect( "flush" );
+	drop( tx );
+
+	assert_eq!
+	(
+		vec![ text( "1" ), text( "2" ), text( "a" ), text( "3" ), text( "4" ), text( "b" ), text( "5" ) ],
+		block_on( rx.collect::<Vec<_>>() )
+	);
+}
+
+
+
+// DropOldest drops normal messages before high priority ones
+//
+#[ test ]
+//
+fn drop_normal_first()
+{
+	let mut buffer     = SendBuffer::new().max_messages( 2 ).overflow( Overflow::DropOldest );
+	let ( mut tx, rx ) = mpsc::unbounded();
+
+	buffer.push_with_priority( text( "1" ), Priority::High ).expect( "push" );
+	buffer.push              ( text( "a" )                 ).expect( "push" );
+	buffer.push_with_priority( text( "2" ), Priority::High ).expect( "push" );
+
+	block_on( buffer.flush( &mut tx ) ).expect( "flush" );
+	drop( tx );
+
+	assert_eq!( vec![ text( "1" ), text( "2" ) ], block_on( rx.collect::<Vec<_>>() ) );
+}
+
+
+
+// A message flushed within it's ttl is sent
+//
+#[ cfg( feature = "mock" ) ]
+#[ test ]
+//
+fn ttl_not_expired()
+{
+	let timer          = MockTimer::new();
+	let mut buffer     = SendBuffer::new().timer( timer.clone() );
+	let ( mut tx, rx ) = mpsc::unbounded();
+
+	buffer.push_with_ttl( text( "a" ), Duration::from_secs( 1 ) ).expect( "push" );
+
+	timer.advance( Duration::from_millis( 999 ) );
+
+	block_on( buffer.flush( &mut tx ) ).expect( "flush" );
+	drop( tx );
+
+	assert_eq!( vec![ text( "a" ) ], block_on( rx.collect::<Vec<_>>() ) );
+	assert_eq!( 0, buffer.expired() );
+}
+
+
+
+// Expired messages are dropped, counted and don't count to limits
+//
+#[ cfg( feature = "mock" ) ]
+#[ test ]
+//
+fn ttl_expired()
+{
+	let timer          = MockTimer::new();
+	let mut buffer     = SendBuffer::new().timer( timer.clone() ).max_messages( 2 );
+	let ( mut tx, rx ) = mpsc::unbounded();
+
+	buffer.push_with_ttl( text( "a" ), Duration::from_secs( 1 ) ).expect( "push" );
+	buffer.push_with_ttl( text( "b" ), Duration::from_secs( 3 ) ).expect( "push" );
+
+	timer.advance( Duration::from_secs( 2 ) );
+
+	// "a" makes room.
+	//
+	buffer.push( text( "c" ) ).expect( "push" );
+
+	assert_eq!( 2, buffer.len    () );
+	assert_eq!( 2, buffer.bytes  () );
+	assert_eq!( 1, buffer.expired() );
+
+	timer.advance( Duration::from_secs( 1 ) );
+
+	block_on( buffer.flush( &mut tx ) ).expect( "flush" );
+	drop( tx );
+
+	assert_eq!( vec![ text( "c" ) ], block_on( rx.collect::<Vec<_>>() ) );
+	assert_eq!( 2, buffer.expired() );
+	assert_eq!( 0, buffer.bytes  () );
+}
+
+
+
+// A message that expires while flush waits for the sink is dropped
+//
+#[ cfg( feature = "mock" ) ]
+#[ test ]
+//
+fn ttl_expired_while_waiting()
+{
+	let timer              = MockTimer::new();
+	let mut buffer         = SendBuffer::new().timer( timer.clone() );
+	let ( mut tx, mut rx ) = mpsc::channel( 0 );
+
+	buffer.push         ( text( "a" )                           ).expect( "push" );
+	buffer.push_with_ttl( text( "b" ), Duration::from_secs( 1 ) ).expect( "push" );
+
+	{
+		let mut flush = buffer.flush( &mut tx ).boxed_local();
+
+		// "a" takes the only slot in the channel, so flush waits with "b".
+		//
+		assert!( matches!( block_on( select( &mut flush, ready(()) ) ), Either::Right(_) ) );
+
+		timer.advance( Duration::from_secs( 1 ) );
+
+		assert_eq!( Some( text( "a" ) ), block_on( rx.next() ) );
+
+		block_on( flush ).expect( "flush" );
+	}
+
+	drop( tx );
+
+	assert_eq!( None, block_on( rx.next() ) );
+	assert_eq!( 1   , buffer.expired()     );
+	assert!( buffer.is_empty() );
+}
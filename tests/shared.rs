@@ -0,0 +1,128 @@
+#![ feature( async_await, trait_alias )]
+wasm_bindgen_test_configure!(run_in_browser);
+
+
+
+// What's tested:
+//
+// Tests send to an echo server which just bounces back all data. The tab that owns the connection and the
+// followers run in the same context, which works because a BroadcastChannel delivers to all other
+// channels with the same name, including the ones of the same context.
+//
+// ✔ Text and binary messages go through the channel in both directions and the open message carries the url.
+// ✔ Every follower receives every message.
+// ✔ Closing the connection in the owning tab ends the followers' streams with the close event.
+// ✔ Detaching a follower ends it's stream without closing the connection.
+//
+use
+{
+	futures_01            :: Future as Future01,
+	futures::prelude      :: * ,
+	futures::future       :: join ,
+	wasm_bindgen::prelude :: * ,
+	wasm_bindgen_test     :: * ,
+	ws_stream_wasm        :: * ,
+	log                   :: * ,
+};
+
+
+
+const URL_TT: &str = "ws://127.0.0.1:3312/";
+
+
+
+// Text and binary messages go through the channel in both directions and the open message carries the url.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn shared_echo() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: shared_echo" );
+
+	async
+	{
+		let (ws, wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let owner      = ws.clone();
+
+		let follower = async
+		{
+			let mut shared = WsStreamShared::connect( "shared_echo" ).await.expect_throw( "connect shared" );
+
+			assert_eq!( URL_TT       , shared.url()         );
+			assert_eq!( WsState::Open, shared.ready_state() );
+
+			let text   = WsMessage::Text  ( "héllo 🦀".to_string() );
+			let binary = WsMessage::Binary( vec![ 0, 1, 2, 255 ]   );
+
+			shared.send( text  .clone() ).await.expect_throw( "send text"   );
+			shared.send( binary.clone() ).await.expect_throw( "send binary" );
+
+			assert_eq!( text  , shared.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" ) );
+			assert_eq!( binary, shared.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" ) );
+
+			owner.close_code( 4000 ).await.expect_throw( "close" );
+
+			assert!( shared.next().await.is_none() );
+			assert_eq!( WsState::Closed, shared.ready_state()                   );
+			assert_eq!( 4000           , shared.close_event().unwrap_throw().code );
+		};
+
+		let (res, _) = join( serve_shared( ws, wsio, "shared_echo" ), follower ).await;
+
+		res.expect_throw( "serve_shared" );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Every follower receives every message, and detaching one doesn't close the connection.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn two_followers() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: two_followers" );
+
+	async
+	{
+		let (ws, wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let owner      = ws.clone();
+
+		let followers = async
+		{
+			let mut one = WsStreamShared::connect( "two_followers" ).await.expect_throw( "connect one" );
+			let mut two = WsStreamShared::connect( "two_followers" ).await.expect_throw( "connect two" );
+
+			one.send( WsMessage::from( "hi" ) ).await.expect_throw( "send" );
+
+			assert_eq!( WsMessage::from( "hi" ), one.next().await.unwrap_throw().expect_throw( "one" ) );
+			assert_eq!( WsMessage::from( "hi" ), two.next().await.unwrap_throw().expect_throw( "two" ) );
+
+			one.close().await.expect_throw( "detach" );
+
+			assert!( one.next().await.is_none() );
+			assert_eq!( WsState::Closed, one.ready_state() );
+
+			two.send( WsMessage::from( "still here" ) ).await.expect_throw( "send" );
+
+			assert_eq!( WsMessage::from( "still here" ), two.next().await.unwrap_throw().expect_throw( "two" ) );
+			assert_eq!( WsState::Open, owner.ready_state() );
+
+			owner.close().await;
+		};
+
+		let (res, _) = join( serve_shared( ws, wsio, "two_followers" ), followers ).await;
+
+		res.expect_throw( "serve_shared" );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
@@ -0,0 +1,74 @@
+// What's tested:
+//
+// These don't need a backend, so they are regular tests.
+//
+// ✔ RtSpawner runs tasks on the local pool of async_runtime.
+// ✔ A custom spawner can be set on the config and configs are only equal when they share it.
+//
+use
+{
+	async_runtime  :: { rt                                                    } ,
+	futures        :: { future::LocalBoxFuture                                } ,
+	std            :: { rc::Rc, cell::{ Cell, RefCell }                       } ,
+	ws_stream_wasm :: { Spawner, RtSpawner, WsConfig                          } ,
+};
+
+
+
+// Keeps the futures so the test decides when they run.
+//
+#[ derive( Default, Clone ) ]
+//
+struct Collect
+{
+	tasks: Rc<RefCell< Vec< LocalBoxFuture<'static, ()> > >>,
+}
+
+
+impl Spawner for Collect
+{
+	fn spawn_local( &self, fut: LocalBoxFuture<'static, ()> )
+	{
+		self.tasks.borrow_mut().push( fut );
+	}
+}
+
+
+
+// RtSpawner runs tasks on the local pool of async_runtime.
+//
+#[ test ]
+//
+fn rt_spawner()
+{
+	let done  = Rc::new( Cell::new( false ) );
+	let done2 = done.clone();
+
+	RtSpawner.spawn_local( Box::pin( async move { done2.set( true ) } ) );
+
+	assert!( !done.get() );
+
+	rt::run();
+
+	assert!( done.get() );
+}
+
+
+
+// A custom spawner can be set on the config and configs are only equal when they share it.
+//
+#[ test ]
+//
+fn custom_spawner()
+{
+	let collect = Collect::default();
+
+	collect.spawn_local( Box::pin( async {} ) );
+	assert_eq!( 1, collect.tasks.borrow().len() );
+
+	let config = WsConfig::default().spawner( collect.clone() );
+
+	assert_eq!( config        , config.clone()                           );
+	assert_ne!( config        , WsConfig::default().spawner( collect )   );
+	assert_ne!( config.clone(), WsConfig::default()                      );
+}
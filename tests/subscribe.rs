@@ -0,0 +1,238 @@
+#![ feature( async_await, trait_alias )]
+wasm_bindgen_test_configure!(run_in_browser);
+
+
+
+// What's tested:
+//
+// Tests send to an echo server which just bounces back all data.
+//
+// ✔ Two subscribers and the WsIo stream all receive every message.
+// ✔ A subscriber only sees messages received after it subscribed.
+// ✔ A slow subscriber drops the oldest messages when it's buffer is full.
+// ✔ Drops are counted per subscriber and per connection, and the stats can be reset.
+// ✔ The lag warning is called once for a burst of drops.
+//
+use
+{
+	futures_01            :: Future as Future01,
+	futures::prelude      :: * ,
+	wasm_bindgen::prelude :: * ,
+	wasm_bindgen_test     :: * ,
+	ws_stream_wasm        :: * ,
+	log                   :: * ,
+	std                   :: { rc::Rc, cell::Cell, time::Duration } ,
+};
+
+
+
+const URL_TT: &str = "ws://127.0.0.1:3312/";
+
+
+
+fn text( s: &str ) -> WsMessage
+{
+	WsMessage::Text( s.to_string() )
+}
+
+
+
+// Two subscribers and the WsIo stream all receive every message.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn two_subscribers() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: two_subscribers" );
+
+	async
+	{
+		let (_ws, mut wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+
+		let mut a = wsio.subscribe();
+		let mut b = wsio.subscribe();
+
+		wsio.send( text( "one" ) ).await.expect_throw( "send" );
+		wsio.send( text( "two" ) ).await.expect_throw( "send" );
+
+		for expect in &[ "one", "two" ]
+		{
+			assert_eq!( text( expect ), wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" ) );
+			assert_eq!( text( expect ), *a.next().await.expect_throw( "Subscription a ended" ) );
+			assert_eq!( text( expect ), *b.next().await.expect_throw( "Subscription b ended" ) );
+		}
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// A subscriber only sees messages received after it subscribed.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn late_subscriber() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: late_subscriber" );
+
+	async
+	{
+		let (_ws, mut wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+
+		wsio.send( text( "before" ) ).await.expect_throw( "send" );
+		wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
+
+		let mut sub = wsio.subscribe();
+
+		wsio.send( text( "after" ) ).await.expect_throw( "send" );
+
+		assert_eq!( text( "after" ), *sub.next().await.expect_throw( "Subscription ended" ) );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// A slow subscriber drops the oldest messages when it's buffer is full.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn lagging_subscriber() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: lagging_subscriber" );
+
+	async
+	{
+		let config          = WsConfig::default().subscriber_capacity( 2 );
+		let (_ws, mut wsio) = WsStream::connect_with_config( URL_TT, config ).await.expect_throw( "Could not create websocket" );
+		let mut sub         = wsio.subscribe();
+
+		for s in &[ "1", "2", "3" ]
+		{
+			wsio.send( text( s ) ).await.expect_throw( "send" );
+		}
+
+		// Make sure all messages have arrived.
+		//
+		for _ in 0..3
+		{
+			wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
+		}
+
+		assert_eq!( 1, sub.dropped() );
+
+		assert_eq!( text( "2" ), *sub.next().await.expect_throw( "Subscription ended" ) );
+		assert_eq!( text( "3" ), *sub.next().await.expect_throw( "Subscription ended" ) );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Drops are counted per subscriber and per connection, and the stats can be reset.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn drop_stats() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: drop_stats" );
+
+	async
+	{
+		let config          = WsConfig::default().subscriber_capacity( 1 );
+		let (_ws, mut wsio) = WsStream::connect_with_config( URL_TT, config ).await.expect_throw( "Could not create websocket" );
+		let slow            = wsio.subscribe();
+		let mut fast        = wsio.subscribe();
+
+		assert_eq!( WsStats::default(), wsio.stats() );
+
+		for s in &[ "1", "2", "3" ]
+		{
+			wsio.send( text( s ) ).await.expect_throw( "send" );
+			wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
+			fast.next().await.expect_throw( "Subscription ended" );
+		}
+
+		assert_eq!( 2, slow.stats().dropped );
+		assert_eq!( 0, fast.stats().dropped );
+		assert_eq!( 2, wsio.stats().dropped );
+
+		assert!( slow.stats().last_drop.is_some() );
+		assert_eq!( slow.stats().last_drop, wsio.stats().last_drop );
+
+		wsio.reset_stats();
+
+		assert_eq!( WsStats::default(), wsio.stats() );
+		assert_eq!( 2, slow.dropped() );
+
+		slow.reset_stats();
+
+		assert_eq!( WsStats::default(), slow.stats() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// The lag warning is called once for a burst of drops.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn lag_warning() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: lag_warning" );
+
+	async
+	{
+		let calls  = Rc::new( Cell::new( 0 ) );
+		let total  = Rc::new( Cell::new( 0 ) );
+		let calls2 = calls.clone();
+		let total2 = total.clone();
+
+		let config = WsConfig::default()
+
+			.subscriber_capacity( 1 )
+			.lag_warning( Duration::from_secs( 60 ), move |dropped|
+			{
+				calls2.set( calls2.get() + 1       );
+				total2.set( total2.get() + dropped );
+			})
+		;
+
+		let (_ws, mut wsio) = WsStream::connect_with_config( URL_TT, config ).await.expect_throw( "Could not create websocket" );
+		let _sub            = wsio.subscribe();
+
+		for s in &[ "1", "2", "3", "4" ]
+		{
+			wsio.send( text( s ) ).await.expect_throw( "send" );
+			wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
+		}
+
+		// The first drop is reported right away, the others wait for the interval.
+		//
+		assert_eq!( 3, wsio.stats().dropped );
+		assert_eq!( 1, calls.get()          );
+		assert_eq!( 1, total.get()          );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
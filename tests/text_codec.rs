@@ -0,0 +1,172 @@
+#![ feature( async_await, trait_alias )]
+wasm_bindgen_test_configure!(run_in_browser);
+
+
+
+// What's tested:
+//
+// Tests send to an echo server which just bounces back all data.
+//
+// ✔ binary_to_text decodes multi-byte and emoji content.
+// ✔ binary_to_text yields InvalidUtf8 for invalid data and the stream continues.
+// ✔ text_to_binary encodes multi-byte and emoji content.
+// ✔ Rough benchmark: decoding 1MB with TextDecoder vs String::from_utf8 (logged, not asserted).
+//
+use
+{
+	futures_01            :: Future as Future01,
+	futures::prelude      :: * ,
+	wasm_bindgen::prelude :: * ,
+	wasm_bindgen_test     :: * ,
+	ws_stream_wasm        :: * ,
+	log                   :: * ,
+	js_sys                :: Date ,
+};
+
+
+
+const URL   : &str = "ws://127.0.0.1:3212/";
+const URL_TT: &str = "ws://127.0.0.1:3312/";
+const TEXT  : &str = "héllo wörld ✓ 🦀🎉 日本語";
+
+
+
+// binary_to_text decodes multi-byte and emoji content.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn binary_to_text() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: binary_to_text" );
+
+	async
+	{
+		let config          = WsConfig::default().binary_to_text( true );
+		let (_ws, mut wsio) = WsStream::connect_with_config( URL, config ).await.expect_throw( "Could not create websocket" );
+
+		wsio.send( WsMessage::Binary( TEXT.as_bytes().to_vec() ) ).await.expect_throw( "send" );
+
+		let msg = wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
+
+		assert_eq!( WsMessage::Text( TEXT.to_string() ), msg );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// binary_to_text yields InvalidUtf8 for invalid data and the stream continues.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn invalid_utf8() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: invalid_utf8" );
+
+	async
+	{
+		let config          = WsConfig::default().binary_to_text( true );
+		let (_ws, mut wsio) = WsStream::connect_with_config( URL, config ).await.expect_throw( "Could not create websocket" );
+
+		wsio.send( WsMessage::Binary( vec![ 0xff, 0xfe, 0x00 ] ) ).await.expect_throw( "send" );
+		wsio.send( WsMessage::Binary( b"ok".to_vec()          ) ).await.expect_throw( "send" );
+
+		let err = wsio.next().await.expect_throw( "Stream closed" ).unwrap_err();
+
+		assert_eq!( &WsErrKind::InvalidUtf8, err.kind() );
+
+		let msg = wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
+
+		assert_eq!( WsMessage::Text( "ok".to_string() ), msg );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// text_to_binary encodes multi-byte and emoji content.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn text_to_binary() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: text_to_binary" );
+
+	async
+	{
+		let config          = WsConfig::default().text_to_binary( true );
+		let (_ws, mut wsio) = WsStream::connect_with_config( URL_TT, config ).await.expect_throw( "Could not create websocket" );
+
+		wsio.send( WsMessage::Text( TEXT.to_string() ) ).await.expect_throw( "send" );
+
+		let msg = wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
+
+		assert_eq!( WsMessage::Binary( TEXT.as_bytes().to_vec() ), msg );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Rough benchmark: decoding 1MB with TextDecoder vs String::from_utf8 (logged, not asserted).
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn bench_decode() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Info );
+
+	info!( "starting test: bench_decode" );
+
+	async
+	{
+		let payload: Vec<u8> = "日本語 🦀 text ".repeat( 1024 * 1024 / 20 ).into_bytes();
+		let rounds           = 10;
+
+
+		let config          = WsConfig::default().binary_to_text( true );
+		let (_ws, mut wsio) = WsStream::connect_with_config( URL, config ).await.expect_throw( "Could not create websocket" );
+		let start           = Date::now();
+
+		for _ in 0..rounds
+		{
+			wsio.send( WsMessage::Binary( payload.clone() ) ).await.expect_throw( "send" );
+			wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
+		}
+
+		let decoder = Date::now() - start;
+
+
+		let (_ws, mut wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
+		let start           = Date::now();
+
+		for _ in 0..rounds
+		{
+			wsio.send( WsMessage::Binary( payload.clone() ) ).await.expect_throw( "send" );
+
+			let bytes: Vec<u8> = wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" ).into();
+
+			String::from_utf8( bytes ).expect_throw( "valid utf8" );
+		}
+
+		let rust = Date::now() - start;
+
+
+		info!( "{} round trips of {} bytes: TextDecoder: {}ms, String::from_utf8: {}ms", rounds, payload.len(), decoder, rust );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
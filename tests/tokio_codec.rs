@@ -38,7 +38,7 @@ const URL: &str = "ws://127.0.0.1:3212";
 
 async fn connect() -> (WsStream, Compat<WsIo>)
 {
-	let (ws, wsio) = WsStream::connect( URL, None ).await.expect_throw( "Could not create websocket" );
+	let (ws, wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
 
 	( ws, AsyncReadExt::compat(wsio) )
 }
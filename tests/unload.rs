@@ -0,0 +1,114 @@
+#![ feature( async_await, trait_alias )]
+wasm_bindgen_test_configure!(run_in_browser);
+
+
+
+// What's tested:
+//
+// ✔ The connection is closed with the configured code and reason on pagehide.
+// ✔ A close code the browser refuses falls back to 1000.
+// ✔ Connections without close_on_unload are not affected by pagehide.
+//
+use
+{
+	futures_01            :: Future as Future01,
+	futures::prelude      :: * ,
+	wasm_bindgen::prelude :: * ,
+	wasm_bindgen_test     :: * ,
+	ws_stream_wasm        :: * ,
+	log                   :: * ,
+	web_sys               :: { window, Event } ,
+};
+
+
+
+const URL: &str = "ws://127.0.0.1:3212/";
+
+
+
+fn page_hide()
+{
+	let evt = Event::new( "pagehide" ).expect_throw( "create event" );
+
+	window().expect_throw( "window" ).dispatch_event( &evt ).expect_throw( "dispatch pagehide" );
+}
+
+
+
+// The connection is closed with the configured code and reason on pagehide.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn close_on_unload() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: close_on_unload" );
+
+	async
+	{
+		let config      = WsConfig::default().close_on_unload( 4000, "bye" );
+		let (ws, _wsio) = WsStream::connect_with_config( URL, config ).await.expect_throw( "Could not create websocket" );
+
+		page_hide();
+
+		let evt = ws.closed().await;
+
+		assert_eq!( 4000 , evt.code   );
+		assert_eq!( "bye", evt.reason );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// A close code the browser refuses falls back to 1000.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn going_away() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: going_away" );
+
+	async
+	{
+		let config      = WsConfig::default().close_on_unload( 1001, "going away" );
+		let (ws, _wsio) = WsStream::connect_with_config( URL, config ).await.expect_throw( "Could not create websocket" );
+
+		page_hide();
+
+		assert_eq!( 1000, ws.closed().await.code );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Connections without close_on_unload are not affected by pagehide.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn not_configured() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: not_configured" );
+
+	async
+	{
+		let (ws, _wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
+
+		page_hide();
+
+		assert_eq!( WsState::Open, ws.ready_state() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
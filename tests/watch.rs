@@ -0,0 +1,117 @@
+// What's tested:
+//
+// These don't need a backend, so they are regular tests.
+//
+// ✔ A subscriber gets the current value right away
+// ✔ Setting an equal value doesn't notify
+// ✔ A slow subscriber only sees the latest value, and nothing if the value changed back
+// ✔ Clones share the value and the stream ends once all handles are dropped
+//
+use
+{
+	futures        :: { executor::block_on, future::{ select, ready, Either }, prelude::* } ,
+	ws_stream_wasm :: { Watch, WatchStream                                                } ,
+};
+
+
+
+// The next value if the stream has one ready, `Some( None )` if it ended.
+//
+fn try_next( rx: &mut WatchStream<u8> ) -> Option< Option<u8> >
+{
+	match block_on( select( rx.next(), ready(()) ) )
+	{
+		Either::Left(( item, _ )) => Some( item ),
+		Either::Right(_)          => None        ,
+	}
+}
+
+
+
+// A subscriber gets the current value right away
+//
+#[ test ]
+//
+fn current_value()
+{
+	let watch  = Watch::new( 1 );
+	let mut rx = watch.subscribe();
+
+	assert_eq!( Some( Some( 1 ) ), try_next( &mut rx ) );
+	assert_eq!( None             , try_next( &mut rx ) );
+
+	watch.set( 2 );
+
+	assert_eq!( 2                , watch.get()         );
+	assert_eq!( Some( Some( 2 ) ), try_next( &mut rx ) );
+}
+
+
+
+// Setting an equal value doesn't notify
+//
+#[ test ]
+//
+fn dedup()
+{
+	let watch  = Watch::new( 1 );
+	let mut rx = watch.subscribe();
+
+	assert_eq!( Some( Some( 1 ) ), try_next( &mut rx ) );
+
+	watch.set( 1 );
+
+	assert_eq!( None, try_next( &mut rx ) );
+}
+
+
+
+// A slow subscriber only sees the latest value, and nothing if the value changed back
+//
+#[ test ]
+//
+fn coalesce()
+{
+	let watch  = Watch::new( 1 );
+	let mut rx = watch.subscribe();
+
+	assert_eq!( Some( Some( 1 ) ), try_next( &mut rx ) );
+
+	watch.set( 2 );
+	watch.set( 3 );
+
+	assert_eq!( Some( Some( 3 ) ), try_next( &mut rx ) );
+
+	watch.set( 4 );
+	watch.set( 3 );
+
+	assert_eq!( None, try_next( &mut rx ) );
+}
+
+
+
+// Clones share the value and the stream ends once all handles are dropped
+//
+#[ test ]
+//
+fn clones()
+{
+	let watch  = Watch::new( 1 );
+	let other  = watch.clone();
+	let mut rx = watch.subscribe();
+
+	other.set( 2 );
+
+	assert_eq!( 2, watch.get() );
+
+	drop( watch );
+
+	assert_eq!( Some( Some( 2 ) ), try_next( &mut rx ) );
+	assert_eq!( None             , try_next( &mut rx ) );
+
+	other.set( 3 );
+	drop( other );
+
+	assert_eq!( Some( Some( 3 ) ), try_next( &mut rx ) );
+	assert_eq!( Some( None )     , try_next( &mut rx ) );
+}
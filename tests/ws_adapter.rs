@@ -0,0 +1,141 @@
+// What's tested:
+//
+// These work on any stream of WsMessage, so they are regular tests.
+//
+// ✔ text_only with each policy.
+// ✔ binary_only with each policy.
+// ✔ Errors from the underlying stream are passed through.
+// ✔ FusedStream is preserved.
+//
+use
+{
+	futures        :: { executor::block_on, stream::{ self, StreamExt, FusedStream } } ,
+	ws_stream_wasm :: * ,
+};
+
+
+
+fn messages() -> Vec< Result<WsMessage, WsErr> >
+{
+	vec!
+	[
+		Ok( WsMessage::Text  ( "a".to_string()        ) ),
+		Ok( WsMessage::Binary( b"b".to_vec()          ) ),
+		Err( WsErrKind::ConnectionNotOpen.into()        ),
+		Ok( WsMessage::Binary( vec![ 0xff, 0xfe ]     ) ),
+		Ok( WsMessage::Text  ( "c".to_string()        ) ),
+	]
+}
+
+
+fn kinds<T>( items: Vec< Result<T, WsErr> > ) -> Vec< Result<T, WsErrKind> >
+{
+	items.into_iter().map( |i| i.map_err( |e| e.kind().clone() ) ).collect()
+}
+
+
+
+// text_only with each policy.
+//
+#[ test ]
+//
+fn text_only()
+{
+	let skip    = block_on( stream::iter( messages() ).text_only( Mismatch::Skip    ).collect::<Vec<_>>() );
+	let error   = block_on( stream::iter( messages() ).text_only( Mismatch::Error   ).collect::<Vec<_>>() );
+	let convert = block_on( stream::iter( messages() ).text_only( Mismatch::Convert ).collect::<Vec<_>>() );
+
+	assert_eq!
+	(
+		vec![ Ok( "a".to_string() ), Err( WsErrKind::ConnectionNotOpen ), Ok( "c".to_string() ) ],
+		kinds( skip )
+	);
+
+	assert_eq!
+	(
+		vec!
+		[
+			Ok ( "a".to_string()               ),
+			Err( WsErrKind::UnexpectedBinary   ),
+			Err( WsErrKind::ConnectionNotOpen  ),
+			Err( WsErrKind::UnexpectedBinary   ),
+			Ok ( "c".to_string()               ),
+		],
+		kinds( error )
+	);
+
+	assert_eq!
+	(
+		vec!
+		[
+			Ok ( "a".to_string()              ),
+			Ok ( "b".to_string()              ),
+			Err( WsErrKind::ConnectionNotOpen ),
+			Err( WsErrKind::InvalidUtf8       ),
+			Ok ( "c".to_string()              ),
+		],
+		kinds( convert )
+	);
+}
+
+
+
+// binary_only with each policy.
+//
+#[ test ]
+//
+fn binary_only()
+{
+	let skip    = block_on( stream::iter( messages() ).binary_only( Mismatch::Skip    ).collect::<Vec<_>>() );
+	let error   = block_on( stream::iter( messages() ).binary_only( Mismatch::Error   ).collect::<Vec<_>>() );
+	let convert = block_on( stream::iter( messages() ).binary_only( Mismatch::Convert ).collect::<Vec<_>>() );
+
+	assert_eq!
+	(
+		vec![ Ok( b"b".to_vec() ), Err( WsErrKind::ConnectionNotOpen ), Ok( vec![ 0xff, 0xfe ] ) ],
+		kinds( skip )
+	);
+
+	assert_eq!
+	(
+		vec!
+		[
+			Err( WsErrKind::UnexpectedText    ),
+			Ok ( b"b".to_vec()                ),
+			Err( WsErrKind::ConnectionNotOpen ),
+			Ok ( vec![ 0xff, 0xfe ]           ),
+			Err( WsErrKind::UnexpectedText    ),
+		],
+		kinds( error )
+	);
+
+	assert_eq!
+	(
+		vec!
+		[
+			Ok ( b"a".to_vec()                ),
+			Ok ( b"b".to_vec()                ),
+			Err( WsErrKind::ConnectionNotOpen ),
+			Ok ( vec![ 0xff, 0xfe ]           ),
+			Ok ( b"c".to_vec()                ),
+		],
+		kinds( convert )
+	);
+}
+
+
+
+// FusedStream is preserved.
+//
+#[ test ]
+//
+fn fused()
+{
+	let mut text = stream::iter( messages() ).fuse().text_only( Mismatch::Skip );
+
+	assert!( !text.is_terminated() );
+
+	block_on( async { while text.next().await.is_some() {} } );
+
+	assert!( text.is_terminated() );
+}
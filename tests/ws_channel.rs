@@ -0,0 +1,242 @@
+#![ feature( async_await, trait_alias )]
+wasm_bindgen_test_configure!(run_in_browser);
+
+
+
+// What's tested:
+//
+// Tests send to an echo server which just bounces back all data. The codec encodes commands as "cmd:<n>" and
+// decodes events from the same format, so what we send comes back as a different type.
+//
+// ✔ Send Tx and receive Rx over the same channel
+// ✔ A message that can't be decoded yields a Decode error and the stream continues
+// ✔ An item that can't be encoded yields an Encode error
+// ✔ Split halves send and receive independently
+// ✔ Reunite halves of the same channel, mismatched halves are given back
+// ✔ into_parts returns the connection and the codec, which the halves shared
+//
+use
+{
+	futures_01            :: Future as Future01,
+	futures::prelude      :: * ,
+	wasm_bindgen::prelude :: * ,
+	wasm_bindgen_test     :: * ,
+	ws_stream_wasm        :: * ,
+	log                   :: * ,
+};
+
+
+
+const URL_TT: &str = "ws://127.0.0.1:3312/";
+
+
+
+#[ derive( Debug, PartialEq ) ] struct Cmd  ( u32 );
+#[ derive( Debug, PartialEq ) ] struct Event( u32 );
+
+
+
+// Counts what it does.
+//
+#[ derive( Default ) ]
+//
+struct Codec
+{
+	encoded: usize,
+	decoded: usize,
+}
+
+
+impl WsEncode<Cmd> for Codec
+{
+	fn encode( &mut self, item: &Cmd ) -> Result<WsMessage, WsErr>
+	{
+		if item.0 == 0 { return Err( WsErrKind::Encode( "zero is not a command".to_string() ).into() ) }
+
+		self.encoded += 1;
+
+		Ok( WsMessage::Text( format!( "cmd:{}", item.0 ) ) )
+	}
+}
+
+
+impl WsDecode<Event> for Codec
+{
+	fn decode( &mut self, msg: WsMessage ) -> Result<Event, WsErr>
+	{
+		self.decoded += 1;
+
+		match msg
+		{
+			WsMessage::Text( t ) if t.starts_with( "cmd:" ) => t[4..].parse().map( Event ).map_err( |_| WsErrKind::Decode( t ).into() ),
+
+			// A codec may return other kinds, the channel turns them into Decode.
+			//
+			_ => Err( WsErrKind::InvalidUtf8.into() ),
+		}
+	}
+}
+
+
+
+// Send Tx and receive Rx over the same channel
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn round_trip() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: round_trip" );
+
+	async
+	{
+		let (_ws, wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let mut channel = wsio.into_channel::<Cmd, Event, _>( Codec::default() );
+
+		channel.send( Cmd( 5 ) ).await.expect_throw( "send" );
+
+		assert_eq!( Event( 5 ), channel.next().await.unwrap_throw().expect_throw( "decode" ) );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// A message that can't be decoded yields a Decode error and the stream continues
+// An item that can't be encoded yields an Encode error
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn codec_errors() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: codec_errors" );
+
+	async
+	{
+		let (_ws, wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let mut sender  = wsio.sender();
+		let mut channel = wsio.into_channel::<Cmd, Event, _>( Codec::default() );
+
+		let err = channel.send( Cmd( 0 ) ).await.expect_err( "encode error" );
+
+		assert_eq!( &WsErrKind::Encode( "zero is not a command".to_string() ), err.kind() );
+
+
+		sender.send( WsMessage::Text  ( "cmd:x".to_string() ) ).await.expect_throw( "send" );
+		sender.send( WsMessage::Binary( vec![ 1, 2 ]        ) ).await.expect_throw( "send" );
+
+		channel.send( Cmd( 7 ) ).await.expect_throw( "send" );
+
+		let err = channel.next().await.unwrap_throw().expect_err( "decode error" );
+		assert_eq!( &WsErrKind::Decode( "cmd:x".to_string() ), err.kind() );
+
+		let err = channel.next().await.unwrap_throw().expect_err( "decode error" );
+		assert!( matches!( err.kind(), WsErrKind::Decode(_) ) );
+
+		assert_eq!( Event( 7 ), channel.next().await.unwrap_throw().expect_throw( "decode" ) );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Split halves send and receive independently
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn split() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: split" );
+
+	async
+	{
+		let (_ws, wsio)        = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let ( mut tx, mut rx ) = wsio.into_channel::<Cmd, Event, _>( Codec::default() ).split();
+
+		tx.send( Cmd( 1 ) ).await.expect_throw( "send" );
+		tx.send( Cmd( 2 ) ).await.expect_throw( "send" );
+
+		assert_eq!( Event( 1 ), rx.next().await.unwrap_throw().expect_throw( "decode" ) );
+		assert_eq!( Event( 2 ), rx.next().await.unwrap_throw().expect_throw( "decode" ) );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Reunite halves of the same channel, mismatched halves are given back
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn reunite() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: reunite" );
+
+	async
+	{
+		let (_wsa, wsio_a) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let (_wsb, wsio_b) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+
+		let ( tx_a, rx_a ) = wsio_a.into_channel::<Cmd, Event, _>( Codec::default() ).split();
+		let ( tx_b, rx_b ) = wsio_b.into_channel::<Cmd, Event, _>( Codec::default() ).split();
+
+		let ReuniteError( tx_a, rx_b ) = WsChannel::reunite( tx_a, rx_b ).expect_err( "mismatched halves" );
+
+		let mut a = WsChannel::reunite( tx_a, rx_a ).expect_throw( "reunite a" );
+		let     _ = WsChannel::reunite( tx_b, rx_b ).expect_throw( "reunite b" );
+
+		a.send( Cmd( 3 ) ).await.expect_throw( "send" );
+
+		assert_eq!( Event( 3 ), a.next().await.unwrap_throw().expect_throw( "decode" ) );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// into_parts returns the connection and the codec, which the halves shared
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn into_parts() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: into_parts" );
+
+	async
+	{
+		let (_ws, wsio)        = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let ( mut tx, mut rx ) = wsio.into_channel::<Cmd, Event, _>( Codec::default() ).split();
+
+		tx.send( Cmd( 1 ) ).await.expect_throw( "send" );
+		rx.next().await.unwrap_throw().expect_throw( "decode" );
+
+		let ( mut wsio, codec ) = WsChannel::reunite( tx, rx ).expect_throw( "reunite" ).into_parts();
+
+		assert_eq!( 1, codec.encoded );
+		assert_eq!( 1, codec.decoded );
+
+		wsio.send( WsMessage::from( "raw" ) ).await.expect_throw( "send" );
+
+		assert_eq!( WsMessage::from( "raw" ), wsio.next().await.unwrap_throw().expect_throw( "receive" ) );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
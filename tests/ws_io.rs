@@ -12,6 +12,14 @@ wasm_bindgen_test_configure!(run_in_browser);
 // ✔ Send while closing and verify the error
 // ✔ Send while closed  and verify the error
 // ✔ Test Debug impl
+// ✔ Receive a message over max_message_size and verify the error, the stream continues afterwards
+// ✔ Receive a message over max_message_size with close_on_oversize and verify the connection closes
+// ✔ try_next_now and peek return None without waiting, then see a message once it arrived
+// ✔ A message that arrives after a pending next() was dropped goes to try_next_now
+// ✔ pending_messages and is_backlogged follow what the stream yields
+// ✔ Closing while a consumer and a subscriber wait ends both, after they got the queued messages
+//   note: there is no mock WebSocket, so this runs against the echo server.
+// ✔ Rough benchmark of the receive path: small messages in a tight echo loop (logged, not asserted).
 //
 // Note that AsyncRead/AsyncWrite are tested by futures_codec.rs and tokio_codec.rs
 //
@@ -19,6 +27,9 @@ use
 {
 	futures_01            :: Future as Future01,
 	futures::prelude      :: * ,
+	futures::future       :: { poll_fn, join } ,
+	js_sys                :: Date ,
+	std                   :: { task::Poll, pin::Pin } ,
 	wasm_bindgen::prelude :: * ,
 	wasm_bindgen_test     :: * ,
 	ws_stream_wasm        :: * ,
@@ -44,7 +55,7 @@ pub fn round_trip_text() -> impl Future01<Item = (), Error = JsValue>
 
 	async
 	{
-		let (_ws, mut wsio) = WsStream::connect( URL_TT, None ).await.expect_throw( "Could not create websocket" );
+		let (_ws, mut wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
 		let message         = "Hello from browser".to_string();
 
 
@@ -54,7 +65,7 @@ pub fn round_trip_text() -> impl Future01<Item = (), Error = JsValue>
 
 
 		let msg    = wsio.next().await;
-		let result = msg.expect_throw( "Stream closed" );
+		let result = msg.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
 
 		assert_eq!( WsMessage::Text( message ), result );
 
@@ -77,7 +88,7 @@ pub fn round_trip_binary() -> impl Future01<Item = (), Error = JsValue>
 
 	async
 	{
-		let (_ws, mut wsio) = WsStream::connect( URL, None ).await.expect_throw( "Could not create websocket" );
+		let (_ws, mut wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
 		let message         = b"Hello from browser".to_vec();
 
 
@@ -87,7 +98,7 @@ pub fn round_trip_binary() -> impl Future01<Item = (), Error = JsValue>
 
 
 		let msg    = wsio.next().await;
-		let result = msg.expect_throw( "Stream closed" );
+		let result = msg.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
 
 		assert_eq!( WsMessage::Binary( message ), result );
 
@@ -106,7 +117,7 @@ fn send_while_closing() -> impl Future01<Item = (), Error = JsValue>
 
 	async
 	{
-		let (ws, mut wsio) = WsStream::connect( URL, None ).await.expect_throw( "Could not create websocket" );
+		let (ws, mut wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
 
 		ws.wrapped().close().expect_throw( "close connection" );
 
@@ -129,7 +140,7 @@ fn send_after_close() -> impl Future01<Item = (), Error = JsValue>
 
 	async
 	{
-		let (ws, mut wsio) = WsStream::connect( URL, None ).await.expect_throw( "Could not create websocket" );
+		let (ws, mut wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
 
 		ws.close().await;
 
@@ -155,7 +166,7 @@ pub fn debug() -> impl Future01<Item = (), Error = JsValue>
 
 	async
 	{
-		let (_ws, wsio) = WsStream::connect( URL, None ).await.expect_throw( "Could not create websocket" );
+		let (_ws, wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
 
 		assert_eq!( format!( "WsIo for connection: {}", URL ), format!( "{:?}", wsio ) );
 
@@ -163,3 +174,299 @@ pub fn debug() -> impl Future01<Item = (), Error = JsValue>
 
 	}.boxed_local().compat()
 }
+
+
+
+// Receive a message over max_message_size and verify the error, the stream continues afterwards.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn max_message_size() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: max_message_size" );
+
+	async
+	{
+		let config          = WsConfig::default().max_message_size( 5 );
+		let (_ws, mut wsio) = WsStream::connect_with_config( URL_TT, config ).await.expect_throw( "Could not create websocket" );
+
+		wsio.send( WsMessage::Text  ( "Hello from browser".to_string() ) ).await.expect_throw( "send" );
+		wsio.send( WsMessage::Binary( vec![ 0; 6 ]                     ) ).await.expect_throw( "send" );
+		wsio.send( WsMessage::Text  ( "Hello"             .to_string() ) ).await.expect_throw( "send" );
+
+		let err = wsio.next().await.expect_throw( "Stream closed" ).unwrap_err();
+		assert_eq!( &WsErrKind::MessageTooBig{ size: 18, limit: 5 }, err.kind() );
+
+		let err = wsio.next().await.expect_throw( "Stream closed" ).unwrap_err();
+		assert_eq!( &WsErrKind::MessageTooBig{ size: 6, limit: 5 }, err.kind() );
+
+		let msg = wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
+		assert_eq!( WsMessage::Text( "Hello".to_string() ), msg );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Receive a message over max_message_size with close_on_oversize and verify the connection closes.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn close_on_oversize() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: close_on_oversize" );
+
+	async
+	{
+		let config         = WsConfig::default().max_message_size( 5 ).close_on_oversize( true );
+		let (ws, mut wsio) = WsStream::connect_with_config( URL_TT, config ).await.expect_throw( "Could not create websocket" );
+
+		wsio.send( WsMessage::Text( "Hello from browser".to_string() ) ).await.expect_throw( "send" );
+
+		let err = wsio.next().await.expect_throw( "Stream closed" ).unwrap_err();
+		assert_eq!( &WsErrKind::MessageTooBig{ size: 18, limit: 5 }, err.kind() );
+
+		assert_eq!( 1009, ws.closed().await.code );
+		assert!   ( wsio.next().await.is_none()  );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// try_next_now and peek return None without waiting, then see a message once it arrived.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn try_next_now() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: try_next_now" );
+
+	async
+	{
+		let (_ws, mut wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let mut sub         = wsio.subscribe();
+		let message         = WsMessage::Text( "now".to_string() );
+
+		assert!( wsio.peek()        .is_none() );
+		assert!( wsio.try_next_now().is_none() );
+
+		wsio.send( message.clone() ).await.expect_throw( "send" );
+
+		// Subscribers get the message in the same callback that queues it for wsio.
+		//
+		sub.next().await.expect_throw( "subscription ended" );
+
+		assert_eq!( Some( Ok( message.clone() ) ), wsio.peek() );
+		assert_eq!( Some( Ok( message.clone() ) ), wsio.peek() );
+
+		assert_eq!( message, wsio.try_next_now().expect_throw( "message" ).expect_throw( "no error" ) );
+
+		assert!( wsio.peek()        .is_none() );
+		assert!( wsio.try_next_now().is_none() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// A message that arrives after a pending next() was dropped goes to try_next_now.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn try_next_now_after_next() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: try_next_now_after_next" );
+
+	async
+	{
+		let (_ws, mut wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let mut sub         = wsio.subscribe();
+		let message         = WsMessage::Text( "for whom".to_string() );
+
+		wsio.sender().send( message.clone() ).await.expect_throw( "send" );
+
+		// Poll next once so it registers it's waker, then drop it before the message arrives.
+		//
+		{
+			let mut next = wsio.next();
+
+			let pending = poll_fn( |cx| Poll::Ready( Pin::new( &mut next ).poll( cx ).is_pending() ) ).await;
+
+			assert!( pending );
+		}
+
+		sub.next().await.expect_throw( "subscription ended" );
+
+		assert_eq!( message, wsio.try_next_now().expect_throw( "message" ).expect_throw( "no error" ) );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// pending_messages and is_backlogged follow what the stream yields.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn pending_messages() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: pending_messages" );
+
+	async
+	{
+		let config          = WsConfig::default().backlog_threshold( 3 );
+		let (_ws, mut wsio) = WsStream::connect_with_config( URL_TT, config ).await.expect_throw( "Could not create websocket" );
+		let mut sub         = wsio.subscribe();
+
+		assert_eq!( 0, wsio.pending_messages() );
+		assert!( !wsio.is_backlogged() );
+
+		for i in 0..5
+		{
+			wsio.send( WsMessage::Text( i.to_string() ) ).await.expect_throw( "send" );
+		}
+
+		for _ in 0..5 { sub.next().await.expect_throw( "subscription ended" ); }
+
+		assert_eq!( 5, wsio.pending_messages() );
+		assert!( wsio.is_backlogged() );
+
+		wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
+		wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
+
+		assert_eq!( 3, wsio.pending_messages() );
+		assert!( wsio.is_backlogged() );
+
+		wsio.try_next_now().expect_throw( "message" ).expect_throw( "no error" );
+
+		assert_eq!( 2, wsio.pending_messages() );
+		assert!( !wsio.is_backlogged() );
+
+		while wsio.try_next_now().is_some() {}
+
+		assert_eq!( 0, wsio.pending_messages() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Closing while a consumer and a subscriber wait ends both, after they got the queued messages
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn close_wakes_consumers() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: close_wakes_consumers" );
+
+	async
+	{
+		let (ws, mut wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let mut sub        = wsio.subscribe();
+		let msg            = WsMessage::from( "last" );
+
+		wsio.send( msg.clone() ).await.expect_throw( "send" );
+
+		let consumer = async
+		{
+			let mut got = Vec::new();
+
+			while let Some( item ) = wsio.next().await { got.push( item.expect_throw( "receive" ) ) }
+
+			got
+		};
+
+		let closer = async
+		{
+			// Once the subscriber has it, the message is queued for the consumer as well.
+			//
+			assert_eq!( msg, *sub.next().await.expect_throw( "subscription ended" ) );
+
+			ws.close().await;
+
+			assert!( sub.next().await.is_none() );
+		};
+
+		let ( got, _ ) = join( consumer, closer ).await;
+
+		assert_eq!( vec![ msg ], got );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Rough benchmark of the receive path: small messages in a tight echo loop (logged, not asserted).
+// One at a time measures the latency of a message through onmessage and the stream, a burst
+// measures the throughput of the queue when many messages arrive before the task runs.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn bench_echo_loop() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Info );
+
+	info!( "starting test: bench_echo_loop" );
+
+	async
+	{
+		let (_ws, mut wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
+		let rounds          = 10_000;
+		let msg             = WsMessage::Text( "tick".to_string() );
+		let start           = Date::now();
+
+		for _ in 0..rounds
+		{
+			wsio.send( msg.clone() ).await.expect_throw( "send" );
+			wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
+		}
+
+		let one = Date::now() - start;
+
+
+		let start = Date::now();
+
+		for _ in 0..rounds
+		{
+			wsio.send( msg.clone() ).await.expect_throw( "send" );
+		}
+
+		for _ in 0..rounds
+		{
+			wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
+		}
+
+		let burst = Date::now() - start;
+
+
+		info!( "{} echoes of a small message: one at a time: {}ms, in a burst: {}ms", rounds, one, burst );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
@@ -0,0 +1,179 @@
+wasm_bindgen_test_configure!(run_in_browser);
+
+
+
+// What's tested:
+//
+// These work directly on JsValues, so they don't need a server.
+//
+// ✔ Convert a JavaScript string.
+// ✔ Convert an ArrayBuffer.
+// ✔ Convert a view, respecting byteOffset and byteLength.
+// ✔ Convert a DataView and a typed array with elements bigger than a byte.
+// ✔ Blobs and other values are rejected.
+// ✔ into_js round trips for text and binary.
+// ✔ Messages from borrowed and owned data are equal and hash the same.
+// ✔ to_uint8array and to_array_buffer encode text as UTF-8, from_uint8array respects the view.
+//
+use
+{
+	std                   :: { convert::TryFrom, collections::hash_map::DefaultHasher, hash::{ Hash, Hasher } } ,
+	wasm_bindgen::prelude :: * ,
+	wasm_bindgen_test     :: * ,
+	ws_stream_wasm        :: * ,
+	js_sys                :: { ArrayBuffer, DataView, Uint8Array, Uint16Array } ,
+	web_sys               :: Blob ,
+};
+
+
+
+fn bytes( data: &[u8] ) -> Uint8Array
+{
+	Uint8Array::from( data )
+}
+
+
+
+// Convert a JavaScript string.
+//
+#[ wasm_bindgen_test ]
+//
+fn from_string()
+{
+	let msg = WsMessage::try_from( JsValue::from_str( "🦀 crab" ) ).expect_throw( "convert" );
+
+	assert_eq!( WsMessage::Text( "🦀 crab".to_string() ), msg );
+}
+
+
+
+// Convert an ArrayBuffer.
+//
+#[ wasm_bindgen_test ]
+//
+fn from_array_buffer()
+{
+	let buf: ArrayBuffer = bytes( &[ 1, 2, 3 ] ).buffer();
+
+	let msg = WsMessage::try_from( JsValue::from( buf ) ).expect_throw( "convert" );
+
+	assert_eq!( WsMessage::Binary( vec![ 1, 2, 3 ] ), msg );
+}
+
+
+
+// Convert a view, respecting byteOffset and byteLength.
+//
+#[ wasm_bindgen_test ]
+//
+fn from_view()
+{
+	let buf  = bytes( &[ 0, 1, 2, 3, 4, 5 ] ).buffer();
+	let view = Uint8Array::new_with_byte_offset_and_length( &buf, 2, 3 );
+
+	let msg = WsMessage::try_from( JsValue::from( view ) ).expect_throw( "convert" );
+
+	assert_eq!( WsMessage::Binary( vec![ 2, 3, 4 ] ), msg );
+}
+
+
+
+// Convert a DataView and a typed array with elements bigger than a byte.
+//
+#[ wasm_bindgen_test ]
+//
+fn from_other_views()
+{
+	let buf  = bytes( &[ 0, 1, 2, 3, 4, 5 ] ).buffer();
+	let dv   = DataView::new( &buf, 1, 2 );
+	let u16s = Uint16Array::new_with_byte_offset_and_length( &buf, 2, 2 );
+
+	assert_eq!( WsMessage::Binary( vec![ 1, 2       ] ), WsMessage::try_from( JsValue::from( dv   ) ).expect_throw( "convert" ) );
+	assert_eq!( WsMessage::Binary( vec![ 2, 3, 4, 5 ] ), WsMessage::try_from( JsValue::from( u16s ) ).expect_throw( "convert" ) );
+}
+
+
+
+// Blobs and other values are rejected.
+//
+#[ wasm_bindgen_test ]
+//
+fn unsupported()
+{
+	let blob = Blob::new().expect_throw( "create blob" );
+
+	let err = WsMessage::try_from( JsValue::from( blob ) ).unwrap_err();
+
+	assert_eq!( &WsErrKind::UnsupportedData( "Blob".to_string() ), err.kind() );
+
+	assert!( WsMessage::try_from( JsValue::from( 5 ) ).is_err() );
+	assert!( WsMessage::try_from( JsValue::NULL      ).is_err() );
+}
+
+
+
+// into_js round trips for text and binary.
+//
+#[ wasm_bindgen_test ]
+//
+fn into_js()
+{
+	let text = WsMessage::Text  ( "héllo".to_string() );
+	let bin  = WsMessage::Binary( vec![ 9, 8, 7 ]     );
+
+	let js_text = text.clone().into_js();
+	let js_bin  = bin .clone().into_js();
+
+	assert_eq!( Some( "héllo".to_string() ), js_text.as_string() );
+	assert!   ( js_bin.is_instance_of::<Uint8Array>()             );
+
+	assert_eq!( text, WsMessage::try_from( js_text ).expect_throw( "convert" ) );
+	assert_eq!( bin , WsMessage::try_from( js_bin  ).expect_throw( "convert" ) );
+}
+
+
+
+// Messages from borrowed and owned data are equal and hash the same.
+//
+#[ wasm_bindgen_test ]
+//
+fn from_rust()
+{
+	let hash = |msg: &WsMessage|
+	{
+		let mut h = DefaultHasher::new();
+		msg.hash( &mut h );
+		h.finish()
+	};
+
+	let borrowed = WsMessage::from( "ping"               );
+	let owned    = WsMessage::from( "ping".to_string()   );
+
+	assert_eq!( borrowed, owned                            );
+	assert_eq!( borrowed, WsMessage::Text( "ping".into() ) );
+	assert_eq!( hash( &borrowed ), hash( &owned )          );
+
+	assert_eq!( WsMessage::Binary( vec![ 1, 2 ] ), WsMessage::from( vec![ 1, 2 ] ) );
+}
+
+
+
+// to_uint8array and to_array_buffer encode text as UTF-8, from_uint8array respects the view.
+//
+#[ wasm_bindgen_test ]
+//
+fn js_buffers()
+{
+	let text = WsMessage::from( "é" );
+	let bin  = WsMessage::Binary( vec![ 1, 2, 3, 4 ] );
+
+	assert_eq!( vec![ 0xc3, 0xa9 ]    , text.to_uint8array().to_vec() );
+	assert_eq!( 2                      , text.to_array_buffer().byte_length() );
+	assert_eq!( vec![ 1, 2, 3, 4 ]    , bin .to_uint8array().to_vec() );
+
+	assert_eq!( bin, WsMessage::from_array_buffer( &bin.to_array_buffer() ) );
+
+	let view = Uint8Array::new_with_byte_offset_and_length( &bin.to_array_buffer(), 1, 2 );
+
+	assert_eq!( WsMessage::Binary( vec![ 2, 3 ] ), WsMessage::from_uint8array( &view ) );
+}
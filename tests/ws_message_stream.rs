@@ -0,0 +1,190 @@
+#![ feature( async_await, trait_alias )]
+wasm_bindgen_test_configure!(run_in_browser);
+
+
+
+// What's tested:
+//
+// Tests send to an echo server which just bounces back all data.
+//
+// ✔ Binary messages under the threshold arrive whole, big ones as a stream in chunks
+// ✔ The next message waits until the stream is dropped
+// ✔ Dropping the stream while a chunk is being read doesn't break the connection
+// ✔ Used as a Stream, WsIo yields MessageTooBig for big messages and continues
+//
+use
+{
+	futures_01            :: Future as Future01,
+	futures::prelude      :: * ,
+	wasm_bindgen::prelude :: * ,
+	wasm_bindgen_test     :: * ,
+	ws_stream_wasm        :: * ,
+	log                   :: * ,
+	std                   :: time::Duration,
+};
+
+
+
+const URL: &str = "ws://127.0.0.1:3212/";
+
+
+
+// Binary messages under the threshold arrive whole, big ones as a stream in chunks
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn chunks() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: chunks" );
+
+	async
+	{
+		let config          = WsConfig::default().stream_threshold( 10 );
+		let (_ws, mut wsio) = WsStream::connect_with_config( URL, config ).await.expect_throw( "Could not create websocket" );
+		let big             = ( 0..100u8 ).collect::<Vec<u8>>();
+
+		wsio.send( WsMessage::Binary( vec![ 1, 2, 3 ] ) ).await.expect_throw( "send" );
+		wsio.send( WsMessage::Binary( big.clone()     ) ).await.expect_throw( "send" );
+
+		match wsio.next_incoming().await.unwrap_throw().expect_throw( "small" )
+		{
+			WsIncoming::Message( msg ) => assert_eq!( WsMessage::Binary( vec![ 1, 2, 3 ] ), msg ),
+			other                      => panic!( "unexpected: {:?}", other ),
+		}
+
+		let stream = match wsio.next_incoming().await.unwrap_throw().expect_throw( "big" )
+		{
+			WsIncoming::Stream( s ) => s.chunk_size( 30 ),
+			other                   => panic!( "unexpected: {:?}", other ),
+		};
+
+		assert_eq!( 100, stream.size() );
+
+		let chunks: Vec<Vec<u8>> = stream.map( |c| c.expect_throw( "chunk" ) ).collect().await;
+
+		assert_eq!( vec![ 30, 30, 30, 10 ], chunks.iter().map( Vec::len ).collect::<Vec<_>>() );
+		assert_eq!( big, chunks.concat() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// The next message waits until the stream is dropped
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn ordering() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: ordering" );
+
+	async
+	{
+		let config          = WsConfig::default().stream_threshold( 10 );
+		let (_ws, mut wsio) = WsStream::connect_with_config( URL, config ).await.expect_throw( "Could not create websocket" );
+		let mut sub         = wsio.subscribe();
+
+		wsio.send( WsMessage::Binary( vec![ 0; 50 ]        ) ).await.expect_throw( "send" );
+		wsio.send( WsMessage::Text  ( "after".to_string() ) ).await.expect_throw( "send" );
+
+		let stream = match wsio.next_incoming().await.unwrap_throw().expect_throw( "big" )
+		{
+			WsIncoming::Stream( s ) => s,
+			other                   => panic!( "unexpected: {:?}", other ),
+		};
+
+		// Subscribers don't see the big message, so this is the text.
+		//
+		sub.next().await.unwrap_throw();
+
+		assert!( wsio.try_next_now().is_none() );
+		assert!( wsio.peek()        .is_none() );
+
+		drop( stream );
+
+		assert_eq!( Some( Ok( WsMessage::Text( "after".to_string() ) ) ), wsio.peek() );
+		assert_eq!( WsMessage::Text( "after".to_string() ), wsio.next().await.unwrap_throw().expect_throw( "text" ) );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Dropping the stream while a chunk is being read doesn't break the connection
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn drop_while_reading() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: drop_while_reading" );
+
+	async
+	{
+		let config          = WsConfig::default().stream_threshold( 10 );
+		let (_ws, mut wsio) = WsStream::connect_with_config( URL, config ).await.expect_throw( "Could not create websocket" );
+
+		wsio.send( WsMessage::Binary( vec![ 0; 50 ]        ) ).await.expect_throw( "send" );
+		wsio.send( WsMessage::Text  ( "after".to_string() ) ).await.expect_throw( "send" );
+
+		let mut stream = match wsio.next_incoming().await.unwrap_throw().expect_throw( "big" )
+		{
+			WsIncoming::Stream( s ) => s.chunk_size( 10 ),
+			other                   => panic!( "unexpected: {:?}", other ),
+		};
+
+		// Starts reading the first chunk, which can't be done yet.
+		//
+		assert!( stream.next().now_or_never().is_none() );
+
+		drop( stream );
+
+		// Let the read finish after its reader is gone.
+		//
+		JsTimer.sleep( Duration::from_millis( 50 ) ).await;
+
+		assert_eq!( WsMessage::Text( "after".to_string() ), wsio.next().await.unwrap_throw().expect_throw( "text" ) );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Used as a Stream, WsIo yields MessageTooBig for big messages and continues
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn plain_stream() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: plain_stream" );
+
+	async
+	{
+		let config          = WsConfig::default().stream_threshold( 10 );
+		let (_ws, mut wsio) = WsStream::connect_with_config( URL, config ).await.expect_throw( "Could not create websocket" );
+
+		wsio.send( WsMessage::Binary( vec![ 0; 50 ] ) ).await.expect_throw( "send" );
+		wsio.send( WsMessage::Binary( vec![ 7; 5  ] ) ).await.expect_throw( "send" );
+
+		let err = wsio.next().await.unwrap_throw().expect_err( "too big" );
+
+		assert_eq!( &WsErrKind::MessageTooBig{ size: 50, limit: 10 }, err.kind() );
+		assert_eq!( WsMessage::Binary( vec![ 7; 5 ] ), wsio.next().await.unwrap_throw().expect_throw( "small" ) );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
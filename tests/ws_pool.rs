@@ -0,0 +1,177 @@
+#![ feature( async_await, trait_alias )]
+wasm_bindgen_test_configure!(run_in_browser);
+
+
+
+// What's tested:
+//
+// Tests send to an echo server which just bounces back all data, so every message comes back on the
+// connection it was sent on.
+//
+// ✔ Round robin uses every connection in turn and the order on each connection is kept.
+// ✔ When a connection closes, the pool shrinks and the others carry on.
+// ✔ With replace, a closed connection is replaced in the same slot.
+// ✔ LeastBuffered sends on an open connection.
+//
+use
+{
+	futures_01            :: Future as Future01,
+	futures::prelude      :: * ,
+	wasm_bindgen::prelude :: * ,
+	wasm_bindgen_test     :: * ,
+	ws_stream_wasm        :: * ,
+	log                   :: * ,
+};
+
+
+
+const URL_TT: &str = "ws://127.0.0.1:3312/";
+
+
+
+// Round robin uses every connection in turn and the order on each connection is kept.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn round_robin() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: round_robin" );
+
+	async
+	{
+		let mut pool = WsPool::connect( URL_TT, 3, WsConfig::default() ).await.expect_throw( "connect pool" );
+
+		assert_eq!( 3, pool.len () );
+		assert_eq!( 3, pool.size() );
+
+		for i in 0..6 { pool.send( WsMessage::Text( i.to_string() ) ).await.expect_throw( "send" ); }
+
+		let mut got: Vec< Vec<String> > = vec![ Vec::new(); 3 ];
+
+		for _ in 0..6
+		{
+			let ( index, msg ) = pool.next().await.expect_throw( "pool ended" );
+
+			match msg.expect_throw( "receive" )
+			{
+				WsMessage::Text( t ) => got[ index ].push( t ),
+				WsMessage::Binary(_) => panic!( "unexpected binary" ),
+			}
+		}
+
+		assert_eq!( vec![ "0", "3" ], got[ 0 ] );
+		assert_eq!( vec![ "1", "4" ], got[ 1 ] );
+		assert_eq!( vec![ "2", "5" ], got[ 2 ] );
+
+		pool.close().await.expect_throw( "close" );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// When a connection closes, the pool shrinks and the others carry on.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn shrink() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: shrink" );
+
+	async
+	{
+		let mut pool = WsPool::connect( URL_TT, 2, WsConfig::default() ).await.expect_throw( "connect pool" );
+
+		pool.stream( 0 ).unwrap_throw().clone().close().await;
+
+		for _ in 0..2 { pool.send( WsMessage::from( "hi" ) ).await.expect_throw( "send" ); }
+
+		for _ in 0..2
+		{
+			let ( index, msg ) = pool.next().await.expect_throw( "pool ended" );
+
+			assert_eq!( 1, index );
+			assert_eq!( WsMessage::from( "hi" ), msg.expect_throw( "receive" ) );
+		}
+
+		assert_eq!( 1, pool.len() );
+
+		pool.stream( 1 ).unwrap_throw().clone().close().await;
+
+		assert!( pool.next().await.is_none() );
+		assert_eq!( &WsErrKind::ConnectionNotOpen, pool.send( WsMessage::from( "late" ) ).await.unwrap_err().kind() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// With replace, a closed connection is replaced in the same slot.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn replace() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: replace" );
+
+	async
+	{
+		let mut pool = WsPool::connect( URL_TT, 1, WsConfig::default() ).await.expect_throw( "connect pool" ).replace( true );
+
+		pool.stream( 0 ).unwrap_throw().clone().close().await;
+
+		// Sending waits for the replacement.
+		//
+		pool.send( WsMessage::from( "again" ) ).await.expect_throw( "send" );
+
+		let ( index, msg ) = pool.next().await.expect_throw( "pool ended" );
+
+		assert_eq!( 0, index );
+		assert_eq!( WsMessage::from( "again" ), msg.expect_throw( "receive" ) );
+		assert_eq!( WsState::Open, pool.stream( 0 ).unwrap_throw().ready_state() );
+
+		pool.close().await.expect_throw( "close" );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// LeastBuffered sends on an open connection.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn least_buffered() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: least_buffered" );
+
+	async
+	{
+		let mut pool = WsPool::connect( URL_TT, 2, WsConfig::default() ).await.expect_throw( "connect pool" ).balance( Balance::LeastBuffered );
+
+		pool.send( WsMessage::from( "hi" ) ).await.expect_throw( "send" );
+
+		let ( _, msg ) = pool.next().await.expect_throw( "pool ended" );
+
+		assert_eq!( WsMessage::from( "hi" ), msg.expect_throw( "receive" ) );
+
+		pool.close().await.expect_throw( "close" );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
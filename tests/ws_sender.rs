@@ -0,0 +1,732 @@
+#![ feature( async_await, trait_alias )]
+wasm_bindgen_test_configure!(run_in_browser);
+
+
+
+// What's tested:
+//
+// Tests send to an echo server which just bounces back all data.
+//
+// ✔ Send through two shared references to the same WsSender concurrently within one task.
+// ✔ Send on a WsSender after the connection was closed and verify the error.
+// ✔ Sending a message bigger than max_send_size returns MessageTooBig, counting UTF-8 bytes.
+// ✔ send_unchecked bypasses max_send_size.
+// ✔ send_text_ref sends, checks max_send_size, runs interceptors and fails after close.
+// ✔ send_text sends, checks max_send_size, runs interceptors and fails after close.
+// ✔ send_binary sends from a borrowed slice and fails after close.
+// ✔ send_timeout sends when the connection is ready, even with a zero timeout, and the sender keeps working.
+// ✔ send_timeout on a closed connection returns ConnectionNotOpen rather than Timeout.
+// ✔ send_timeout waits while bufferedAmount is above send_high_water and times out with Timeout.    (feature mock)
+// ✔ send_timeout sends once bufferedAmount drains below send_high_water.                          (feature mock)
+// ✔ Two tasks sending through &WsSender sinks that wait on send_high_water are both woken.       (feature mock)
+// ✔ send_with_ttl sends when the connection is ready, even with a zero ttl, and nothing is counted as expired.
+// ✔ send_with_ttl drops and counts a message that is still held back by send_high_water when the ttl passes. (feature mock)
+// ✔ SendBuffer::send_or_buffer buffers while bufferedAmount is above send_high_water.               (feature mock)
+// ✔ Sending while the connection is closing returns ConnectionNotOpen, and closing a sender again returns Ok.
+//
+use
+{
+	futures_01            :: Future as Future01,
+	futures::prelude      :: * ,
+	futures::future       :: join ,
+	wasm_bindgen::prelude :: * ,
+	wasm_bindgen_test     :: * ,
+	ws_stream_wasm        :: * ,
+	log                   :: * ,
+	std                   :: { rc::Rc, time::Duration } ,
+};
+
+#[ cfg( feature = "mock" ) ]
+//
+use futures::channel::oneshot;
+
+
+
+
+const URL   : &str = "ws://127.0.0.1:3212/";
+const URL_TT: &str = "ws://127.0.0.1:3312/";
+
+
+
+// Send through two shared references to the same WsSender concurrently within one task.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn shared_sender() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: shared_sender" );
+
+	async
+	{
+		let (_ws, mut wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let sender          = Rc::new( wsio.sender() );
+		let s2              = sender.clone();
+
+		let a = async move
+		{
+			let mut tx = &*sender;
+			tx.send( WsMessage::Text( "a".to_string() ) ).await
+		};
+
+		let b = async move
+		{
+			let mut tx = &*s2;
+			tx.send( WsMessage::Text( "b".to_string() ) ).await
+		};
+
+
+		let (ra, rb) = join( a, b ).await;
+
+		ra.expect_throw( "send a" );
+		rb.expect_throw( "send b" );
+
+
+		let mut received =
+		[
+			wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" ),
+			wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" ),
+		];
+
+		received.sort_by_key( |m| Vec::<u8>::from( m.clone() ) );
+
+		assert_eq!( WsMessage::Text( "a".to_string() ), received[0] );
+		assert_eq!( WsMessage::Text( "b".to_string() ), received[1] );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Send on a WsSender after the connection was closed and verify the error.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn send_after_close() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: send_after_close" );
+
+	async
+	{
+		let (ws, wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let mut tx     = wsio.sender();
+
+		ws.close().await;
+
+		let res = tx.send( WsMessage::Text( "a".to_string() ) ).await;
+
+		assert_eq!( &WsErrKind::ConnectionNotOpen, res.unwrap_err().kind() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Sending a message bigger than max_send_size returns MessageTooBig, counting UTF-8 bytes.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn max_send_size() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: max_send_size" );
+
+	async
+	{
+		let config      = WsConfig::default().max_send_size( 4 );
+		let (_ws, wsio) = WsStream::connect_with_config( URL_TT, config ).await.expect_throw( "Could not create websocket" );
+		let mut tx      = wsio.sender();
+
+		// 3 chars, but 6 bytes in UTF-8
+		//
+		let res = tx.send( WsMessage::Text( "ééé".to_string() ) ).await;
+
+		assert_eq!( &WsErrKind::MessageTooBig{ size: 6, limit: 4 }, res.unwrap_err().kind() );
+
+		let res = tx.send( WsMessage::Binary( vec![ 1, 2, 3, 4, 5 ] ) ).await;
+
+		assert_eq!( &WsErrKind::MessageTooBig{ size: 5, limit: 4 }, res.unwrap_err().kind() );
+
+		tx.send( WsMessage::Binary( vec![ 1, 2, 3, 4 ] ) ).await.expect_throw( "send at the limit" );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// send_unchecked bypasses max_send_size.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn send_unchecked() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: send_unchecked" );
+
+	async
+	{
+		let config          = WsConfig::default().max_send_size( 4 );
+		let (_ws, mut wsio) = WsStream::connect_with_config( URL_TT, config ).await.expect_throw( "Could not create websocket" );
+		let message         = WsMessage::Text( "too long".to_string() );
+
+		wsio.sender().send_unchecked( message.clone() ).expect_throw( "send_unchecked" );
+
+		let echo = wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" );
+
+		assert_eq!( message, echo );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// send_text_ref sends, checks max_send_size, runs interceptors and fails after close.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn send_text_ref() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: send_text_ref" );
+
+	struct Upper;
+
+	impl WsInterceptor for Upper
+	{
+		fn on_send( &mut self, msg: WsMessage ) -> Option<WsMessage>
+		{
+			match msg
+			{
+				WsMessage::Text( t ) => Some( WsMessage::Text( t.to_uppercase() ) ),
+				other                => Some( other ),
+			}
+		}
+	}
+
+	async
+	{
+		let config         = WsConfig::default().max_send_size( 4 );
+		let (ws, mut wsio) = WsStream::connect_with_config( URL_TT, config ).await.expect_throw( "Could not create websocket" );
+
+		wsio.send_text_ref( "ping" ).expect_throw( "send_text_ref" );
+
+		assert_eq!( WsMessage::from( "ping" ), wsio.next().await.unwrap_throw().expect_throw( "Receive message" ) );
+
+		let res = wsio.send_text_ref( "pings" );
+
+		assert_eq!( &WsErrKind::MessageTooBig{ size: 5, limit: 4 }, res.unwrap_err().kind() );
+
+
+		wsio.add_interceptor( Upper );
+		wsio.sender().send_text_ref( "pong" ).expect_throw( "send_text_ref" );
+
+		assert_eq!( WsMessage::from( "PONG" ), wsio.next().await.unwrap_throw().expect_throw( "Receive message" ) );
+
+
+		ws.close().await;
+
+		assert_eq!( &WsErrKind::ConnectionNotOpen, wsio.send_text_ref( "a" ).unwrap_err().kind() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// send_text sends, checks max_send_size, runs interceptors and fails after close.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn send_text() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: send_text" );
+
+	struct Prefix;
+
+	impl WsInterceptor for Prefix
+	{
+		fn on_send( &mut self, msg: WsMessage ) -> Option<WsMessage>
+		{
+			match msg
+			{
+				WsMessage::Text( t ) => Some( WsMessage::Text( format!( ">{}", t ) ) ),
+				other                => Some( other ),
+			}
+		}
+	}
+
+	async
+	{
+		let config         = WsConfig::default().max_send_size( 4 );
+		let (ws, mut wsio) = WsStream::connect_with_config( URL_TT, config ).await.expect_throw( "Could not create websocket" );
+
+		ws.send_text( "ping" ).await.expect_throw( "send_text" );
+		ws.send_text( String::from( "pong" ) ).await.expect_throw( "send_text" );
+
+		assert_eq!( WsMessage::from( "ping" ), wsio.next().await.unwrap_throw().expect_throw( "Receive message" ) );
+		assert_eq!( WsMessage::from( "pong" ), wsio.next().await.unwrap_throw().expect_throw( "Receive message" ) );
+
+		let res = ws.sender().send_text( "pings" ).await;
+
+		assert_eq!( &WsErrKind::MessageTooBig{ size: 5, limit: 4 }, res.unwrap_err().kind() );
+
+
+		wsio.add_interceptor( Prefix );
+		ws.send_text( "hi" ).await.expect_throw( "send_text" );
+
+		assert_eq!( WsMessage::from( ">hi" ), wsio.next().await.unwrap_throw().expect_throw( "Receive message" ) );
+
+
+		ws.close().await;
+
+		assert_eq!( &WsErrKind::ConnectionNotOpen, ws.send_text( "a" ).await.unwrap_err().kind() );
+
+		// The Sink gives the same error.
+		//
+		assert_eq!( &WsErrKind::ConnectionNotOpen, wsio.send( WsMessage::from( "a" ) ).await.unwrap_err().kind() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// send_binary sends from a borrowed slice and fails after close.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn send_binary() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: send_binary" );
+
+	async
+	{
+		let (ws, mut wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
+		let sender         = ws.sender();
+		let bytes          = vec![ 1u8, 2, 3 ];
+
+		sender.send_binary( &bytes      ).await.expect_throw( "send_binary" );
+		ws    .send_binary( &bytes[..2] ).await.expect_throw( "send_binary" );
+
+		assert_eq!( WsMessage::Binary( bytes.clone() ), wsio.next().await.unwrap_throw().expect_throw( "Receive message" ) );
+		assert_eq!( WsMessage::Binary( vec![ 1, 2 ] ) , wsio.next().await.unwrap_throw().expect_throw( "Receive message" ) );
+
+		ws.close().await;
+
+		assert_eq!( &WsErrKind::ConnectionNotOpen, sender.send_binary( bytes ).await.unwrap_err().kind() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// send_timeout sends when the connection is ready, even with a zero timeout, and the sender keeps working.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn send_timeout() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: send_timeout" );
+
+	async
+	{
+		let (_ws, mut wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let sender          = wsio.sender();
+		let first           = WsMessage::Text( "first"  .to_string() );
+		let second          = WsMessage::Text( "second" .to_string() );
+
+		sender.send_timeout( first.clone(), Duration::from_millis( 0 ) ).await.expect_throw( "send_timeout" );
+		sender.send_timeout( second.clone(), Duration::from_secs( 2 ) ).await.expect_throw( "send_timeout" );
+
+		assert_eq!( first , wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" ) );
+		assert_eq!( second, wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" ) );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// send_timeout on a closed connection returns ConnectionNotOpen rather than Timeout.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn send_timeout_closed() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: send_timeout_closed" );
+
+	async
+	{
+		let (ws, wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let sender     = wsio.sender();
+
+		ws.close().await;
+
+		let err = sender.send_timeout( WsMessage::Text( "late".to_string() ), Duration::from_secs( 2 ) ).await.unwrap_err();
+
+		assert_eq!( &WsErrKind::ConnectionNotOpen, err.kind() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Connect with a high water mark of 0 and a MockTimer, and queue a big message, so the browser has data
+// buffered until we yield to the event loop.
+//
+#[ cfg( feature = "mock" ) ]
+//
+async fn backpressure() -> ( WsStream, WsIo, MockTimer, WsMessage )
+{
+	let timer          = MockTimer::new();
+	let config         = WsConfig::default().send_high_water( 0 ).timer( timer.clone() );
+	let (ws, wsio)     = WsStream::connect_with_config( URL_TT, config ).await.expect_throw( "Could not create websocket" );
+	let big            = WsMessage::Binary( vec![ 7; 1024 * 1024 ] );
+
+	wsio.sender().send_unchecked( big.clone() ).expect_throw( "send_unchecked" );
+
+	assert!( ws.buffered_amount() > 0 );
+
+	( ws, wsio, timer, big )
+}
+
+
+
+// send_timeout waits while bufferedAmount is above send_high_water and times out with Timeout.
+//
+#[ cfg( feature = "mock" ) ]
+#[ wasm_bindgen_test(async) ]
+//
+pub fn send_timeout_backpressure() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: send_timeout_backpressure" );
+
+	async
+	{
+		let ( _ws, mut wsio, timer, big ) = backpressure().await;
+		let sender                        = wsio.sender();
+		let mut send                      = Box::pin( sender.send_timeout( WsMessage::from( "late" ), Duration::from_secs( 1 ) ) );
+
+		// Nothing is sent while we don't yield, so there still is data buffered.
+		//
+		assert!( (&mut send).now_or_never().is_none() );
+
+		timer.advance( Duration::from_secs( 1 ) );
+
+		assert_eq!( &WsErrKind::Timeout, send.await.unwrap_err().kind() );
+
+
+		// The late message was not sent.
+		//
+		sender.send_unchecked( WsMessage::from( "after" ) ).expect_throw( "send_unchecked" );
+
+		assert_eq!( big                      , wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" ) );
+		assert_eq!( WsMessage::from( "after" ), wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" ) );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// send_timeout sends once bufferedAmount drains below send_high_water.
+//
+#[ cfg( feature = "mock" ) ]
+#[ wasm_bindgen_test(async) ]
+//
+pub fn send_timeout_drained() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: send_timeout_drained" );
+
+	async
+	{
+		let ( _ws, mut wsio, timer, big ) = backpressure().await;
+		let sender                        = wsio.sender();
+		let mut send                      = Box::pin( sender.send_timeout( WsMessage::from( "next" ), Duration::from_secs( 3600 ) ) );
+
+		// Yield to the browser so it can send, and move the mock clock so the sender checks bufferedAmount again.
+		//
+		loop
+		{
+			if let Some( res ) = (&mut send).now_or_never()
+			{
+				res.expect_throw( "send_timeout" );
+				break;
+			}
+
+			JsTimer.sleep( Duration::from_millis( 10 ) ).await;
+			timer.advance( Duration::from_millis( 10 ) );
+		}
+
+		assert_eq!( big                     , wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" ) );
+		assert_eq!( WsMessage::from( "next" ), wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" ) );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Two tasks sending through &WsSender sinks that wait on send_high_water are both woken.
+//
+#[ cfg( feature = "mock" ) ]
+#[ wasm_bindgen_test(async) ]
+//
+pub fn shared_sender_backpressure() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: shared_sender_backpressure" );
+
+	async
+	{
+		let ( _ws, mut wsio, timer, big ) = backpressure().await;
+		let sender                        = Rc::new( wsio.sender() );
+		let ( tx_a, rx_a )                = oneshot::channel();
+		let ( tx_b, rx_b )                = oneshot::channel();
+
+		// Each send runs in it's own task, so each has it's own waker.
+		//
+		for ( text, tx ) in [ ( "a", tx_a ), ( "b", tx_b ) ]
+		{
+			let sender = sender.clone();
+
+			RtSpawner.spawn_local( async move
+			{
+				let mut sink = &*sender;
+
+				let _ = tx.send( sink.send( WsMessage::from( text ) ).await );
+
+			}.boxed_local() );
+		}
+
+
+		// Yield to the browser so it can send, and move the mock clock so the senders check bufferedAmount again.
+		//
+		let mut both = join( rx_a, rx_b );
+
+		for i in 0..
+		{
+			if let Some(( a, b )) = (&mut both).now_or_never()
+			{
+				a.expect_throw( "task a" ).expect_throw( "send a" );
+				b.expect_throw( "task b" ).expect_throw( "send b" );
+				break;
+			}
+
+			assert!( i < 1000, "a task waiting on send_high_water was never woken" );
+
+			JsTimer.sleep( Duration::from_millis( 10 ) ).await;
+			timer.advance( Duration::from_millis( 10 ) );
+		}
+
+
+		assert_eq!( big, wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" ) );
+
+		let mut received =
+		[
+			wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" ),
+			wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" ),
+		];
+
+		received.sort_by_key( |m| Vec::<u8>::from( m.clone() ) );
+
+		assert_eq!( WsMessage::from( "a" ), received[0] );
+		assert_eq!( WsMessage::from( "b" ), received[1] );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// send_with_ttl sends when the connection is ready, even with a zero ttl, and nothing is counted as expired.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn send_with_ttl() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: send_with_ttl" );
+
+	async
+	{
+		let (_ws, mut wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let sender          = wsio.sender();
+		let msg             = WsMessage::Text( "cursor".to_string() );
+
+		sender.send_with_ttl( msg.clone(), Duration::from_millis( 0 ) ).await.expect_throw( "send_with_ttl" );
+
+		assert_eq!( msg, wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" ) );
+		assert_eq!( 0  , sender.clone().expired() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// send_with_ttl drops and counts a message that is still held back by send_high_water when the ttl passes.
+//
+#[ cfg( feature = "mock" ) ]
+#[ wasm_bindgen_test(async) ]
+//
+pub fn send_with_ttl_expired() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: send_with_ttl_expired" );
+
+	async
+	{
+		let ( _ws, mut wsio, timer, big ) = backpressure().await;
+		let sender                        = wsio.sender();
+		let mut send                      = Box::pin( sender.send_with_ttl( WsMessage::from( "cursor" ), Duration::from_millis( 50 ) ) );
+
+		assert!( (&mut send).now_or_never().is_none() );
+
+		timer.advance( Duration::from_millis( 50 ) );
+
+		send.await.expect_throw( "send_with_ttl" );
+
+		assert_eq!( 1, sender.expired() );
+		assert_eq!( 1, wsio.sender().expired() );
+
+
+		// The expired message was not sent.
+		//
+		sender.send_unchecked( WsMessage::from( "after" ) ).expect_throw( "send_unchecked" );
+
+		assert_eq!( big                      , wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" ) );
+		assert_eq!( WsMessage::from( "after" ), wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" ) );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// SendBuffer::send_or_buffer buffers while bufferedAmount is above send_high_water.
+//
+#[ cfg( feature = "mock" ) ]
+#[ wasm_bindgen_test(async) ]
+//
+pub fn send_or_buffer_backpressure() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: send_or_buffer_backpressure" );
+
+	async
+	{
+		let ( _ws, mut wsio, timer, big ) = backpressure().await;
+		let mut sender                    = wsio.sender();
+		let mut buffer                    = SendBuffer::new();
+
+		buffer.send_or_buffer( &sender, WsMessage::from( "queued" ) ).expect_throw( "send_or_buffer" );
+
+		assert_eq!( 1, buffer.len() );
+
+
+		// Yield to the browser so it can send, and move the mock clock so flush checks bufferedAmount again.
+		//
+		let mut flush = Box::pin( buffer.flush( &mut sender ) );
+
+		for i in 0..
+		{
+			if let Some( result ) = (&mut flush).now_or_never()
+			{
+				result.expect_throw( "flush" );
+				break;
+			}
+
+			assert!( i < 1000, "flush waiting on send_high_water was never woken" );
+
+			JsTimer.sleep( Duration::from_millis( 10 ) ).await;
+			timer.advance( Duration::from_millis( 10 ) );
+		}
+
+
+		assert_eq!( big                       , wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" ) );
+		assert_eq!( WsMessage::from( "queued" ), wsio.next().await.expect_throw( "Stream closed" ).expect_throw( "Receive message" ) );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Sending while the connection is closing returns ConnectionNotOpen, and closing a sender again returns Ok.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn send_while_closing() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: send_while_closing" );
+
+	async
+	{
+		let (ws, wsio) = WsStream::connect( URL_TT ).await.expect_throw( "Could not create websocket" );
+		let mut tx     = wsio.sender();
+		let mut tx2    = wsio.sender();
+
+		// Start closing, without waiting for the close event.
+		//
+		assert!( (&mut tx.close()).now_or_never().is_none() );
+		assert_eq!( WsState::Closing, ws.ready_state() );
+
+		let err = tx2.send( WsMessage::Text( "a".to_string() ) ).await.unwrap_err();
+
+		assert_eq!( &WsErrKind::ConnectionNotOpen, err.kind() );
+
+		tx2.close().await.expect_throw( "close while closing" );
+		tx .close().await.expect_throw( "close after closed"  );
+
+		assert_eq!( WsState::Closed, ws.ready_state() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
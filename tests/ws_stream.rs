@@ -15,19 +15,32 @@ wasm_bindgen_test_configure!(run_in_browser);
 // ✔ Verify url method
 // ✔ Verify sending no subprotocols
 //   note: we currently don't have a backend server that supports protocols,
-//   so there is no test for testing usage of protocols
+//   so there is no test for testing usage of protocols, nor for WsConfig::require_protocol
 // ✔ Verify closing with a valid code
 // ✔ Verify error upon closing with invalid code
 // ✔ Verify closing with a valid code and reason
 // ✔ Verfiy close_reason with an invalid close code
 // ✔ Verfiy close_reason with an invalid reason string
 // ✔ Verfiy Debug impl
+// ✔ Verify closed resolves when the connection is closed from WsIo
+// ✔ Verify closed resolves immediately when the connection is already closed
+// ✔ Verify close doesn't hang when the connection is already closed
+// ✔ Verify aborting a pending connection attempt through the AbortHandle
+// ✔ Verify close_event is None while open and is kept on both halves after the connection closed
+// ✔ Verify ready resolves once the buffer drained after a big send
+// ✔ Verify ready fails when the connection closes while waiting, or is already closed
+// ✔ Verify clones share the connection and keep working after the original is dropped
+// ✔ Verify closing from one clone while sending through another gives ConnectionNotOpen
+// ✔ Verify state_signal starts with the current state, follows the close and then ends
 //
 use
 {
 	futures_01            :: { Future as Future01 } ,
 	futures::prelude      :: { *                  } ,
 	futures               :: { sink::SinkExt      } ,
+	futures::future       :: { poll_fn            } ,
+	std                   :: { pin::Pin           } ,
+	futures::future       :: { join               } ,
 	wasm_bindgen::prelude :: { *                  } ,
 	wasm_bindgen_test     :: { *                  } ,
 	ws_stream_wasm        :: { *                  } ,
@@ -52,7 +65,7 @@ pub fn connect_wrong_port() -> impl Future01<Item = (), Error = JsValue>
 
 	async
 	{
-		let err = WsStream::connect( "ws://127.0.0.1:33212/", None ).await;
+		let err = WsStream::connect( "ws://127.0.0.1:33212/" ).await;
 
 		assert!( err.is_err() );
 
@@ -91,7 +104,7 @@ pub fn connect_forbidden_port() -> impl Future01<Item = (), Error = JsValue>
 
 	async
 	{
-		let err = WsStream::connect( "ws://127.0.0.1:6666/", None ).await;
+		let err = WsStream::connect( "ws://127.0.0.1:6666/" ).await;
 
 		assert!( err.is_err() );
 
@@ -119,7 +132,7 @@ pub fn connect_wrong_wss() -> impl Future01<Item = (), Error = JsValue>
 
 	async
 	{
-		let err = WsStream::connect( "wss://127.0.0.1:3212/", None ).await;
+		let err = WsStream::connect( "wss://127.0.0.1:3212/" ).await;
 
 		assert!( err.is_err() );
 
@@ -158,7 +171,7 @@ pub fn connect_wrong_scheme() -> impl Future01<Item = (), Error = JsValue>
 
 	async
 	{
-		let err = WsStream::connect( "http://127.0.0.1:3212/", None ).await;
+		let err = WsStream::connect( "http://127.0.0.1:3212/" ).await;
 
 		assert!( err.is_err() );
 
@@ -185,7 +198,7 @@ pub fn state() -> impl Future01<Item = (), Error = JsValue>
 
 	async
 	{
-		let (ws, wsio) = WsStream::connect( URL, None ).await.expect_throw( "Could not create websocket" );
+		let (ws, wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
 
 		assert_eq!( WsState::Open, ws  .ready_state() );
 		assert_eq!( WsState::Open, wsio.ready_state() );
@@ -218,7 +231,7 @@ pub fn close_from_wsio() -> impl Future01<Item = (), Error = JsValue>
 
 	async
 	{
-		let (ws, mut wsio) = WsStream::connect( URL, None ).await.expect_throw( "Could not create websocket" );
+		let (ws, mut wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
 
 		assert_eq!( WsState::Open, ws.ready_state() );
 
@@ -247,7 +260,7 @@ pub fn url() -> impl Future01<Item = (), Error = JsValue>
 
 	async
 	{
-		let (ws, _wsio) = WsStream::connect( URL, None ).await.expect_throw( "Could not create websocket" );
+		let (ws, _wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
 
 		assert_eq!( URL, ws.url() );
 
@@ -271,7 +284,7 @@ pub fn no_protocols() -> impl Future01<Item = (), Error = JsValue>
 
 	async
 	{
-		let (ws, _wsio) = WsStream::connect( URL, None ).await.expect_throw( "Could not create websocket" );
+		let (ws, _wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
 
 		assert_eq!( "", ws.protocol() );
 
@@ -297,7 +310,7 @@ pub fn protocols_server_accept_none() -> impl Future01<Item = (), Error = JsValu
 
 	async
 	{
-		let (ws, _wsio) = WsStream::connect( URL, vec![ "chat" ] ).await.expect_throw( "Could not create websocket" );
+		let (ws, _wsio) = WsStream::connect_with_protocols( URL, vec![ "chat" ] ).await.expect_throw( "Could not create websocket" );
 
 		assert_eq!( "", ws.protocol() );
 
@@ -323,7 +336,7 @@ pub fn close_code_valid() -> impl Future01<Item = (), Error = JsValue>
 
 	async
 	{
-		let (ws, _wsio) = WsStream::connect( URL, None ).await.expect_throw( "Could not create websocket" );
+		let (ws, _wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
 
 		let res = ws.close_code( 1000 ).await;
 
@@ -347,7 +360,7 @@ pub fn close_code_invalid() -> impl Future01<Item = (), Error = JsValue>
 
 	async
 	{
-		let (ws, _wsio) = WsStream::connect( URL, None ).await.expect_throw( "Could not create websocket" );
+		let (ws, _wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
 
 		let res = ws.close_code( 500 ).await;
 
@@ -371,7 +384,7 @@ pub fn close_reason_valid() -> impl Future01<Item = (), Error = JsValue>
 
 	async
 	{
-		let (ws, _wsio) = WsStream::connect( URL, None ).await.expect_throw( "Could not create websocket" );
+		let (ws, _wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
 
 		let res = ws.close_reason( 1000, "Normal shutdown" ).await;
 
@@ -395,7 +408,7 @@ pub fn close_reason_invalid_code() -> impl Future01<Item = (), Error = JsValue>
 
 	async
 	{
-		let (ws, _wsio) = WsStream::connect( URL, None ).await.expect_throw( "Could not create websocket" );
+		let (ws, _wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
 
 		let res = ws.close_reason( 500, "Normal Shutdown" ).await;
 
@@ -419,7 +432,7 @@ pub fn close_reason_invalid() -> impl Future01<Item = (), Error = JsValue>
 
 	async
 	{
-		let (ws, _wsio) = WsStream::connect( URL, None ).await.expect_throw( "Could not create websocket" );
+		let (ws, _wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
 
 		let res = ws.close_reason( 1000, vec![ "a"; 124 ].join( "" ) ).await;
 
@@ -444,7 +457,7 @@ pub fn debug() -> impl Future01<Item = (), Error = JsValue>
 
 	async
 	{
-		let (ws, _wsio) = WsStream::connect( URL, None ).await.expect_throw( "Could not create websocket" );
+		let (ws, _wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
 
 		assert_eq!( format!( "WsStream for connection: {}", URL ), format!( "{:?}", ws ) );
 
@@ -453,3 +466,329 @@ pub fn debug() -> impl Future01<Item = (), Error = JsValue>
 	}.boxed_local().compat()
 }
 
+
+
+
+// Verify closed resolves when the connection is closed from WsIo, and that each call
+// gets it's own future.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn closed() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: closed" );
+
+	async
+	{
+		let (ws, mut wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
+
+		let first  = ws.closed();
+		let second = ws.closed();
+
+		SinkExt::close( &mut wsio ).await.expect( "close wsio sink" );
+
+		let evt = first.await;
+
+		assert_eq!( 1000, evt.code     );
+		assert!   ( evt.was_clean      );
+		assert_eq!( evt , second.await );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Verify closed resolves immediately when the connection is already closed.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn closed_after_close() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: closed_after_close" );
+
+	async
+	{
+		let (ws, _wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
+
+		let evt = ws.close_reason( 1000, "done" ).await.expect_throw( "close" );
+
+		assert_eq!( "done", evt.reason       );
+		assert_eq!( evt   , ws.closed().await );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Verify close doesn't hang when the connection is already closed.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn close_twice() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: close_twice" );
+
+	async
+	{
+		let (ws, _wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
+
+		let evt = ws.close().await;
+
+		assert_eq!( evt, ws.close().await );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Verify aborting a pending connection attempt through the AbortHandle.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn connect_abort() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: connect_abort" );
+
+	async
+	{
+		// A non routable address, so the connection stays pending.
+		//
+		let (connect, handle) = WsStream::connect_abortable( "ws://10.255.255.1:3212/", WsConfig::default() );
+
+		// join polls the connect future first, so the socket exists when we abort.
+		//
+		let (res, _) = join( connect, async move { handle.abort() } ).await;
+
+		assert_eq!( &WsErrKind::ConnectAborted, res.unwrap_err().kind() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Verify ready resolves once the buffer drained after a big send.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn ready_drained() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: ready_drained" );
+
+	async
+	{
+		let (ws, mut wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
+
+		ws.ready( 0 ).await.expect_throw( "ready on idle connection" );
+
+		wsio.send( WsMessage::Binary( vec![ 0; 4 * 1024 * 1024 ] ) ).await.expect_throw( "send" );
+
+		ws.ready( 0 ).await.expect_throw( "ready after send" );
+
+		assert_eq!( 0, ws.buffered_amount() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Verify ready fails when the connection closes while waiting, or is already closed.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn ready_closed() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: ready_closed" );
+
+	async
+	{
+		let (ws, mut wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
+
+		wsio.send( WsMessage::Binary( vec![ 0; 4 * 1024 * 1024 ] ) ).await.expect_throw( "send" );
+
+		// Created while the buffer is full, but only polled after the close started.
+		//
+		let ready = ws.ready( 0 );
+
+		ws.wrapped().close().expect_throw( "close" );
+
+		assert_eq!( &WsErrKind::ConnectionNotOpen, ready.await.unwrap_err().kind() );
+
+		ws.closed().await;
+
+		assert_eq!( &WsErrKind::ConnectionNotOpen, ws.ready( 0 ).await.unwrap_err().kind() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Verify close_event is None while open and is kept on both halves after the connection closed.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn close_event() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: close_event" );
+
+	async
+	{
+		let (ws, mut wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
+
+		assert_eq!( None, ws  .close_event() );
+		assert_eq!( None, wsio.close_event() );
+
+		let evt = ws.close_reason( 4321, "bye" ).await.expect_throw( "close" );
+
+		// The stream ended, but we can still find out why.
+		//
+		assert!( wsio.next().await.is_none() );
+
+		assert_eq!( Some( evt.clone() ), ws  .close_event() );
+		assert_eq!( Some( evt.clone() ), wsio.close_event() );
+		assert_eq!( 4321               , evt.code           );
+		assert_eq!( "bye"              , evt.reason         );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Verify clones share the connection and keep working after the original is dropped.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn clone_handle() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: clone_handle" );
+
+	async
+	{
+		let (ws, mut wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
+
+		let ws2    = ws.clone();
+		let mut tx = ws2.sender();
+		let events = ws2.events_unbounded();
+
+		drop( ws );
+
+		tx.send( WsMessage::from( vec![ 1, 2, 3 ] ) ).await.expect_throw( "send" );
+
+		assert_eq!( WsMessage::from( vec![ 1, 2, 3 ] ), wsio.next().await.unwrap_throw().expect_throw( "echo" ) );
+		assert_eq!( WsState::Open, ws2.ready_state() );
+
+		let evt = ws2.clone().close_code( 4000 ).await.expect_throw( "close" );
+
+		assert_eq!( 4000, evt.code );
+
+		// The listeners are still installed, so we saw the close event.
+		//
+		let evts: Vec<WsEvent> = events.take( 2 ).collect().await;
+
+		assert_eq!( vec![ WsEvent::Closing, WsEvent::Close( evt ) ], evts );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Verify closing from one clone while sending through another gives ConnectionNotOpen.
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn close_while_sending() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: close_while_sending" );
+
+	async
+	{
+		let (ws, _wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
+
+		let ws2    = ws.clone();
+		let mut tx = ws.sender();
+
+		// The sender is ready, then the other clone starts closing before the message goes out.
+		// join polls the close first.
+		//
+		poll_fn( |cx| Pin::new( &mut tx ).poll_ready( cx ) ).await.expect_throw( "ready" );
+
+		let ( _evt, sent ) = join( ws2.close(), async { Pin::new( &mut tx ).start_send( WsMessage::from( "late" ) ) } ).await;
+
+		assert_eq!( &WsErrKind::ConnectionNotOpen, sent.unwrap_err().kind() );
+
+
+		// And through the Sink API after the connection closed.
+		//
+		let res = ws.sender().send( WsMessage::from( "later" ) ).await;
+
+		assert_eq!( &WsErrKind::ConnectionNotOpen, res.unwrap_err().kind() );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
+
+
+
+// Verify state_signal starts with the current state, follows the close and then ends
+//
+#[ wasm_bindgen_test(async) ]
+//
+pub fn state_signal() -> impl Future01<Item = (), Error = JsValue>
+{
+	let _ = console_log::init_with_level( Level::Trace );
+
+	info!( "starting test: state_signal" );
+
+	async
+	{
+		let (ws, _wsio) = WsStream::connect( URL ).await.expect_throw( "Could not create websocket" );
+		let mut states  = ws.state_signal();
+
+		assert_eq!( Some( WsState::Open ), states.next().await );
+
+		let ( _evt, states ) = join( ws.close(), states.collect::<Vec<_>>() ).await;
+
+		assert_eq!( vec![ WsState::Closing, WsState::Closed ], states );
+
+		// After the close, the stream has the current state and ends.
+		//
+		assert_eq!( vec![ WsState::Closed ], ws.state_signal().collect::<Vec<_>>().await );
+
+		Ok(())
+
+	}.boxed_local().compat()
+}
@@ -0,0 +1,112 @@
+// What's tested:
+//
+// These don't need a backend, so they are regular tests.
+//
+// ✔ Keys and values are percent-encoded (spaces, '+', '&', unicode)
+// ✔ Repeated keys are preserved in order
+// ✔ Parameters are appended to an existing query string
+// ✔ A fragment is removed
+// ✔ Relative urls are left alone
+// ✔ Redacted parameters don't show up in redacted(), Display and Debug
+//
+use
+{
+	ws_stream_wasm :: * ,
+};
+
+
+
+// Keys and values are percent-encoded.
+//
+#[ test ]
+//
+fn encoding()
+{
+	let url = WsUrl::new( "ws://127.0.0.1:3212/" )
+
+		.param( "a b", "c+d" )
+		.param( "e&f", "g=h" )
+		.param( "ü"  , "😀"  )
+		.param( "ok" , "A-z_0.9~" )
+	;
+
+	assert_eq!
+	(
+		"ws://127.0.0.1:3212/?a%20b=c%2Bd&e%26f=g%3Dh&%C3%BC=%F0%9F%98%80&ok=A-z_0.9~",
+		url.url()
+	);
+}
+
+
+
+// Repeated keys are preserved in order.
+//
+#[ test ]
+//
+fn repeated_keys()
+{
+	let url = WsUrl::new( "ws://127.0.0.1:3212/" ).param( "a", "1" ).param( "b", "2" ).param( "a", "3" );
+
+	assert_eq!( "ws://127.0.0.1:3212/?a=1&b=2&a=3", url.url() );
+}
+
+
+
+// Parameters are appended to an existing query string.
+//
+#[ test ]
+//
+fn existing_query()
+{
+	assert_eq!( "ws://h/?x=1&a=2", WsUrl::new( "ws://h/?x=1"  ).param( "a", "2" ).url() );
+	assert_eq!( "ws://h/?x=1&a=2", WsUrl::new( "ws://h/?x=1&" ).param( "a", "2" ).url() );
+	assert_eq!( "ws://h/?a=2"    , WsUrl::new( "ws://h/?"     ).param( "a", "2" ).url() );
+	assert_eq!( "ws://h/"        , WsUrl::new( "ws://h/"      ).url()                   );
+}
+
+
+
+// A fragment is removed.
+//
+#[ test ]
+//
+fn fragment()
+{
+	assert_eq!( "ws://h/?a=2"       , WsUrl::new( "ws://h/#frag"     ).param( "a", "2" ).url()                     );
+	assert_eq!( "ws://h/?x=1&a=2"   , WsUrl::new( "ws://h/?x=1#frag" ).param( "a", "2" ).url()                     );
+	assert_eq!( "ws://h/"           , WsUrl::new( "ws://h/#"         ).url()                                       );
+	assert_eq!( "ws://h/?t=REDACTED", WsUrl::new( "ws://h/#frag"     ).param( "t", "s" ).redact( "t" ).redacted() );
+}
+
+
+
+// Relative urls are left alone.
+//
+#[ test ]
+//
+fn relative()
+{
+	assert_eq!( "/socket?room=main", WsUrl::new( "/socket" ).param( "room", "main" ).url() );
+}
+
+
+
+// Redacted parameters don't show up in redacted(), Display and Debug.
+//
+#[ test ]
+//
+fn redact()
+{
+	let url = WsUrl::new( "ws://h/" )
+
+		.param ( "token", "secret" )
+		.param ( "room" , "main"   )
+		.param ( "token", "other"  )
+		.redact( "token"           )
+	;
+
+	assert_eq!( "ws://h/?token=secret&room=main&token=other"    , url.url()                );
+	assert_eq!( "ws://h/?token=REDACTED&room=main&token=REDACTED", url.redacted()           );
+	assert_eq!( url.redacted()                                    , format!( "{}"  , url )   );
+	assert!   ( !format!( "{:?}", url ).contains( "secret" )                                );
+}